@@ -0,0 +1,94 @@
+//! Location annotations.
+//!
+//! An [`Annotator`] adds an event's distance and bearing from `--home` and
+//! its nearest gazetteer place. Annotations never decide whether an event
+//! passes; that is the [`EventFilter`](crate::filters::EventFilter)'s job, so
+//! callers annotate only the events they are about to write.
+
+use std::sync::Arc;
+
+use crate::filters::Home;
+use crate::gazetteer::Gazetteer;
+use crate::geodesic::DistanceModel;
+use crate::models::{Feature, Proximity};
+
+/// Distance, bearing and nearest-place annotations.
+#[derive(Debug, Default, Clone)]
+pub struct Annotator {
+    /// Reference point for distance annotations
+    pub home: Option<Home>,
+    /// Places for nearest-place annotations
+    pub gazetteer: Option<Arc<Gazetteer>>,
+    /// How annotation distances are measured
+    pub distance_model: DistanceModel,
+}
+
+impl Annotator {
+    /// Record the distance of `event` from home and its nearest place on it.
+    pub fn annotate(&self, event: &mut Feature) {
+        let (lat, lon) = (event.latitude(), event.longitude());
+        event.home = self
+            .home
+            .map(|home| Proximity::between(self.distance_model, home.lat, home.lon, lat, lon));
+        event.nearest_place = self
+            .gazetteer
+            .as_ref()
+            .and_then(|g| g.nearest(lat, lon, self.distance_model));
+    }
+
+    /// Distance of `event` from home in km, if there is a home.
+    #[must_use]
+    pub fn distance_km(&self, event: &Feature) -> Option<f64> {
+        self.home.map(|home| {
+            self.distance_model
+                .distance_km(home.lat, home.lon, event.latitude(), event.longitude())
+        })
+    }
+
+    /// Sort events nearest to home first, without annotating them.
+    pub fn sort_by_distance(&self, events: &mut Vec<Feature>) {
+        let mut keyed: Vec<(f64, Feature)> = events
+            .drain(..)
+            .map(|e| (self.distance_km(&e).unwrap_or(f64::INFINITY), e))
+            .collect();
+        keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+        events.extend(keyed.into_iter().map(|(_, e)| e));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FeatureCollection;
+    use crate::test_support::SAMPLE_FEED;
+
+    #[test]
+    fn test_sort_then_annotate() {
+        let annotator = Annotator {
+            home: Some("61.2,-149.9".parse().unwrap()), // Anchorage
+            gazetteer: Some(Arc::new(Gazetteer::embedded())),
+            ..Default::default()
+        };
+        let feed: FeatureCollection = serde_json::from_str(SAMPLE_FEED).unwrap();
+        let mut events = feed.features;
+
+        annotator.sort_by_distance(&mut events);
+        assert_eq!(events[0].id, "ak0247xyz1");
+        assert!(
+            events
+                .iter()
+                .all(|e| e.home.is_none() && e.nearest_place.is_none())
+        );
+        assert!(events.windows(2).all(|w| {
+            annotator.distance_km(&w[0]).unwrap() <= annotator.distance_km(&w[1]).unwrap()
+        }));
+
+        annotator.annotate(&mut events[0]);
+        let home = events[0].home.as_ref().unwrap();
+        assert_eq!(home.direction, "SW");
+        assert!((home.distance_km - annotator.distance_km(&events[0]).unwrap()).abs() < 0.1);
+        assert!(events[0].nearest_place.is_some());
+
+        assert!(Annotator::default().distance_km(&events[0]).is_none());
+    }
+}
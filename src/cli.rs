@@ -2,11 +2,29 @@
 //!
 //! Uses clap derive API for argument parsing.
 
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
 use clap::{Parser, Subcommand};
 
-use crate::client::FeedType;
-use crate::filters::{BBox, RadiusFilter};
-use crate::output::Format;
+use crate::annotate::Annotator;
+use crate::client::{FeedType, USGS_BASE_URL};
+use crate::emsc::{EMSC_BASE_URL, EMSC_WS_URL};
+use crate::expr::FilterExpr;
+use crate::filters::{BBox, EventFilter, Home, RadiusFilter, TimeBound};
+use crate::gazetteer::Gazetteer;
+use crate::geodesic::DistanceModel;
+use crate::geonet::GEONET_BASE_URL;
+use crate::models::SchemaVersion;
+use crate::output::{Format, SortKey};
+use crate::quality::QualityGrade;
+use crate::query::OrderBy;
+use crate::record;
+use crate::region::Region;
+use crate::source::SourceKind;
+use crate::types::{AlertLevel, EventStatus, EventType};
+use crate::zones::ZoneFile;
 
 /// Real-time earthquake monitoring from your terminal.
 #[derive(Parser, Debug)]
@@ -25,6 +43,26 @@ pub struct Cli {
     /// Suppress all output except errors
     #[arg(long, global = true)]
     pub quiet: bool,
+
+    /// Base URL of the USGS API (e.g. a local `mock-server`)
+    #[arg(long, global = true, default_value = USGS_BASE_URL)]
+    pub usgs_url: String,
+
+    /// Base URL of the EMSC `SeismicPortal` API
+    #[arg(long, global = true, default_value = EMSC_BASE_URL)]
+    pub emsc_url: String,
+
+    /// WebSocket URL of the EMSC real-time stream (used by `live --push`)
+    #[arg(long, global = true, default_value = EMSC_WS_URL)]
+    pub emsc_ws_url: String,
+
+    /// Base URL of the `GeoNet` API
+    #[arg(long, global = true, default_value = GEONET_BASE_URL)]
+    pub geonet_url: String,
+
+    /// Attempts per USGS, EMSC or `GeoNet` request before giving up (1 = no retries)
+    #[arg(long, global = true, default_value = "3", value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_attempts: u32,
 }
 
 /// Available commands.
@@ -39,20 +77,35 @@ pub enum Command {
     /// Query historical earthquakes
     Query(QueryArgs),
 
+    /// Show one event with its products (moment tensor, PAGER, ShakeMap, ...)
+    Event(EventArgs),
+
     /// Start the web UI server
     Ui(UiArgs),
 
     /// Run EEW detection demo on OpenEEW data
     Detect(DetectArgs),
+
+    /// Serve recorded USGS feeds locally for offline testing
+    MockServer(MockServerArgs),
 }
 
 /// Arguments for the `tail` command.
 #[derive(Parser, Debug)]
 pub struct TailArgs {
+    /// Data source: usgs, emsc (Euro-Mediterranean) or geonet (New Zealand)
+    #[arg(long, default_value = "usgs", value_parser = parse_source)]
+    pub source: SourceKind,
+
     /// Feed type to fetch
     #[arg(long, default_value = "2.5_day", value_parser = parse_feed_type)]
     pub feed: FeedType,
 
+    /// Read a GeoJSON feed dump from a file, a directory of snapshots or
+    /// stdin (-) instead of fetching
+    #[arg(long, conflicts_with_all = ["source", "feed"])]
+    pub input: Option<PathBuf>,
+
     /// Minimum magnitude to show
     #[arg(long)]
     pub min_magnitude: Option<f64>,
@@ -61,34 +114,67 @@ pub struct TailArgs {
     #[arg(long)]
     pub max_depth: Option<f64>,
 
-    /// Bounding box filter: minlat,minlon,maxlat,maxlon
+    /// Bounding box filter: minlat,minlon,maxlat,maxlon (minlon > maxlon wraps across ±180°)
     #[arg(long, value_parser = parse_bbox)]
     pub bbox: Option<BBox>,
 
-    /// Radius filter: lat,lon,radius_km
+    /// Radius filter: `lat,lon,radius_km`
     #[arg(long, value_parser = parse_radius)]
     pub radius: Option<RadiusFilter>,
 
+    /// Polygon region file (GeoJSON or WKT), optionally named: `[NAME=]FILE`.
+    /// Repeatable; events must lie in one of the regions
+    #[arg(long, value_name = "[NAME=]FILE", value_parser = parse_region)]
+    pub region: Vec<Region>,
+
     /// Only show significant events (with alert level)
     #[arg(long)]
     pub significant: bool,
 
+    /// Filter expression, e.g. 'mag >= 4.5 && (alert >= yellow || tsunami) && place ~ "Alaska"'
+    #[arg(long = "where", value_name = "EXPR", value_parser = parse_where)]
+    pub filter_expr: Option<FilterExpr>,
+
+    #[command(flatten)]
+    pub filters: FilterArgs,
+
+    #[command(flatten)]
+    pub location: LocationArgs,
+
     /// Maximum number of events to show
     #[arg(long, short = 'n', default_value = "50")]
     pub limit: usize,
 
+    /// Sort order: time (newest first), magnitude (largest first) or
+    /// distance (nearest to --home first)
+    #[arg(long, default_value = "time", value_parser = parse_sort)]
+    pub sort: SortKey,
+
     /// Output format
     #[arg(long, short = 'f', default_value = "human", value_parser = parse_format)]
     pub format: Format,
+
+    /// JSON/NDJSON event schema: v1 (original fields) or v2 (all fields)
+    #[arg(long, default_value = "v1", value_parser = parse_schema)]
+    pub schema: SchemaVersion,
 }
 
 /// Arguments for the `live` command.
 #[derive(Parser, Debug)]
 pub struct LiveArgs {
+    /// Data source: usgs, emsc (Euro-Mediterranean) or geonet (New Zealand)
+    #[arg(long, default_value = "usgs", value_parser = parse_source)]
+    pub source: SourceKind,
+
     /// Feed type to stream
     #[arg(long, default_value = "all_hour", value_parser = parse_feed_type)]
     pub feed: FeedType,
 
+    /// Read GeoJSON feed dumps from a file, a directory of snapshots (new
+    /// files are picked up on each poll) or stdin (-) instead of fetching
+    #[arg(long, conflicts_with_all = ["source", "feed", "push"])]
+    pub input: Option<PathBuf>,
+
     /// Minimum magnitude to show
     #[arg(long)]
     pub min_magnitude: Option<f64>,
@@ -97,37 +183,234 @@ pub struct LiveArgs {
     #[arg(long)]
     pub max_depth: Option<f64>,
 
-    /// Bounding box filter: minlat,minlon,maxlat,maxlon
+    /// Bounding box filter: minlat,minlon,maxlat,maxlon (minlon > maxlon wraps across ±180°)
     #[arg(long, value_parser = parse_bbox)]
     pub bbox: Option<BBox>,
 
-    /// Radius filter: lat,lon,radius_km
+    /// Radius filter: `lat,lon,radius_km`
     #[arg(long, value_parser = parse_radius)]
     pub radius: Option<RadiusFilter>,
 
+    /// Polygon region file (GeoJSON or WKT), optionally named: `[NAME=]FILE`.
+    /// Repeatable; events must lie in one of the regions
+    #[arg(long, value_name = "[NAME=]FILE", value_parser = parse_region)]
+    pub region: Vec<Region>,
+
     /// Only show significant events (with alert level)
     #[arg(long)]
     pub significant: bool,
 
+    /// Filter expression, e.g. 'mag >= 4.5 && (alert >= yellow || tsunami) && place ~ "Alaska"'
+    #[arg(long = "where", value_name = "EXPR", value_parser = parse_where)]
+    pub filter_expr: Option<FilterExpr>,
+
+    #[command(flatten)]
+    pub filters: FilterArgs,
+
+    #[command(flatten)]
+    pub location: LocationArgs,
+
     /// Poll interval in seconds (minimum 30)
     #[arg(long, default_value = "60")]
     pub poll_interval: u64,
 
+    /// Record every poll (fetch time, outcome and feed snapshot) into this directory
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["replay", "push"])]
+    pub record: Option<PathBuf>,
+
+    /// Replay polls recorded with --record instead of fetching
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["source", "feed", "input", "push"])]
+    pub replay: Option<PathBuf>,
+
+    /// Replay speed, e.g. 10x (with --replay)
+    #[arg(long, default_value = "1x", requires = "replay", value_parser = parse_speed)]
+    pub speed: f64,
+
+    /// Receive events pushed over the EMSC WebSocket instead of polling
+    /// (requires --source emsc; --feed and --poll-interval are ignored)
+    #[arg(long)]
+    pub push: bool,
+
     /// Output format
     #[arg(long, short = 'f', default_value = "human", value_parser = parse_format)]
     pub format: Format,
+
+    /// JSON/NDJSON event schema: v1 (original fields) or v2 (all fields)
+    #[arg(long, default_value = "v1", value_parser = parse_schema)]
+    pub schema: SchemaVersion,
+}
+
+/// Attribute filters shared by `tail`, `live` and `ui`.
+#[derive(clap::Args, Debug)]
+pub struct FilterArgs {
+    /// Maximum magnitude to show
+    #[arg(long)]
+    pub max_magnitude: Option<f64>,
+
+    /// Minimum depth in km to show
+    #[arg(long)]
+    pub min_depth: Option<f64>,
+
+    /// Only show events at or after this time: relative (30m, 2h, 7d, 1w) or absolute
+    #[arg(long, value_name = "TIME", value_parser = parse_time_bound)]
+    pub since: Option<TimeBound>,
+
+    /// Only show events at or before this time: relative (30m, 2h, 7d, 1w) or absolute
+    #[arg(long, value_name = "TIME", value_parser = parse_time_bound)]
+    pub until: Option<TimeBound>,
+
+    /// Only show events from these networks, e.g. us,ak
+    #[arg(long = "net", value_name = "NETS", value_delimiter = ',')]
+    pub networks: Vec<String>,
+
+    /// Hide events from these networks
+    #[arg(long = "exclude-net", value_name = "NETS", value_delimiter = ',')]
+    pub exclude_networks: Vec<String>,
+
+    /// Only show events that any of these networks contributed to
+    #[arg(long, value_name = "NETS", value_delimiter = ',')]
+    pub sources: Vec<String>,
+
+    /// Hide events that any of these networks contributed to
+    #[arg(long, value_name = "NETS", value_delimiter = ',')]
+    pub exclude_sources: Vec<String>,
+
+    /// Review status: reviewed or automatic
+    #[arg(long, value_parser = parse_status)]
+    pub status: Option<EventStatus>,
+
+    /// Only show these event types, e.g. earthquake
+    #[arg(long = "type", value_name = "TYPES", value_delimiter = ',', value_parser = parse_event_type)]
+    pub event_types: Vec<EventType>,
+
+    /// Hide these event types, e.g. 'quarry blast,explosion'
+    #[arg(long = "exclude-type", value_name = "TYPES", value_delimiter = ',', value_parser = parse_event_type)]
+    pub exclude_event_types: Vec<EventType>,
+
+    /// Only show events flagged for tsunami potential
+    #[arg(long)]
+    pub tsunami: bool,
+
+    /// Minimum PAGER alert level: green, yellow, orange or red
+    #[arg(long, value_parser = parse_alert)]
+    pub min_alert: Option<AlertLevel>,
+
+    /// Minimum number of "Did You Feel It?" reports
+    #[arg(long)]
+    pub min_felt: Option<i32>,
+
+    /// Minimum ShakeMap intensity (MMI)
+    #[arg(long)]
+    pub min_mmi: Option<f64>,
+
+    /// Minimum community intensity (CDI)
+    #[arg(long)]
+    pub min_cdi: Option<f64>,
+
+    /// Maximum azimuthal gap in degrees
+    #[arg(long)]
+    pub max_gap: Option<f64>,
+
+    /// Maximum travel-time RMS residual in seconds
+    #[arg(long)]
+    pub max_rms: Option<f64>,
+
+    /// Minimum number of stations used to locate the event
+    #[arg(long)]
+    pub min_stations: Option<i32>,
+
+    /// Worst location quality grade to show: A (best) to D
+    #[arg(long, value_parser = parse_quality)]
+    pub min_quality: Option<QualityGrade>,
+
+    /// Watch zones file (JSON list of named circles, boxes or polygons, each
+    /// with its own thresholds). Repeatable; events must trigger a zone
+    #[arg(long, value_name = "FILE", value_parser = parse_zones)]
+    pub zones: Vec<ZoneFile>,
+}
+
+impl FilterArgs {
+    /// Build an event filter holding these criteria.
+    #[must_use]
+    pub fn to_filter(&self) -> EventFilter {
+        EventFilter {
+            max_magnitude: self.max_magnitude,
+            min_depth: self.min_depth,
+            since: self.since,
+            until: self.until,
+            networks: self.networks.clone(),
+            exclude_networks: self.exclude_networks.clone(),
+            sources: self.sources.clone(),
+            exclude_sources: self.exclude_sources.clone(),
+            status: self.status.clone(),
+            event_types: self.event_types.clone(),
+            exclude_event_types: self.exclude_event_types.clone(),
+            tsunami_only: self.tsunami,
+            min_alert: self.min_alert.clone(),
+            min_felt: self.min_felt,
+            min_mmi: self.min_mmi,
+            min_cdi: self.min_cdi,
+            max_gap: self.max_gap,
+            max_rms: self.max_rms,
+            min_stations: self.min_stations,
+            min_quality: self.min_quality,
+            zones: self
+                .zones
+                .iter()
+                .flat_map(|f| f.0.iter().cloned())
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Location annotations shared by `tail`, `live` and `ui`.
+#[derive(clap::Args, Debug)]
+pub struct LocationArgs {
+    /// Home location `lat,lon`: show each event's distance and bearing from it
+    #[arg(long, value_name = "LAT,LON", value_parser = parse_home)]
+    pub home: Option<Home>,
+
+    /// Show the nearest populated place from the built-in gazetteer
+    #[arg(long)]
+    pub nearest: bool,
+
+    /// Places file for --nearest: a GeoNames dump such as cities15000.txt, or
+    /// tab-separated name, country, lat, lon (implies --nearest)
+    #[arg(long, value_name = "FILE", value_parser = parse_gazetteer)]
+    pub gazetteer: Option<Arc<Gazetteer>>,
+
+    /// Distance model for --radius, --home, --nearest and zone circles:
+    /// haversine (sphere) or wgs84 (ellipsoid)
+    #[arg(long, default_value = "haversine", value_parser = parse_distance_model)]
+    pub distance_model: DistanceModel,
+}
+
+impl LocationArgs {
+    /// Build the annotator for these options.
+    #[must_use]
+    pub fn annotator(&self) -> Annotator {
+        Annotator {
+            home: self.home,
+            gazetteer: self
+                .gazetteer
+                .clone()
+                .or_else(|| self.nearest.then(|| Arc::new(Gazetteer::embedded()))),
+            distance_model: self.distance_model,
+        }
+    }
 }
 
 /// Arguments for the `query` command.
 #[derive(Parser, Debug)]
 pub struct QueryArgs {
     /// Start date (YYYY-MM-DD or ISO8601)
-    #[arg(long)]
-    pub start: String,
+    #[arg(long, value_parser = parse_time)]
+    pub start: DateTime<Utc>,
 
     /// End date (YYYY-MM-DD or ISO8601, defaults to now)
-    #[arg(long)]
-    pub end: Option<String>,
+    #[arg(long, value_parser = parse_time)]
+    pub end: Option<DateTime<Utc>>,
 
     /// Minimum magnitude
     #[arg(long)]
@@ -137,13 +420,52 @@ pub struct QueryArgs {
     #[arg(long)]
     pub max_magnitude: Option<f64>,
 
-    /// Maximum results to return
+    /// Minimum depth in km
+    #[arg(long)]
+    pub min_depth: Option<f64>,
+
+    /// Maximum depth in km
+    #[arg(long)]
+    pub max_depth: Option<f64>,
+
+    /// Bounding box filter: minlat,minlon,maxlat,maxlon (minlon > maxlon wraps across ±180°)
+    #[arg(long, value_parser = parse_bbox, conflicts_with = "radius")]
+    pub bbox: Option<BBox>,
+
+    /// Radius filter: `lat,lon,radius_km`
+    #[arg(long, value_parser = parse_radius)]
+    pub radius: Option<RadiusFilter>,
+
+    /// Result ordering (time, time-asc, magnitude, magnitude-asc)
+    #[arg(long, default_value = "time", value_parser = parse_order_by)]
+    pub order_by: OrderBy,
+
+    /// Maximum results to return (0 = no limit; large results are fetched in time windows)
     #[arg(long, default_value = "100")]
     pub limit: usize,
 
     /// Output format
     #[arg(long, short = 'f', default_value = "human", value_parser = parse_format)]
     pub format: Format,
+
+    /// JSON/NDJSON event schema: v1 (original fields) or v2 (all fields)
+    #[arg(long, default_value = "v1", value_parser = parse_schema)]
+    pub schema: SchemaVersion,
+}
+
+/// Arguments for the `event` command.
+#[derive(Parser, Debug)]
+pub struct EventArgs {
+    /// Event ID (e.g. us7000n1a2)
+    pub id: String,
+
+    /// Output format
+    #[arg(long, short = 'f', default_value = "human", value_parser = parse_format)]
+    pub format: Format,
+
+    /// JSON/NDJSON event schema: v1 (original fields) or v2 (all fields)
+    #[arg(long, default_value = "v1", value_parser = parse_schema)]
+    pub schema: SchemaVersion,
 }
 
 /// Arguments for the `ui` command.
@@ -157,6 +479,10 @@ pub struct UiArgs {
     #[arg(long, default_value = "127.0.0.1")]
     pub host: String,
 
+    /// Data source: usgs, emsc (Euro-Mediterranean) or geonet (New Zealand)
+    #[arg(long, default_value = "usgs", value_parser = parse_source)]
+    pub source: SourceKind,
+
     /// Feed type to stream
     #[arg(long, default_value = "all_hour", value_parser = parse_feed_type)]
     pub feed: FeedType,
@@ -165,10 +491,29 @@ pub struct UiArgs {
     #[arg(long, default_value = "60")]
     pub poll_interval: u64,
 
+    /// Record every poll (fetch time, outcome and feed snapshot) into this directory
+    #[arg(long, value_name = "DIR")]
+    pub record: Option<PathBuf>,
+
     /// Minimum magnitude to show
     #[arg(long)]
     pub min_magnitude: Option<f64>,
 
+    /// Filter expression, e.g. 'mag >= 4.5 && (alert >= yellow || tsunami) && place ~ "Alaska"'
+    #[arg(long = "where", value_name = "EXPR", value_parser = parse_where)]
+    pub filter_expr: Option<FilterExpr>,
+
+    #[command(flatten)]
+    pub filters: FilterArgs,
+
+    #[command(flatten)]
+    pub location: LocationArgs,
+
+    /// Polygon region file (GeoJSON or WKT), optionally named: `[NAME=]FILE`.
+    /// Repeatable; events must lie in one of the regions
+    #[arg(long, value_name = "[NAME=]FILE", value_parser = parse_region)]
+    pub region: Vec<Region>,
+
     /// Open browser automatically
     #[arg(long)]
     pub open: bool,
@@ -198,16 +543,132 @@ pub struct DetectArgs {
     pub threshold: f32,
 }
 
+/// Arguments for the `mock-server` command.
+#[derive(Parser, Debug)]
+pub struct MockServerArgs {
+    /// Directory of recorded feeds (`<feed>.geojson`, e.g. `all_hour.geojson`)
+    #[arg(long, default_value = "tools")]
+    pub dir: PathBuf,
+
+    /// Port to listen on
+    #[arg(long, short = 'p', default_value = "8081")]
+    pub port: u16,
+
+    /// Host to bind to
+    #[arg(long, default_value = "127.0.0.1")]
+    pub host: String,
+
+    /// Add and revise events every N seconds (default: serve feeds unchanged)
+    #[arg(long)]
+    pub mutate_every: Option<u64>,
+
+    /// Seed for the mutation sequence (same seed, same mutations)
+    #[arg(long, default_value = "42")]
+    pub seed: u64,
+}
+
 /// Parse a feed type from string.
 fn parse_feed_type(s: &str) -> Result<FeedType, String> {
     s.parse()
 }
 
+/// Parse a data source from string.
+fn parse_source(s: &str) -> Result<SourceKind, String> {
+    s.parse()
+}
+
+/// Parse a replay speed such as `10x`.
+fn parse_speed(s: &str) -> Result<f64, String> {
+    record::parse_speed(s)
+}
+
 /// Parse an output format from string.
 fn parse_format(s: &str) -> Result<Format, String> {
     s.parse()
 }
 
+/// Parse an output schema version from string.
+fn parse_schema(s: &str) -> Result<SchemaVersion, String> {
+    s.parse()
+}
+
+/// Parse a `--where` filter expression.
+fn parse_where(s: &str) -> Result<FilterExpr, String> {
+    s.parse()
+}
+
+/// Parse a `--since`/`--until` bound.
+fn parse_time_bound(s: &str) -> Result<TimeBound, String> {
+    s.parse()
+}
+
+/// Parse a review status, rejecting unknown values.
+fn parse_status(s: &str) -> Result<EventStatus, String> {
+    match EventStatus::from(s) {
+        EventStatus::Unknown(_) => Err(format!(
+            "invalid status '{s}' (expected reviewed or automatic)"
+        )),
+        status => Ok(status),
+    }
+}
+
+/// Parse an event type (`quarry-blast` or `quarry blast`), rejecting unknown values.
+fn parse_event_type(s: &str) -> Result<EventType, String> {
+    match EventType::from(s.trim().replace(['-', '_'], " ").as_str()) {
+        EventType::Unknown(_) => Err(format!("unknown event type '{s}'")),
+        event_type => Ok(event_type),
+    }
+}
+
+/// Parse a PAGER alert level, rejecting unknown values.
+fn parse_alert(s: &str) -> Result<AlertLevel, String> {
+    let level = AlertLevel::from(s);
+    if level.is_known() {
+        Ok(level)
+    } else {
+        Err(format!(
+            "invalid alert level '{s}' (expected green, yellow, orange or red)"
+        ))
+    }
+}
+
+/// Parse a `--home` location.
+fn parse_home(s: &str) -> Result<Home, String> {
+    s.parse()
+}
+
+/// Load a `--gazetteer` file.
+fn parse_gazetteer(s: &str) -> Result<Arc<Gazetteer>, String> {
+    Gazetteer::load(Path::new(s))
+        .map(Arc::new)
+        .map_err(|e| e.to_string())
+}
+
+/// Parse a distance model from string.
+fn parse_distance_model(s: &str) -> Result<DistanceModel, String> {
+    s.parse()
+}
+
+/// Parse a `tail` sort order from string.
+fn parse_sort(s: &str) -> Result<SortKey, String> {
+    s.parse()
+}
+
+/// Parse a location quality grade from string.
+fn parse_quality(s: &str) -> Result<QualityGrade, String> {
+    s.parse()
+}
+
+/// Load a `--zones` file.
+fn parse_zones(s: &str) -> Result<ZoneFile, String> {
+    ZoneFile::load(Path::new(s)).map_err(|e| e.to_string())
+}
+
+/// Load a `--region` file.
+fn parse_region(s: &str) -> Result<Region, String> {
+    Region::load(s).map_err(|e| e.to_string())
+}
+
 /// Parse a bounding box from string.
 fn parse_bbox(s: &str) -> Result<BBox, String> {
     s.parse()
@@ -217,3 +678,13 @@ fn parse_bbox(s: &str) -> Result<BBox, String> {
 fn parse_radius(s: &str) -> Result<RadiusFilter, String> {
     s.parse()
 }
+
+/// Parse a query ordering from string.
+fn parse_order_by(s: &str) -> Result<OrderBy, String> {
+    s.parse()
+}
+
+/// Parse an absolute time from string.
+fn parse_time(s: &str) -> Result<DateTime<Utc>, String> {
+    crate::query::parse_time(s)
+}
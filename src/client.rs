@@ -1,15 +1,29 @@
 //! USGS Earthquake API client.
 //!
-//! Provides blocking HTTP access to USGS earthquake feeds.
-//! Uses reqwest with rustls for TLS.
+//! Provides blocking HTTP access to the FDSN event web service and event
+//! details, plus an async client for polling the summary feeds.
+//! Uses reqwest with rustls for TLS. Transient failures are retried per
+//! the client's [`RetryPolicy`].
 
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use reqwest::blocking::Client;
-use tracing::{debug, instrument};
+use chrono::TimeDelta;
+use reqwest::StatusCode;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::{
+    CONTENT_TYPE, ETAG, HeaderMap, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RETRY_AFTER,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument, warn};
 
+use crate::detail::EventDetail;
 use crate::errors::SeismotailError;
-use crate::models::FeatureCollection;
+use crate::models::{BodyFormat, CountResponse, FeatureCollection};
+use crate::quakeml;
+use crate::query::EventQuery;
+use crate::retry::{RetryPolicy, parse_retry_after};
 
 /// Default request timeout in seconds.
 const REQUEST_TIMEOUT_SECS: u64 = 10;
@@ -18,10 +32,14 @@ const REQUEST_TIMEOUT_SECS: u64 = 10;
 const USER_AGENT: &str = concat!("seismotail/", env!("CARGO_PKG_VERSION"));
 
 /// USGS base URL for earthquake feeds.
-const USGS_BASE_URL: &str = "https://earthquake.usgs.gov";
+pub const USGS_BASE_URL: &str = "https://earthquake.usgs.gov";
+
+/// Path of the FDSN event web service below the base URL.
+const FDSN_EVENT_PATH: &str = "/fdsnws/event/1";
 
 /// Available feed types for summary feeds.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub enum FeedType {
     AllHour,
     AllDay,
@@ -46,6 +64,30 @@ pub enum FeedType {
 }
 
 impl FeedType {
+    /// Every summary feed, in declaration order.
+    pub const ALL: [Self; 20] = [
+        Self::AllHour,
+        Self::AllDay,
+        Self::AllWeek,
+        Self::AllMonth,
+        Self::Mag1Hour,
+        Self::Mag1Day,
+        Self::Mag1Week,
+        Self::Mag1Month,
+        Self::Mag25Hour,
+        Self::Mag25Day,
+        Self::Mag25Week,
+        Self::Mag25Month,
+        Self::Mag45Hour,
+        Self::Mag45Day,
+        Self::Mag45Week,
+        Self::Mag45Month,
+        Self::SignificantHour,
+        Self::SignificantDay,
+        Self::SignificantWeek,
+        Self::SignificantMonth,
+    ];
+
     /// Get the URL path segment for this feed type.
     #[must_use]
     pub const fn as_str(self) -> &'static str {
@@ -72,6 +114,54 @@ impl FeedType {
             Self::SignificantMonth => "significant_month",
         }
     }
+
+    /// Time span covered by the feed.
+    #[must_use]
+    pub const fn window(self) -> TimeDelta {
+        match self {
+            Self::AllHour
+            | Self::Mag1Hour
+            | Self::Mag25Hour
+            | Self::Mag45Hour
+            | Self::SignificantHour => TimeDelta::hours(1),
+            Self::AllDay
+            | Self::Mag1Day
+            | Self::Mag25Day
+            | Self::Mag45Day
+            | Self::SignificantDay => TimeDelta::days(1),
+            Self::AllWeek
+            | Self::Mag1Week
+            | Self::Mag25Week
+            | Self::Mag45Week
+            | Self::SignificantWeek => TimeDelta::days(7),
+            Self::AllMonth
+            | Self::Mag1Month
+            | Self::Mag25Month
+            | Self::Mag45Month
+            | Self::SignificantMonth => TimeDelta::days(30),
+        }
+    }
+
+    /// Magnitude threshold of the feed, for providers without summary feeds.
+    ///
+    /// USGS significance is not available elsewhere, so the significant
+    /// feeds map to M4.5+.
+    #[must_use]
+    pub const fn min_magnitude(self) -> Option<f64> {
+        match self {
+            Self::AllHour | Self::AllDay | Self::AllWeek | Self::AllMonth => None,
+            Self::Mag1Hour | Self::Mag1Day | Self::Mag1Week | Self::Mag1Month => Some(1.0),
+            Self::Mag25Hour | Self::Mag25Day | Self::Mag25Week | Self::Mag25Month => Some(2.5),
+            Self::Mag45Hour
+            | Self::Mag45Day
+            | Self::Mag45Week
+            | Self::Mag45Month
+            | Self::SignificantHour
+            | Self::SignificantDay
+            | Self::SignificantWeek
+            | Self::SignificantMonth => Some(4.5),
+        }
+    }
 }
 
 impl std::str::FromStr for FeedType {
@@ -104,10 +194,96 @@ impl std::str::FromStr for FeedType {
     }
 }
 
+impl TryFrom<String> for FeedType {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<FeedType> for String {
+    fn from(v: FeedType) -> Self {
+        v.as_str().to_string()
+    }
+}
+
+/// Result of a conditional feed fetch.
+#[derive(Debug)]
+pub enum FeedFetch {
+    /// The feed changed since the previous conditional fetch
+    Modified(FeatureCollection),
+    /// The feed is unchanged (HTTP 304, or the same `metadata.generated`)
+    NotModified,
+}
+
+/// Cache validators remembered from the last response of a feed.
+#[derive(Debug, Clone, Default)]
+struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// `metadata.generated` of the last feed body
+    generated: Option<i64>,
+}
+
+/// Per-feed validator store shared by clones of the async client.
+#[derive(Debug, Default)]
+struct ValidatorCache {
+    feeds: Mutex<HashMap<FeedType, Validators>>,
+}
+
+impl ValidatorCache {
+    /// Conditional request headers for the next fetch of `feed`.
+    fn request_headers(&self, feed: FeedType) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let Ok(feeds) = self.feeds.lock() else {
+            return headers;
+        };
+        let Some(v) = feeds.get(&feed) else {
+            return headers;
+        };
+
+        if let Some(etag) = v.etag.as_deref().and_then(|e| e.parse().ok()) {
+            headers.insert(IF_NONE_MATCH, etag);
+        }
+        if let Some(lm) = v.last_modified.as_deref().and_then(|l| l.parse().ok()) {
+            headers.insert(IF_MODIFIED_SINCE, lm);
+        }
+        headers
+    }
+
+    /// Remember the validators of a fresh response.
+    ///
+    /// Returns `false` if the body was generated at the same instant as the
+    /// previous one, i.e. the feed did not actually change.
+    fn record(&self, feed: FeedType, headers: &HeaderMap, generated: i64) -> bool {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|v: &reqwest::header::HeaderValue| v.to_str().ok())
+                .map(String::from)
+        };
+        let Ok(mut feeds) = self.feeds.lock() else {
+            return true;
+        };
+
+        let previous = feeds.insert(
+            feed,
+            Validators {
+                etag: header(ETAG),
+                last_modified: header(LAST_MODIFIED),
+                generated: Some(generated),
+            },
+        );
+        previous.and_then(|p| p.generated) != Some(generated)
+    }
+}
+
 /// Client for USGS earthquake API.
 pub struct UsgsClient {
     client: Client,
     base_url: String,
+    retry: RetryPolicy,
 }
 
 impl UsgsClient {
@@ -117,6 +293,15 @@ impl UsgsClient {
     ///
     /// Returns an error if the HTTP client cannot be initialized.
     pub fn new() -> Result<Self, SeismotailError> {
+        Self::with_base_url(USGS_BASE_URL)
+    }
+
+    /// Create a client against an alternate USGS-compatible host.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be initialized.
+    pub fn with_base_url(base_url: &str) -> Result<Self, SeismotailError> {
         let client = Client::builder()
             .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
             .user_agent(USER_AGENT)
@@ -124,48 +309,378 @@ impl UsgsClient {
 
         Ok(Self {
             client,
-            base_url: USGS_BASE_URL.to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            retry: RetryPolicy::default(),
         })
     }
 
-    /// Fetch a summary GeoJSON feed.
+    /// Use a different retry policy (the default retries 3 times).
+    #[must_use]
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Send a request, retrying transient failures per the retry policy.
+    ///
+    /// `build` is called once per attempt. Success and 304 responses are
+    /// returned; any other status becomes an error.
+    fn send(&self, build: impl Fn() -> RequestBuilder) -> Result<Response, SeismotailError> {
+        let mut attempt = 1;
+        loop {
+            let err = match build().send() {
+                Ok(response) => match check_status(response) {
+                    Ok(response) => return Ok(response),
+                    Err(e) => e,
+                },
+                Err(e) => send_error(e),
+            };
+            let Some(delay) = self.retry.next_delay(attempt, &err) else {
+                return Err(err);
+            };
+            warn!(
+                "attempt {} failed, retrying in {:?}: {}",
+                attempt, delay, err
+            );
+            std::thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+
+    /// Fetch the detail GeoJSON (with all products) of a single event.
     ///
     /// # Errors
     ///
-    /// Returns an error if the request fails or response cannot be parsed.
-    #[instrument(skip(self), fields(feed = feed_type.as_str()))]
-    pub fn fetch_feed(&self, feed_type: FeedType) -> Result<FeatureCollection, SeismotailError> {
+    /// Returns an error if the ID is malformed, the event does not exist
+    /// (HTTP 404) or the response cannot be parsed.
+    #[instrument(skip(self))]
+    pub fn fetch_event_detail(&self, id: &str) -> Result<EventDetail, SeismotailError> {
+        // IDs are network code + event code; reject anything that could
+        // escape the URL path
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(SeismotailError::Validation(format!(
+                "invalid event ID: {id:?}"
+            )));
+        }
         let url = format!(
-            "{}/earthquakes/feed/v1.0/summary/{}.geojson",
-            self.base_url,
-            feed_type.as_str()
+            "{}/earthquakes/feed/v1.0/detail/{}.geojson",
+            self.base_url, id
         );
 
-        debug!("fetching feed from {}", url);
+        debug!("fetching event detail from {}", url);
 
-        let response = self.client.get(&url).send()?;
+        let detail: EventDetail = self.send(|| self.client.get(&url))?.json()?;
+        detail.validate()?;
 
-        // Check status before parsing
-        let status = response.status();
-        if !status.is_success() {
-            let body = response.text().unwrap_or_default();
-            return Err(SeismotailError::Api {
-                status: status.as_u16(),
-                message: body,
-            });
+        debug!("fetched {} product types", detail.properties.products.len());
+        Ok(detail)
+    }
+
+    /// Search the event catalog through the FDSN `query` endpoint.
+    ///
+    /// Services that answer with QuakeML instead of GeoJSON are parsed
+    /// into the same model.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or response cannot be parsed.
+    #[instrument(skip_all)]
+    pub fn query(&self, query: &EventQuery) -> Result<FeatureCollection, SeismotailError> {
+        let url = format!("{}{}/query", self.base_url, FDSN_EVENT_PATH);
+
+        debug!("querying {} with {:?}", url, query);
+
+        let params = query.to_params();
+        let response = self.send(|| {
+            self.client
+                .get(&url)
+                .query(&[("format", "geojson")])
+                .query(&params)
+        })?;
+
+        // FDSN signals "no matching events" with 204 No Content
+        if response.status() == StatusCode::NO_CONTENT {
+            return Ok(FeatureCollection::empty());
         }
 
-        let feed: FeatureCollection = response.json()?;
+        let is_xml = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.contains("xml"));
+        let feed = if is_xml {
+            quakeml::parse_quakeml(&response.text()?)?
+        } else {
+            FeatureCollection::from_json(&response.text()?)?
+        };
+
+        debug!("query returned {} events", feed.features.len());
+        Ok(feed)
+    }
+
+    /// Count matching events through the FDSN `count` endpoint.
+    ///
+    /// Ordering and limit are ignored; the count covers the whole match.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or response cannot be parsed.
+    #[instrument(skip_all)]
+    pub fn count(&self, query: &EventQuery) -> Result<CountResponse, SeismotailError> {
+        let url = format!("{}{}/count", self.base_url, FDSN_EVENT_PATH);
+        let unlimited = EventQuery {
+            limit: None,
+            ..query.clone()
+        };
+
+        debug!("counting {} with {:?}", url, unlimited);
+
+        let params = unlimited.to_params();
+        let response = self.send(|| {
+            self.client
+                .get(&url)
+                .query(&[("format", "geojson")])
+                .query(&params)
+        })?;
+        let count: CountResponse = response.json()?;
+
+        debug!("count returned {}", count.count);
+        Ok(count)
+    }
+}
+
+/// Async client for USGS earthquake feeds.
+///
+/// Same feed API as [`UsgsClient`], for use inside the tokio runtime (the
+/// web server). Cloning is cheap and shares the underlying connection pool
+/// and the conditional-request validators.
+#[derive(Clone)]
+pub struct AsyncUsgsClient {
+    client: reqwest::Client,
+    base_url: String,
+    validators: Arc<ValidatorCache>,
+    retry: RetryPolicy,
+}
+
+impl AsyncUsgsClient {
+    /// Create an async client against a USGS-compatible host
+    /// (normally [`USGS_BASE_URL`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be initialized.
+    pub fn with_base_url(base_url: &str) -> Result<Self, SeismotailError> {
+        Ok(Self {
+            client: async_http_client()?,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            validators: Arc::new(ValidatorCache::default()),
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    /// Use a different retry policy (the default retries 3 times).
+    #[must_use]
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Send a request, retrying transient failures per the retry policy.
+    async fn send(
+        &self,
+        build: impl Fn() -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, SeismotailError> {
+        send_async(&self.retry, build).await
+    }
+
+    /// Fetch a summary GeoJSON feed only if it changed since the last call.
+    ///
+    /// Sends `If-None-Match` / `If-Modified-Since` from the previous response
+    /// of the same feed, and also treats an unchanged `metadata.generated`
+    /// as not modified for servers that ignore conditional requests.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or response cannot be parsed.
+    #[instrument(skip(self), fields(feed = feed_type.as_str()))]
+    pub async fn fetch_feed_if_modified(
+        &self,
+        feed_type: FeedType,
+    ) -> Result<FeedFetch, SeismotailError> {
+        let url = feed_url(&self.base_url, feed_type);
+
+        debug!("conditionally fetching feed from {}", url);
+
+        let response = self
+            .send(|| {
+                self.client
+                    .get(&url)
+                    .headers(self.validators.request_headers(feed_type))
+            })
+            .await?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            debug!("feed not modified (304)");
+            return Ok(FeedFetch::NotModified);
+        }
+
+        let headers = response.headers().clone();
+        let body = response.text().await?;
+        let feed = FeatureCollection::from_json(&body)?.with_body(BodyFormat::GeoJson, body);
+
+        if !self
+            .validators
+            .record(feed_type, &headers, feed.metadata.generated)
+        {
+            debug!("feed not regenerated since last fetch");
+            return Ok(FeedFetch::NotModified);
+        }
+
+        debug!("fetched {} events", feed.features.len());
+        Ok(FeedFetch::Modified(feed))
+    }
+
+    /// Fetch a summary GeoJSON feed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or response cannot be parsed.
+    #[instrument(skip(self), fields(feed = feed_type.as_str()))]
+    pub async fn fetch_feed(
+        &self,
+        feed_type: FeedType,
+    ) -> Result<FeatureCollection, SeismotailError> {
+        let url = feed_url(&self.base_url, feed_type);
+
+        debug!("fetching feed from {}", url);
+
+        let response = self.send(|| self.client.get(&url)).await?;
 
-        // Validate response structure
-        feed.validate()?;
+        // Skips malformed features; the collection itself is validated
+        let body = response.text().await?;
+        let feed = FeatureCollection::from_json(&body)?.with_body(BodyFormat::GeoJson, body);
 
         debug!("fetched {} events", feed.features.len());
         Ok(feed)
     }
 }
 
+/// Build an async HTTP client with the shared timeout and user agent.
+///
+/// # Errors
+///
+/// Returns an error if the HTTP client cannot be initialized.
+pub(crate) fn async_http_client() -> Result<reqwest::Client, SeismotailError> {
+    Ok(reqwest::Client::builder()
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .user_agent(USER_AGENT)
+        .build()?)
+}
+
+/// Send an async request, retrying transient failures per `retry`.
+///
+/// `build` is called once per attempt. Success and 304 responses are
+/// returned; any other status becomes an error.
+///
+/// # Errors
+///
+/// Returns the last error once it is fatal or the attempts are used up.
+pub(crate) async fn send_async(
+    retry: &RetryPolicy,
+    build: impl Fn() -> reqwest::RequestBuilder,
+) -> Result<reqwest::Response, SeismotailError> {
+    let mut attempt = 1;
+    loop {
+        let err = match build().send().await {
+            Ok(response) => {
+                let status = response.status();
+                if status.is_success() || status == StatusCode::NOT_MODIFIED {
+                    return Ok(response);
+                }
+                let retry_after = retry_after_header(response.headers());
+                let body = response.text().await.unwrap_or_default();
+                status_error(status, retry_after, body)
+            }
+            Err(e) => send_error(e),
+        };
+        let Some(delay) = retry.next_delay(attempt, &err) else {
+            return Err(err);
+        };
+        warn!(
+            "attempt {} failed, retrying in {:?}: {}",
+            attempt, delay, err
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Build the URL of a summary feed.
+fn feed_url(base_url: &str, feed_type: FeedType) -> String {
+    format!(
+        "{}/earthquakes/feed/v1.0/summary/{}.geojson",
+        base_url,
+        feed_type.as_str()
+    )
+}
+
+/// Turn an unexpected HTTP status into an error (304 passes through).
+fn check_status(response: Response) -> Result<Response, SeismotailError> {
+    let status = response.status();
+    if status.is_success() || status == StatusCode::NOT_MODIFIED {
+        return Ok(response);
+    }
+
+    let retry_after = retry_after_header(response.headers());
+    let body = response.text().unwrap_or_default();
+    Err(status_error(status, retry_after, body))
+}
+
+/// Classify an error status: throttling, server-side failure or rejection.
+fn status_error(
+    status: StatusCode,
+    retry_after: Option<Duration>,
+    message: String,
+) -> SeismotailError {
+    let status_code = status.as_u16();
+    let throttled = status == StatusCode::TOO_MANY_REQUESTS
+        || (status == StatusCode::SERVICE_UNAVAILABLE && retry_after.is_some());
+    if throttled {
+        SeismotailError::Throttled {
+            status: status_code,
+            retry_after,
+        }
+    } else if status.is_server_error() {
+        SeismotailError::Server {
+            status: status_code,
+            message,
+        }
+    } else {
+        SeismotailError::Api {
+            status: status_code,
+            message,
+        }
+    }
+}
+
+/// Read the `Retry-After` header of a response.
+fn retry_after_header(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| parse_retry_after(v, chrono::Utc::now()))
+}
+
+/// Classify a transport error, separating timeouts from other failures.
+fn send_error(e: reqwest::Error) -> SeismotailError {
+    if e.is_timeout() {
+        SeismotailError::Timeout(e.to_string())
+    } else {
+        SeismotailError::Http(e)
+    }
+}
+
 impl Default for UsgsClient {
+    #[allow(clippy::expect_used)]
     fn default() -> Self {
         Self::new().expect("failed to create default UsgsClient")
     }
@@ -174,19 +689,323 @@ impl Default for UsgsClient {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::query::{OrderBy, parse_time};
+    use crate::test_support::{
+        Response, SAMPLE_DETAIL, SAMPLE_FEED, SAMPLE_QUAKEML, StandInServer,
+    };
 
     #[test]
     fn test_feed_type_round_trip() {
-        let types = [
-            FeedType::AllHour,
-            FeedType::Mag25Day,
-            FeedType::SignificantWeek,
-        ];
-
-        for feed_type in types {
+        for feed_type in FeedType::ALL {
             let s = feed_type.as_str();
             let parsed: FeedType = s.parse().expect("failed to parse");
             assert_eq!(parsed, feed_type);
         }
     }
+
+    #[tokio::test]
+    async fn test_async_fetch_feed_from_stand_in() {
+        let server = StandInServer::start(|_| Response::json(SAMPLE_FEED));
+        let client = AsyncUsgsClient::with_base_url(server.url()).expect("client");
+
+        let feed = client.fetch_feed(FeedType::AllHour).await.expect("fetch");
+        assert_eq!(feed.features.len(), 8);
+        assert_eq!(
+            server.requests()[0].path,
+            "/earthquakes/feed/v1.0/summary/all_hour.geojson"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_async_fetch_feed_maps_http_errors() {
+        let server = StandInServer::start(|req| {
+            if req.path.contains("all_hour") {
+                Response::status(503)
+            } else {
+                Response::status(503).with_header("Retry-After", "0")
+            }
+        });
+        let client = AsyncUsgsClient::with_base_url(server.url())
+            .expect("client")
+            .with_retry(RetryPolicy {
+                max_attempts: 1,
+                ..RetryPolicy::default()
+            });
+
+        // A 503 is only throttling when it says when to come back
+        let err = client.fetch_feed(FeedType::AllHour).await.unwrap_err();
+        assert!(matches!(err, SeismotailError::Server { status: 503, .. }));
+        assert!(err.is_retryable());
+        let err = client.fetch_feed(FeedType::AllDay).await.unwrap_err();
+        assert!(matches!(
+            err,
+            SeismotailError::Throttled { status: 503, .. }
+        ));
+        assert!(err.is_retryable());
+    }
+
+    /// A retry policy with negligible delays.
+    fn fast_retry() -> RetryPolicy {
+        RetryPolicy {
+            base_delay: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Answer with `failure` for the first `failures` requests, then the feed.
+    fn flaky_server(failures: usize, failure: Response) -> StandInServer {
+        let seen = std::sync::atomic::AtomicUsize::new(0);
+        StandInServer::start(move |_| {
+            if seen.fetch_add(1, std::sync::atomic::Ordering::SeqCst) < failures {
+                failure.clone()
+            } else {
+                Response::json(SAMPLE_FEED)
+            }
+        })
+    }
+
+    #[test]
+    fn test_retries_server_errors_then_succeeds() {
+        let server = flaky_server(2, Response::status(500));
+        let client = UsgsClient::with_base_url(server.url())
+            .expect("client")
+            .with_retry(fast_retry());
+
+        let feed = client.query(&EventQuery::default()).expect("query");
+        assert_eq!(feed.features.len(), 8);
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[test]
+    fn test_gives_up_after_max_attempts() {
+        let server = flaky_server(usize::MAX, Response::status(502));
+        let client = UsgsClient::with_base_url(server.url())
+            .expect("client")
+            .with_retry(fast_retry());
+
+        let err = client.query(&EventQuery::default()).unwrap_err();
+        assert!(matches!(err, SeismotailError::Server { status: 502, .. }));
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[test]
+    fn test_client_errors_are_not_retried() {
+        let server = flaky_server(usize::MAX, Response::status(404));
+        let client = UsgsClient::with_base_url(server.url())
+            .expect("client")
+            .with_retry(fast_retry());
+
+        let err = client.query(&EventQuery::default()).unwrap_err();
+        assert!(matches!(err, SeismotailError::Api { status: 404, .. }));
+        assert!(!err.is_retryable());
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn test_throttling_honors_retry_after() {
+        let server = flaky_server(1, Response::status(429).with_header("Retry-After", "1"));
+        let client = UsgsClient::with_base_url(server.url())
+            .expect("client")
+            .with_retry(fast_retry());
+
+        let started = std::time::Instant::now();
+        client.query(&EventQuery::default()).expect("query");
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_async_retries_throttling() {
+        let server = flaky_server(1, Response::status(503).with_header("Retry-After", "0"));
+        let client = AsyncUsgsClient::with_base_url(server.url())
+            .expect("client")
+            .with_retry(fast_retry());
+
+        let fetch = client
+            .fetch_feed_if_modified(FeedType::AllDay)
+            .await
+            .expect("fetch");
+        assert!(matches!(fetch, FeedFetch::Modified(_)));
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    /// Serve the sample feed with an `ETag`, answering 304 when it matches.
+    fn etag_server() -> StandInServer {
+        StandInServer::start(|req| {
+            if req.header("If-None-Match") == Some("\"v1\"") {
+                Response::status(304)
+            } else {
+                Response::json(SAMPLE_FEED)
+                    .with_header("ETag", "\"v1\"")
+                    .with_header("Last-Modified", "Wed, 04 Dec 2024 08:13:20 GMT")
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_conditional_fetch_uses_etag() {
+        let server = etag_server();
+        let client = AsyncUsgsClient::with_base_url(server.url()).expect("client");
+
+        let first = client
+            .fetch_feed_if_modified(FeedType::AllDay)
+            .await
+            .expect("first");
+        assert!(matches!(first, FeedFetch::Modified(ref f) if f.features.len() == 8));
+
+        // Clones share validators
+        let second = client
+            .clone()
+            .fetch_feed_if_modified(FeedType::AllDay)
+            .await
+            .expect("second");
+        assert!(matches!(second, FeedFetch::NotModified));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("If-None-Match"), None);
+        assert_eq!(requests[1].header("If-None-Match"), Some("\"v1\""));
+        assert_eq!(
+            requests[1].header("If-Modified-Since"),
+            Some("Wed, 04 Dec 2024 08:13:20 GMT")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_conditional_fetch_tracks_feeds_separately() {
+        let server = etag_server();
+        let client = AsyncUsgsClient::with_base_url(server.url()).expect("client");
+
+        client
+            .fetch_feed_if_modified(FeedType::AllDay)
+            .await
+            .expect("all_day");
+        let other = client
+            .fetch_feed_if_modified(FeedType::AllWeek)
+            .await
+            .expect("all_week");
+        assert!(matches!(other, FeedFetch::Modified(_)));
+    }
+
+    #[tokio::test]
+    async fn test_conditional_fetch_detects_unchanged_generated() {
+        // No validators at all: fall back to comparing metadata.generated
+        let server = StandInServer::start(|_| Response::json(SAMPLE_FEED));
+        let client = AsyncUsgsClient::with_base_url(server.url()).expect("client");
+
+        let first = client
+            .fetch_feed_if_modified(FeedType::AllHour)
+            .await
+            .expect("first");
+        assert!(matches!(first, FeedFetch::Modified(_)));
+        let second = client
+            .fetch_feed_if_modified(FeedType::AllHour)
+            .await
+            .expect("second");
+        assert!(matches!(second, FeedFetch::NotModified));
+    }
+
+    #[test]
+    fn test_fetch_event_detail() {
+        let server = StandInServer::start(|_| Response::json(SAMPLE_DETAIL));
+        let client = UsgsClient::with_base_url(server.url()).expect("client");
+
+        let detail = client.fetch_event_detail("us7000n1a2").expect("detail");
+        assert_eq!(detail.id, "us7000n1a2");
+        assert!(detail.products().moment_tensor.is_some());
+        assert_eq!(
+            server.requests()[0].path,
+            "/earthquakes/feed/v1.0/detail/us7000n1a2.geojson"
+        );
+
+        let err = client.fetch_event_detail("../summary/all_day").unwrap_err();
+        assert!(matches!(err, SeismotailError::Validation(_)));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn test_query_sends_fdsn_parameters() {
+        let server = StandInServer::start(|_| Response::json(SAMPLE_FEED));
+        let client = UsgsClient::with_base_url(server.url()).expect("client");
+
+        let query = EventQuery {
+            start: Some(parse_time("2024-01-01").expect("time")),
+            end: Some(parse_time("2024-02-01").expect("time")),
+            min_magnitude: Some(2.5),
+            max_depth: Some(70.0),
+            radius: Some("37.77,-122.41,250".parse().expect("radius")),
+            order_by: OrderBy::MagnitudeAsc,
+            limit: Some(25),
+            ..Default::default()
+        };
+        let feed = client.query(&query).expect("query");
+        assert_eq!(feed.features.len(), 8);
+
+        let requests = server.requests();
+        let req = &requests[0];
+        assert_eq!(req.path, "/fdsnws/event/1/query");
+        assert_eq!(req.param("format"), Some("geojson"));
+        assert_eq!(req.param("starttime"), Some("2024-01-01T00:00:00.000Z"));
+        assert_eq!(req.param("endtime"), Some("2024-02-01T00:00:00.000Z"));
+        assert_eq!(req.param("minmagnitude"), Some("2.5"));
+        assert_eq!(req.param("maxdepth"), Some("70"));
+        assert_eq!(req.param("latitude"), Some("37.77"));
+        assert_eq!(req.param("maxradiuskm"), Some("250"));
+        assert_eq!(req.param("orderby"), Some("magnitude-asc"));
+        assert_eq!(req.param("limit"), Some("25"));
+    }
+
+    #[test]
+    fn test_query_parses_quakeml_response() {
+        let server = StandInServer::start(|_| Response {
+            status: 200,
+            headers: vec![("Content-Type".into(), "application/xml".into())],
+            body: SAMPLE_QUAKEML.into(),
+        });
+        let client = UsgsClient::with_base_url(server.url()).expect("client");
+
+        let feed = client.query(&EventQuery::default()).expect("query");
+        assert_eq!(feed.features.len(), 3);
+        assert_eq!(feed.features[0].id, "us7000n1a2");
+    }
+
+    #[test]
+    fn test_count_parses_max_allowed() {
+        let server =
+            StandInServer::start(|_| Response::json(r#"{"count":45210,"maxAllowed":20000}"#));
+        let client = UsgsClient::with_base_url(server.url()).expect("client");
+
+        let query = EventQuery {
+            limit: Some(10),
+            ..Default::default()
+        };
+        let count = client.count(&query).expect("count");
+        assert_eq!(count.count, 45_210);
+        assert_eq!(count.max_allowed, Some(20_000));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/fdsnws/event/1/count");
+        assert_eq!(requests[0].param("limit"), None);
+    }
+
+    #[test]
+    fn test_query_no_content_is_empty() {
+        let server = StandInServer::start(|_| Response::status(204));
+        let client = UsgsClient::with_base_url(server.url()).expect("client");
+
+        let feed = client.query(&EventQuery::default()).expect("query");
+        assert!(feed.features.is_empty());
+    }
+
+    #[test]
+    fn test_query_bad_request_is_api_error() {
+        let server = StandInServer::start(|_| Response {
+            status: 400,
+            headers: Vec::new(),
+            body: b"Bad Request: limit must be <= 20000".to_vec(),
+        });
+        let client = UsgsClient::with_base_url(server.url()).expect("client");
+
+        let err = client.query(&EventQuery::default()).unwrap_err();
+        assert!(matches!(err, SeismotailError::Api { status: 400, .. }));
+    }
 }
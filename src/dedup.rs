@@ -1,22 +1,42 @@
 //! Bounded deduplication ring buffer.
 //!
-//! Implements a fixed-size ring buffer for tracking seen event IDs.
-//! Follows NASA Power of 10: bounded resources, no dynamic allocation in hot path.
+//! Implements a fixed-size ring buffer for tracking seen event IDs, along
+//! with a short revision history per event so updates can be explained.
+//! A hash index alongside the FIFO makes each lookup O(1), so a poll costs
+//! O(features) however large the ring is.
+//! Follows NASA Power of 10: bounded resources and bounded memory.
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::models::Feature;
+use crate::types::{AlertLevel, EventStatus, MagnitudeType, Published};
 
 /// Default capacity for the deduplication ring.
 /// Sized for ~24 hours of earthquake data at peak activity.
 pub const DEFAULT_CAPACITY: usize = 10_000;
 
+/// Revisions kept per event (oldest dropped first).
+pub const HISTORY_LEN: usize = 8;
+
+/// Location changes smaller than this (degrees) are not reported.
+const LOCATION_EPSILON: f64 = 1e-4;
+
 /// A bounded ring buffer for deduplicating events by ID.
 ///
 /// Uses a fixed-capacity ring that evicts oldest entries when full.
-/// This ensures bounded memory usage regardless of stream duration.
+/// This ensures bounded memory usage regardless of stream duration:
+/// both the ring and the index are allocated at full capacity up front
+/// and never hold more than `capacity` IDs.
 #[derive(Debug)]
 pub struct DedupeRing {
-    /// Ring of seen IDs (oldest at front, newest at back)
-    seen: VecDeque<SeenEntry>,
+    /// Ring of seen IDs in insertion order (oldest at front, newest at back)
+    order: VecDeque<Arc<str>>,
+    /// Entries by ID; always holds exactly the IDs in `order`
+    entries: HashMap<Arc<str>, SeenEntry>,
     /// Maximum capacity
     capacity: usize,
     /// Total events processed (for stats)
@@ -28,10 +48,171 @@ pub struct DedupeRing {
 /// An entry in the deduplication ring.
 #[derive(Debug, Clone)]
 struct SeenEntry {
-    /// Event ID
-    id: String,
-    /// Last update timestamp (for tracking updates)
-    updated: i64,
+    /// Revisions seen, oldest first; never empty, at most [`HISTORY_LEN`]
+    history: VecDeque<Revision>,
+}
+
+impl SeenEntry {
+    /// The most recent revision.
+    fn latest(&self) -> &Revision {
+        // History always holds the revision the entry was created with
+        &self.history[self.history.len() - 1]
+    }
+}
+
+/// The tracked fields of one revision of an event.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Revision {
+    /// Modification time (epoch milliseconds)
+    pub updated: i64,
+    pub mag: Option<f64>,
+    pub mag_type: Option<Published<MagnitudeType>>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub depth_km: f64,
+    pub status: Published<EventStatus>,
+    pub alert: Option<Published<AlertLevel>>,
+}
+
+impl Revision {
+    /// Take the tracked fields of an event.
+    #[must_use]
+    pub fn of(event: &Feature) -> Self {
+        Self {
+            updated: event.properties.updated,
+            mag: event.properties.mag,
+            mag_type: event.properties.mag_type.clone(),
+            latitude: event.latitude(),
+            longitude: event.longitude(),
+            depth_km: event.depth_km(),
+            status: event.properties.status.clone(),
+            alert: event.properties.alert.clone(),
+        }
+    }
+
+    /// What changed from `self` to `next`.
+    #[must_use]
+    pub fn diff(&self, next: &Self) -> RevisionDiff {
+        let moved = (self.latitude - next.latitude).abs() > LOCATION_EPSILON
+            || (self.longitude - next.longitude).abs() > LOCATION_EPSILON;
+        let mag_changed = match (self.mag, next.mag) {
+            (Some(a), Some(b)) => (a - b).abs() > f64::EPSILON,
+            (a, b) => a.is_some() != b.is_some(),
+        };
+
+        RevisionDiff {
+            mag: mag_changed.then_some(Change::new(self.mag, next.mag)),
+            mag_type: Change::if_differs(&self.mag_type, &next.mag_type),
+            location: moved.then_some(Change::new(
+                [self.latitude, self.longitude],
+                [next.latitude, next.longitude],
+            )),
+            depth_km: ((self.depth_km - next.depth_km).abs() > f64::EPSILON)
+                .then_some(Change::new(self.depth_km, next.depth_km)),
+            status: Change::if_differs(&self.status, &next.status),
+            alert: Change::if_differs(&self.alert, &next.alert),
+        }
+    }
+}
+
+/// A field's old and new value.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change<T> {
+    pub from: T,
+    pub to: T,
+}
+
+impl<T: Clone + PartialEq> Change<T> {
+    fn new(from: T, to: T) -> Self {
+        Self { from, to }
+    }
+
+    fn if_differs(from: &T, to: &T) -> Option<Self> {
+        (from != to).then(|| Self::new(from.clone(), to.clone()))
+    }
+}
+
+/// Tracked fields that changed between two revisions of an event.
+///
+/// Serializes as the NDJSON `changes` object, with unchanged fields omitted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RevisionDiff {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mag: Option<Change<Option<f64>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mag_type: Option<Change<Option<Published<MagnitudeType>>>>,
+    /// `[latitude, longitude]`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Change<[f64; 2]>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth_km: Option<Change<f64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<Change<Published<EventStatus>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alert: Option<Change<Option<Published<AlertLevel>>>>,
+}
+
+impl RevisionDiff {
+    /// Whether no tracked field changed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Display for RevisionDiff {
+    /// Format as e.g. `M4.2 ml → M4.6 mww, status automatic → reviewed`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        /// `M4.2 ml`, `M4.2` or `ml`, depending on which parts changed.
+        fn magnitude(
+            mag: Option<&Change<Option<f64>>>,
+            mag_type: Option<&Change<Option<Published<MagnitudeType>>>>,
+            to: bool,
+        ) -> String {
+            let pick = |c: &Change<_>| if to { c.to } else { c.from };
+            let mag = mag.map(|c| pick(c).map_or_else(|| "M?".into(), |m| format!("M{m:.1}")));
+            let mag_type = mag_type.map(|c| {
+                let t = if to { &c.to } else { &c.from };
+                t.as_ref().map_or("?", Published::as_str)
+            });
+            match (mag, mag_type) {
+                (Some(m), Some(t)) => format!("{m} {t}"),
+                (Some(m), None) => m,
+                (None, Some(t)) => t.to_string(),
+                (None, None) => String::new(),
+            }
+        }
+        fn alert(level: Option<&Published<AlertLevel>>) -> &str {
+            level.map_or("none", Published::as_str)
+        }
+
+        let mut parts = Vec::new();
+        if self.mag.is_some() || self.mag_type.is_some() {
+            let from = magnitude(self.mag.as_ref(), self.mag_type.as_ref(), false);
+            let to = magnitude(self.mag.as_ref(), self.mag_type.as_ref(), true);
+            parts.push(format!("{from} → {to}"));
+        }
+        if let Some(c) = &self.location {
+            parts.push(format!(
+                "location {:.2},{:.2} → {:.2},{:.2}",
+                c.from[0], c.from[1], c.to[0], c.to[1]
+            ));
+        }
+        if let Some(c) = &self.depth_km {
+            parts.push(format!("depth {:.1} → {:.1} km", c.from, c.to));
+        }
+        if let Some(c) = &self.status {
+            parts.push(format!("status {} → {}", c.from, c.to));
+        }
+        if let Some(c) = &self.alert {
+            parts.push(format!(
+                "alert {} → {}",
+                alert(c.from.as_ref()),
+                alert(c.to.as_ref())
+            ));
+        }
+        f.write_str(&parts.join(", "))
+    }
 }
 
 impl DedupeRing {
@@ -45,7 +226,8 @@ impl DedupeRing {
         assert!(capacity > 0, "capacity must be positive");
 
         Self {
-            seen: VecDeque::with_capacity(capacity),
+            order: VecDeque::with_capacity(capacity),
+            entries: HashMap::with_capacity(capacity),
             capacity,
             total_seen: 0,
             total_dupes: 0,
@@ -58,81 +240,99 @@ impl DedupeRing {
         Self::new(DEFAULT_CAPACITY)
     }
 
-    /// Check if an event should be processed (not a duplicate).
-    ///
-    /// Returns `true` if the event is new or has been updated.
-    /// Returns `false` if this is a duplicate we've seen before.
+    /// Check if an event is new, an update or a duplicate by its ID and
+    /// modification time, recording its tracked fields in the event's
+    /// history.
     ///
-    /// This also marks the event as seen if it's new.
-    pub fn check_and_mark(&mut self, id: &str, updated: i64) -> DedupeResult {
+    /// On [`DedupeResult::Updated`] also returns what changed since the
+    /// previous revision.
+    pub fn check_revision(&mut self, event: &Feature) -> (DedupeResult, Option<RevisionDiff>) {
+        self.observe(&event.id, Revision::of(event))
+    }
+
+    /// Revisions seen for an event, oldest first.
+    #[cfg(test)]
+    pub fn history(&self, id: &str) -> Option<impl Iterator<Item = &Revision>> {
+        self.entries.get(id).map(|entry| entry.history.iter())
+    }
+
+    /// Mark a revision as seen, diffing it against the previous one.
+    fn observe(&mut self, id: &str, revision: Revision) -> (DedupeResult, Option<RevisionDiff>) {
         self.total_seen += 1;
 
         // Check if we've seen this ID before
-        if let Some(pos) = self.find_position(id) {
-            let entry = &self.seen[pos];
-
+        if let Some(entry) = self.entries.get_mut(id) {
             // Check if this is an update (newer timestamp)
-            if updated > entry.updated {
-                // Update the existing entry with new timestamp
-                self.seen[pos].updated = updated;
-                return DedupeResult::Updated;
+            if revision.updated > entry.latest().updated {
+                let diff = entry.latest().diff(&revision);
+                if entry.history.len() >= HISTORY_LEN {
+                    entry.history.pop_front();
+                }
+                entry.history.push_back(revision);
+                return (DedupeResult::Updated, Some(diff));
             }
 
             // It's a duplicate with same or older timestamp
             self.total_dupes += 1;
-            return DedupeResult::Duplicate;
+            return (DedupeResult::Duplicate, None);
         }
 
         // New event - add to ring
-        self.insert(id.to_string(), updated);
-        DedupeResult::New
-    }
-
-    /// Find the position of an ID in the ring.
-    fn find_position(&self, id: &str) -> Option<usize> {
-        // Linear search - could optimize with a HashSet if needed,
-        // but for 10k entries this is fast enough (~1-2ms worst case)
-        self.seen.iter().position(|e| e.id == id)
+        self.insert(id, revision);
+        (DedupeResult::New, None)
     }
 
     /// Insert a new entry, evicting oldest if at capacity.
-    fn insert(&mut self, id: String, updated: i64) {
+    fn insert(&mut self, id: &str, revision: Revision) {
         // Evict oldest if at capacity (FIFO)
-        if self.seen.len() >= self.capacity {
-            self.seen.pop_front();
+        if self.order.len() >= self.capacity
+            && let Some(oldest) = self.order.pop_front()
+        {
+            self.entries.remove(&oldest);
         }
 
-        self.seen.push_back(SeenEntry { id, updated });
+        let mut history = VecDeque::with_capacity(HISTORY_LEN);
+        history.push_back(revision);
+        // The ring and the index share one allocation of the ID
+        let id: Arc<str> = Arc::from(id);
+        self.order.push_back(Arc::clone(&id));
+        self.entries.insert(id, SeenEntry { history });
 
         // NASA Power of 10: assert postcondition
-        debug_assert!(self.seen.len() <= self.capacity);
+        debug_assert!(self.order.len() <= self.capacity);
+        debug_assert_eq!(self.order.len(), self.entries.len());
     }
 
     /// Get the current number of tracked IDs.
+    #[allow(dead_code)]
     #[must_use]
     pub fn len(&self) -> usize {
-        self.seen.len()
+        self.order.len()
     }
 
     /// Check if the ring is empty.
+    #[allow(dead_code)]
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.seen.is_empty()
+        self.order.is_empty()
     }
 
     /// Get total events processed.
+    #[allow(dead_code)]
     #[must_use]
     pub fn total_seen(&self) -> u64 {
         self.total_seen
     }
 
     /// Get total duplicates skipped.
+    #[allow(dead_code)]
     #[must_use]
     pub fn total_dupes(&self) -> u64 {
         self.total_dupes
     }
 
     /// Get the deduplication rate (0.0 to 1.0).
+    #[allow(clippy::cast_precision_loss)]
     #[must_use]
     pub fn dupe_rate(&self) -> f64 {
         if self.total_seen == 0 {
@@ -143,8 +343,10 @@ impl DedupeRing {
     }
 
     /// Clear all tracked IDs (for testing or reset).
+    #[allow(dead_code)]
     pub fn clear(&mut self) {
-        self.seen.clear();
+        self.order.clear();
+        self.entries.clear();
         self.total_seen = 0;
         self.total_dupes = 0;
     }
@@ -176,26 +378,32 @@ impl DedupeResult {
             Self::Duplicate => false,
         }
     }
-
-    /// Check if this is an update to an existing event.
-    #[must_use]
-    pub fn is_update(self) -> bool {
-        matches!(self, Self::Updated)
-    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Observe a revision that only carries a modification time.
+    fn mark(ring: &mut DedupeRing, id: &str, updated: i64) -> DedupeResult {
+        ring.observe(
+            id,
+            Revision {
+                updated,
+                ..Revision::default()
+            },
+        )
+        .0
+    }
+
     #[test]
     fn test_new_events() {
         let mut ring = DedupeRing::new(100);
 
         // First occurrence is new
-        assert_eq!(ring.check_and_mark("event1", 1000), DedupeResult::New);
-        assert_eq!(ring.check_and_mark("event2", 2000), DedupeResult::New);
-        assert_eq!(ring.check_and_mark("event3", 3000), DedupeResult::New);
+        assert_eq!(mark(&mut ring, "event1", 1000), DedupeResult::New);
+        assert_eq!(mark(&mut ring, "event2", 2000), DedupeResult::New);
+        assert_eq!(mark(&mut ring, "event3", 3000), DedupeResult::New);
 
         assert_eq!(ring.len(), 3);
         assert_eq!(ring.total_seen(), 3);
@@ -207,11 +415,11 @@ mod tests {
         let mut ring = DedupeRing::new(100);
 
         // First occurrence
-        assert_eq!(ring.check_and_mark("event1", 1000), DedupeResult::New);
+        assert_eq!(mark(&mut ring, "event1", 1000), DedupeResult::New);
 
         // Same ID, same timestamp = duplicate
-        assert_eq!(ring.check_and_mark("event1", 1000), DedupeResult::Duplicate);
-        assert_eq!(ring.check_and_mark("event1", 1000), DedupeResult::Duplicate);
+        assert_eq!(mark(&mut ring, "event1", 1000), DedupeResult::Duplicate);
+        assert_eq!(mark(&mut ring, "event1", 1000), DedupeResult::Duplicate);
 
         assert_eq!(ring.len(), 1);
         assert_eq!(ring.total_dupes(), 2);
@@ -222,14 +430,14 @@ mod tests {
         let mut ring = DedupeRing::new(100);
 
         // First occurrence
-        assert_eq!(ring.check_and_mark("event1", 1000), DedupeResult::New);
+        assert_eq!(mark(&mut ring, "event1", 1000), DedupeResult::New);
 
         // Same ID, newer timestamp = update
-        assert_eq!(ring.check_and_mark("event1", 2000), DedupeResult::Updated);
-        assert_eq!(ring.check_and_mark("event1", 3000), DedupeResult::Updated);
+        assert_eq!(mark(&mut ring, "event1", 2000), DedupeResult::Updated);
+        assert_eq!(mark(&mut ring, "event1", 3000), DedupeResult::Updated);
 
         // Same ID, older timestamp = duplicate
-        assert_eq!(ring.check_and_mark("event1", 2000), DedupeResult::Duplicate);
+        assert_eq!(mark(&mut ring, "event1", 2000), DedupeResult::Duplicate);
 
         assert_eq!(ring.len(), 1);
     }
@@ -238,20 +446,44 @@ mod tests {
     fn test_bounded_capacity() {
         let mut ring = DedupeRing::new(3);
 
-        ring.check_and_mark("event1", 1000);
-        ring.check_and_mark("event2", 2000);
-        ring.check_and_mark("event3", 3000);
+        mark(&mut ring, "event1", 1000);
+        mark(&mut ring, "event2", 2000);
+        mark(&mut ring, "event3", 3000);
         assert_eq!(ring.len(), 3);
 
         // Fourth event evicts oldest
-        ring.check_and_mark("event4", 4000);
+        mark(&mut ring, "event4", 4000);
         assert_eq!(ring.len(), 3);
 
-        // event1 should be gone (evicted)
-        assert_eq!(ring.check_and_mark("event1", 1000), DedupeResult::New);
-
         // event2, event3, event4 should still be tracked
-        assert_eq!(ring.check_and_mark("event2", 2000), DedupeResult::Duplicate);
+        assert_eq!(mark(&mut ring, "event2", 2000), DedupeResult::Duplicate);
+        assert_eq!(mark(&mut ring, "event4", 4000), DedupeResult::Duplicate);
+
+        // event1 should be gone (evicted); re-marking it evicts event2
+        assert_eq!(mark(&mut ring, "event1", 1000), DedupeResult::New);
+        assert_eq!(mark(&mut ring, "event3", 3000), DedupeResult::Duplicate);
+    }
+
+    #[test]
+    fn test_check_revision() {
+        let feed: crate::models::FeatureCollection =
+            serde_json::from_str(crate::test_support::SAMPLE_FEED).unwrap();
+        let mut ring = DedupeRing::new(100);
+
+        for event in &feed.features {
+            assert_eq!(ring.check_revision(event), (DedupeResult::New, None));
+        }
+        for event in &feed.features {
+            assert_eq!(ring.check_revision(event), (DedupeResult::Duplicate, None));
+        }
+
+        let mut revised = feed.features[0].clone();
+        revised.properties.updated += 60_000;
+        revised.properties.mag = revised.properties.mag.map(|m| m + 0.3);
+        let (result, diff) = ring.check_revision(&revised);
+        assert_eq!(result, DedupeResult::Updated);
+        assert!(diff.unwrap().mag.is_some());
+        assert_eq!(ring.history(&revised.id).unwrap().count(), 2);
     }
 
     #[test]
@@ -265,12 +497,145 @@ mod tests {
     fn test_dupe_rate() {
         let mut ring = DedupeRing::new(100);
 
-        ring.check_and_mark("event1", 1000);
-        ring.check_and_mark("event1", 1000); // dupe
-        ring.check_and_mark("event1", 1000); // dupe
-        ring.check_and_mark("event2", 2000);
+        mark(&mut ring, "event1", 1000);
+        mark(&mut ring, "event1", 1000); // dupe
+        mark(&mut ring, "event1", 1000); // dupe
+        mark(&mut ring, "event2", 2000);
 
         // 2 dupes out of 4 = 50%
         assert!((ring.dupe_rate() - 0.5).abs() < 0.01);
     }
+
+    fn revision(updated: i64, mag: f64, mag_type: &str, status: EventStatus) -> Revision {
+        Revision {
+            updated,
+            mag: Some(mag),
+            mag_type: Some(mag_type.into()),
+            latitude: 61.2,
+            longitude: -149.9,
+            depth_km: 10.0,
+            status: status.into(),
+            alert: None,
+        }
+    }
+
+    #[test]
+    fn test_revision_diff() {
+        let mut ring = DedupeRing::new(100);
+
+        let first = revision(1000, 4.2, "ml", EventStatus::Automatic);
+        assert_eq!(
+            ring.observe("ak1", first.clone()),
+            (DedupeResult::New, None)
+        );
+
+        let second = revision(2000, 4.6, "mww", EventStatus::Reviewed);
+        let (result, diff) = ring.observe("ak1", second);
+        assert_eq!(result, DedupeResult::Updated);
+        let diff = diff.unwrap();
+        assert_eq!(
+            diff.to_string(),
+            "M4.2 ml → M4.6 mww, status automatic → reviewed"
+        );
+        assert!(diff.location.is_none() && diff.alert.is_none());
+
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["mag"]["from"], 4.2);
+        assert_eq!(json["mag_type"]["to"], "mww");
+        assert!(json.get("depth_km").is_none());
+
+        // A new timestamp with no tracked change yields an empty diff
+        let third = revision(3000, 4.6, "mww", EventStatus::Reviewed);
+        assert!(ring.observe("ak1", third).1.unwrap().is_empty());
+
+        let alerted = Revision {
+            updated: 4000,
+            alert: Some(AlertLevel::Yellow.into()),
+            depth_km: 12.0,
+            ..revision(0, 4.6, "mww", EventStatus::Reviewed)
+        };
+        let diff = ring.observe("ak1", alerted).1.unwrap();
+        assert_eq!(
+            diff.to_string(),
+            "depth 10.0 → 12.0 km, alert none → yellow"
+        );
+        assert_eq!(
+            first
+                .diff(&revision(0, 4.2, "mb", EventStatus::Automatic))
+                .to_string(),
+            "ml → mb"
+        );
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut ring = DedupeRing::new(100);
+        for i in 0..20 {
+            mark(&mut ring, "event1", i);
+        }
+
+        let history: Vec<i64> = ring.history("event1").unwrap().map(|r| r.updated).collect();
+        assert_eq!(history.len(), HISTORY_LEN);
+        assert_eq!(history.last(), Some(&19));
+        assert!(ring.history("event2").is_none());
+    }
+
+    #[test]
+    fn test_evicted_ids_leave_the_index() {
+        let mut ring = DedupeRing::new(2);
+        mark(&mut ring, "a", 1);
+        mark(&mut ring, "b", 1);
+        mark(&mut ring, "c", 1);
+
+        assert!(ring.history("a").is_none());
+        assert_eq!(mark(&mut ring, "b", 1), DedupeResult::Duplicate);
+        assert_eq!(mark(&mut ring, "a", 1), DedupeResult::New);
+        assert!(ring.history("b").is_none());
+        assert_eq!(ring.len(), 2);
+    }
+
+    /// Poll-processing cost at full rings. Run with
+    /// `cargo test --release -- --ignored --nocapture bench_poll`.
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_poll() {
+        use std::time::{Duration, Instant};
+
+        // A busy feed: mostly unchanged events, a few updates and new ones
+        const POLL: usize = 5_000;
+        const POLLS: i64 = 20;
+
+        let feed: crate::models::FeatureCollection =
+            serde_json::from_str(crate::test_support::SAMPLE_FEED).unwrap();
+        let feature = |id: String, updated: i64| {
+            let mut event = feed.features[0].clone();
+            event.id = id;
+            event.properties.updated = updated;
+            event
+        };
+
+        for capacity in [10_000, 100_000] {
+            let mut ring = DedupeRing::new(capacity);
+            for i in 0..capacity {
+                ring.check_revision(&feature(format!("us{i:08}"), 0));
+            }
+
+            let mut elapsed = Duration::ZERO;
+            for poll in 1..=POLLS {
+                let mut features: Vec<Feature> = (capacity - POLL..capacity)
+                    .map(|i| feature(format!("us{i:08}"), if i % 100 == 0 { poll } else { 0 }))
+                    .collect();
+                features.extend((0..10).map(|i| feature(format!("nc{poll:04}{i:04}"), 0)));
+
+                let start = Instant::now();
+                for event in &features {
+                    std::hint::black_box(ring.check_revision(event));
+                }
+                elapsed += start.elapsed();
+            }
+            let per_poll = elapsed / u32::try_from(POLLS).unwrap_or(1);
+            assert_eq!(ring.len(), capacity);
+            println!("capacity {capacity:>7}: {POLL} features per poll in {per_poll:?}");
+        }
+    }
 }
@@ -0,0 +1,471 @@
+//! Event detail products.
+//!
+//! The per-event detail GeoJSON carries every product contributed for an
+//! event (origins, moment tensors, ShakeMap, PAGER, DYFI, ...). Product
+//! properties are all strings on the wire; this module picks the preferred
+//! product of each common type and parses it into a typed model.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::SeismotailError;
+use crate::models::{Feature, Geometry, Properties};
+use crate::types::AlertLevel;
+
+/// Per-event detail GeoJSON (a single feature with products).
+///
+/// Mirrors the wire format, so not every field is consumed.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventDetail {
+    /// Always "Feature"
+    #[serde(rename = "type")]
+    pub type_: String,
+
+    /// Event ID
+    pub id: String,
+
+    /// Geographic location
+    pub geometry: Geometry,
+
+    /// Summary properties plus products
+    pub properties: DetailProperties,
+}
+
+/// Detail properties: the summary fields plus the product index.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DetailProperties {
+    /// Same fields as in the summary feeds
+    #[serde(flatten)]
+    pub summary: Properties,
+
+    /// Products by type, e.g. `"moment-tensor"`
+    #[serde(default)]
+    pub products: HashMap<String, Vec<Product>>,
+}
+
+/// A product contributed to an event.
+///
+/// Mirrors the wire format, so not every field is consumed.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Deserialize)]
+pub struct Product {
+    /// Product type, e.g. `"shakemap"`
+    #[serde(rename = "type")]
+    pub type_: String,
+
+    /// Product code (unique per source and type)
+    pub code: String,
+
+    /// Contributing network
+    pub source: String,
+
+    /// Product status: "UPDATE" or "DELETE"
+    pub status: String,
+
+    /// Last update time (ms since epoch)
+    #[serde(rename = "updateTime")]
+    pub update_time: i64,
+
+    /// Preference rank among products of the same type (higher wins)
+    #[serde(rename = "preferredWeight", default)]
+    pub preferred_weight: i64,
+
+    /// Product properties (all string-valued on the wire)
+    #[serde(default)]
+    pub properties: HashMap<String, String>,
+
+    /// Downloadable files by path
+    #[serde(default)]
+    pub contents: HashMap<String, Content>,
+}
+
+/// A downloadable file of a product.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Deserialize)]
+pub struct Content {
+    /// MIME type
+    #[serde(rename = "contentType")]
+    pub mime_type: Option<String>,
+
+    /// Size in bytes
+    pub length: Option<u64>,
+
+    /// Download URL
+    pub url: Option<String>,
+}
+
+impl Product {
+    /// Get a string property.
+    fn text(&self, key: &str) -> Option<String> {
+        self.properties
+            .get(key)
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .map(String::from)
+    }
+
+    /// Get a numeric property.
+    fn num(&self, key: &str) -> Option<f64> {
+        self.properties.get(key).and_then(|v| v.trim().parse().ok())
+    }
+
+    /// Get the URL of a content file.
+    fn content_url(&self, path: &str) -> Option<String> {
+        self.contents.get(path).and_then(|c| c.url.clone())
+    }
+
+    /// Parse nodal planes 1 and 2, skipping incomplete ones.
+    fn nodal_planes(&self) -> Vec<NodalPlane> {
+        (1..=2)
+            .filter_map(|n| {
+                Some(NodalPlane {
+                    strike: self.num(&format!("nodal-plane-{n}-strike"))?,
+                    dip: self.num(&format!("nodal-plane-{n}-dip"))?,
+                    rake: self.num(&format!("nodal-plane-{n}-rake"))?,
+                })
+            })
+            .collect()
+    }
+}
+
+impl EventDetail {
+    /// Validate the response structure.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document is not a feature.
+    pub fn validate(&self) -> Result<(), SeismotailError> {
+        if self.type_ != "Feature" {
+            return Err(SeismotailError::InvalidResponse(format!(
+                "expected type 'Feature', got '{}'",
+                self.type_
+            )));
+        }
+        Ok(())
+    }
+
+    /// The event as a summary feature (for the regular event output).
+    #[must_use]
+    pub fn feature(&self) -> Feature {
+        Feature {
+            type_: self.type_.clone(),
+            id: self.id.clone(),
+            geometry: self.geometry.clone(),
+            properties: self.properties.summary.clone(),
+            regions: Vec::new(),
+            home: None,
+            nearest_place: None,
+            zones: Vec::new(),
+        }
+    }
+
+    /// The preferred (highest weight, non-deleted) product of a type.
+    #[must_use]
+    pub fn preferred(&self, product_type: &str) -> Option<&Product> {
+        self.properties
+            .products
+            .get(product_type)?
+            .iter()
+            .filter(|p| p.status != "DELETE")
+            // Keep the first of equally weighted products, like the event page
+            .rev()
+            .max_by_key(|p| p.preferred_weight)
+    }
+
+    /// Parse the preferred product of each common type.
+    #[must_use]
+    pub fn products(&self) -> EventProducts {
+        EventProducts {
+            origin: self.preferred("origin").map(Origin::from_product),
+            moment_tensor: self
+                .preferred("moment-tensor")
+                .map(MomentTensor::from_product),
+            focal_mechanism: self
+                .preferred("focal-mechanism")
+                .map(FocalMechanism::from_product),
+            shakemap: self.preferred("shakemap").map(ShakeMap::from_product),
+            losspager: self.preferred("losspager").map(LossPager::from_product),
+            dyfi: self.preferred("dyfi").map(Dyfi::from_product),
+        }
+    }
+}
+
+/// Preferred products of the common types.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EventProducts {
+    pub origin: Option<Origin>,
+    pub moment_tensor: Option<MomentTensor>,
+    pub focal_mechanism: Option<FocalMechanism>,
+    pub shakemap: Option<ShakeMap>,
+    pub losspager: Option<LossPager>,
+    pub dyfi: Option<Dyfi>,
+}
+
+/// Hypocenter solution (`origin` product).
+#[derive(Debug, Clone, Serialize)]
+pub struct Origin {
+    pub source: String,
+    pub time: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub depth_km: Option<f64>,
+    pub magnitude: Option<f64>,
+    pub magnitude_type: Option<String>,
+    pub review_status: Option<String>,
+    pub num_stations: Option<u32>,
+    pub azimuthal_gap: Option<f64>,
+    pub standard_error: Option<f64>,
+}
+
+impl Origin {
+    fn from_product(p: &Product) -> Self {
+        Self {
+            source: p.source.clone(),
+            time: p.text("eventtime"),
+            latitude: p.num("latitude"),
+            longitude: p.num("longitude"),
+            depth_km: p.num("depth"),
+            magnitude: p.num("magnitude"),
+            magnitude_type: p.text("magnitude-type"),
+            review_status: p.text("review-status"),
+            num_stations: p.text("num-stations-used").and_then(|v| v.parse().ok()),
+            azimuthal_gap: p.num("azimuthal-gap"),
+            standard_error: p.num("standard-error"),
+        }
+    }
+}
+
+/// A fault plane solution.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct NodalPlane {
+    /// Degrees clockwise from north
+    pub strike: f64,
+    /// Degrees from horizontal
+    pub dip: f64,
+    /// Slip direction in degrees
+    pub rake: f64,
+}
+
+/// Moment tensor components in N·m (Harvard convention).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Tensor {
+    pub mrr: f64,
+    pub mtt: f64,
+    pub mpp: f64,
+    pub mrt: f64,
+    pub mrp: f64,
+    pub mtp: f64,
+}
+
+/// Moment tensor solution (`moment-tensor` product).
+#[derive(Debug, Clone, Serialize)]
+pub struct MomentTensor {
+    pub source: String,
+    pub magnitude: Option<f64>,
+    pub magnitude_type: Option<String>,
+    pub depth_km: Option<f64>,
+    /// Scalar moment in N·m
+    pub scalar_moment: Option<f64>,
+    /// Double-couple fraction (0-1)
+    pub percent_double_couple: Option<f64>,
+    pub tensor: Option<Tensor>,
+    pub nodal_planes: Vec<NodalPlane>,
+}
+
+impl MomentTensor {
+    fn from_product(p: &Product) -> Self {
+        let tensor = (|| {
+            Some(Tensor {
+                mrr: p.num("tensor-mrr")?,
+                mtt: p.num("tensor-mtt")?,
+                mpp: p.num("tensor-mpp")?,
+                mrt: p.num("tensor-mrt")?,
+                mrp: p.num("tensor-mrp")?,
+                mtp: p.num("tensor-mtp")?,
+            })
+        })();
+
+        Self {
+            source: p.source.clone(),
+            magnitude: p.num("derived-magnitude"),
+            magnitude_type: p.text("derived-magnitude-type"),
+            depth_km: p.num("derived-depth"),
+            scalar_moment: p.num("scalar-moment"),
+            percent_double_couple: p.num("percent-double-couple"),
+            tensor,
+            nodal_planes: p.nodal_planes(),
+        }
+    }
+}
+
+/// First-motion fault plane solution (`focal-mechanism` product).
+#[derive(Debug, Clone, Serialize)]
+pub struct FocalMechanism {
+    pub source: String,
+    pub num_stations: Option<u32>,
+    pub nodal_planes: Vec<NodalPlane>,
+}
+
+impl FocalMechanism {
+    fn from_product(p: &Product) -> Self {
+        Self {
+            source: p.source.clone(),
+            num_stations: p.text("num-stations-used").and_then(|v| v.parse().ok()),
+            nodal_planes: p.nodal_planes(),
+        }
+    }
+}
+
+/// Ground shaking estimate (`shakemap` product).
+#[derive(Debug, Clone, Serialize)]
+pub struct ShakeMap {
+    pub source: String,
+    pub version: Option<String>,
+    pub map_status: Option<String>,
+    /// Peak Modified Mercalli Intensity
+    pub max_mmi: Option<f64>,
+    /// Peak ground acceleration in %g
+    pub max_pga: Option<f64>,
+    /// Peak ground velocity in cm/s
+    pub max_pgv: Option<f64>,
+    pub intensity_map_url: Option<String>,
+}
+
+impl ShakeMap {
+    fn from_product(p: &Product) -> Self {
+        Self {
+            source: p.source.clone(),
+            version: p.text("version"),
+            map_status: p.text("map-status"),
+            max_mmi: p.num("maxmmi"),
+            max_pga: p.num("maxpga"),
+            max_pgv: p.num("maxpgv"),
+            intensity_map_url: p.content_url("download/intensity.jpg"),
+        }
+    }
+}
+
+/// Impact estimate (`losspager` product).
+#[derive(Debug, Clone, Serialize)]
+pub struct LossPager {
+    pub source: String,
+    /// PAGER alert: green, yellow, orange or red
+    pub alert_level: Option<AlertLevel>,
+    pub max_mmi: Option<f64>,
+    pub onepager_url: Option<String>,
+}
+
+impl LossPager {
+    fn from_product(p: &Product) -> Self {
+        Self {
+            source: p.source.clone(),
+            alert_level: p.text("alertlevel").map(AlertLevel::from),
+            max_mmi: p.num("maxmmi"),
+            onepager_url: p.content_url("onepager.pdf"),
+        }
+    }
+}
+
+/// "Did You Feel It?" summary (`dyfi` product).
+#[derive(Debug, Clone, Serialize)]
+pub struct Dyfi {
+    pub source: String,
+    /// Maximum reported Community Decimal Intensity
+    pub max_cdi: Option<f64>,
+    pub num_responses: Option<u32>,
+}
+
+impl Dyfi {
+    fn from_product(p: &Product) -> Self {
+        Self {
+            source: p.source.clone(),
+            max_cdi: p.num("maxmmi"),
+            num_responses: p
+                .text("num-responses")
+                .or_else(|| p.text("numResp"))
+                .and_then(|v| v.parse().ok()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::SAMPLE_DETAIL;
+
+    fn sample() -> EventDetail {
+        let detail: EventDetail = serde_json::from_str(SAMPLE_DETAIL).unwrap();
+        detail.validate().unwrap();
+        detail
+    }
+
+    #[test]
+    fn test_parse_detail_summary() {
+        let detail = sample();
+        let feature = detail.feature();
+        assert_eq!(feature.id, "us7000n1a2");
+        assert_eq!(feature.properties.mag, Some(6.1));
+        assert!((feature.depth_km() - 42.3).abs() < f64::EPSILON);
+        assert!(detail.properties.products.contains_key("phase-data"));
+    }
+
+    #[test]
+    fn test_preferred_moment_tensor() {
+        let products = sample().products();
+        let mt = products.moment_tensor.unwrap();
+
+        // Mww (weight 218) wins over Mwb (weight 200)
+        assert_eq!(mt.magnitude_type.as_deref(), Some("Mww"));
+        assert_eq!(mt.magnitude, Some(6.08));
+        assert_eq!(mt.scalar_moment, Some(1.653e18));
+        assert_eq!(mt.nodal_planes.len(), 2);
+        assert_eq!(
+            mt.nodal_planes[0],
+            NodalPlane {
+                strike: 113.88,
+                dip: 29.01,
+                rake: 84.37
+            }
+        );
+        assert_eq!(mt.tensor.map(|t| t.mrr), Some(1.42e18));
+    }
+
+    #[test]
+    fn test_parse_impact_products() {
+        let products = sample().products();
+
+        let pager = products.losspager.unwrap();
+        assert_eq!(pager.alert_level, Some(AlertLevel::Green));
+        assert!(pager.onepager_url.unwrap().ends_with("onepager.pdf"));
+
+        let shakemap = products.shakemap.unwrap();
+        assert_eq!(shakemap.max_mmi, Some(6.82));
+        assert_eq!(shakemap.max_pga, Some(31.47));
+        assert_eq!(shakemap.version.as_deref(), Some("3"));
+
+        let dyfi = products.dyfi.unwrap();
+        assert_eq!(dyfi.num_responses, Some(12));
+
+        let origin = products.origin.unwrap();
+        assert_eq!(origin.num_stations, Some(112));
+        assert_eq!(origin.review_status.as_deref(), Some("reviewed"));
+
+        assert_eq!(products.focal_mechanism.unwrap().nodal_planes.len(), 2);
+    }
+
+    #[test]
+    fn test_missing_and_deleted_products() {
+        let mut detail = sample();
+        detail.properties.products.remove("dyfi");
+        for p in detail.properties.products.get_mut("losspager").unwrap() {
+            p.status = "DELETE".into();
+        }
+
+        let products = detail.products();
+        assert!(products.dyfi.is_none());
+        assert!(products.losspager.is_none());
+        assert!(products.origin.is_some());
+    }
+}
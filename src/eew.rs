@@ -2,6 +2,13 @@
 //!
 //! Provides detection algorithms and integration with OpenEEW data.
 
+// Sample counts and rates are small; float <-> index conversions are intentional.
+#![allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+
 use serde::{Deserialize, Serialize};
 
 // ============================================================================
@@ -77,7 +84,7 @@ impl AlertLevel {
         }
     }
 
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(self) -> &'static str {
         match self {
             AlertLevel::None => "none",
             AlertLevel::Weak => "weak",
@@ -88,7 +95,7 @@ impl AlertLevel {
         }
     }
 
-    pub fn emoji(&self) -> &'static str {
+    pub fn emoji(self) -> &'static str {
         match self {
             AlertLevel::None => "⚪",
             AlertLevel::Weak => "🟢",
@@ -134,6 +141,7 @@ impl Default for StaLtaDetector {
 
 impl StaLtaDetector {
     /// Create a new detector with custom parameters.
+    #[allow(dead_code)]
     pub fn new(sta_seconds: f32, lta_seconds: f32, sample_rate: f32, threshold: f32) -> Self {
         Self {
             sta_samples: (sta_seconds * sample_rate) as usize,
@@ -153,7 +161,7 @@ impl StaLtaDetector {
 
     /// Run detection on accelerometer data.
     ///
-    /// Returns a vector of (sample_index, sta_lta_ratio, pga) for each trigger.
+    /// Returns a vector of (`sample_index`, `sta_lta_ratio`, `pga`) for each trigger.
     pub fn detect(&self, record: &AccelerometerRecord) -> Vec<Detection> {
         let n = record.x.len().min(record.y.len()).min(record.z.len());
         if n < self.lta_samples {
@@ -192,12 +200,12 @@ impl StaLtaDetector {
                 triggered = true;
                 let peak_pga = pga[i.saturating_sub(self.sta_samples)..i]
                     .iter()
-                    .cloned()
+                    .copied()
                     .fold(0.0f32, f32::max);
 
                 detections.push(Detection {
                     device_id: record.device_id.clone(),
-                    timestamp: record.timestamp + (i as f64 / record.sr as f64),
+                    timestamp: record.timestamp + (i as f64 / f64::from(record.sr)),
                     pga: peak_pga,
                     sta_lta_ratio: ratio,
                     estimated_magnitude: estimate_magnitude_from_pga(peak_pga),
@@ -233,14 +241,16 @@ fn estimate_magnitude_from_pga(pga: f32) -> Option<f32> {
 pub const OPENEEW_BUCKET: &str = "grillo-openeew";
 
 /// Countries with available data.
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub enum Country {
     Mexico,
     Chile,
 }
 
+#[allow(dead_code)]
 impl Country {
-    pub fn code(&self) -> &'static str {
+    pub fn code(self) -> &'static str {
         match self {
             Country::Mexico => "mx",
             Country::Chile => "cl",
@@ -249,7 +259,7 @@ impl Country {
 }
 
 /// Build the S3 prefix for OpenEEW data.
-/// Structure: records/country_code=mx/device_id=000/year=2018/month=02/day=16/hour=00/
+/// Structure: `records/country_code=mx/device_id=000/year=2018/month=02/day=16/hour=00/`
 pub fn build_s3_prefix(country: &str, device_id: &str, date: &str, hour: Option<&str>) -> String {
     let base = format!(
         "records/country_code={}/device_id={}/year={}/month={}/day={}",
@@ -261,9 +271,9 @@ pub fn build_s3_prefix(country: &str, device_id: &str, date: &str, hour: Option<
     );
     
     if let Some(h) = hour {
-        format!("{}/hour={}/", base, h)
+        format!("{base}/hour={h}/")
     } else {
-        format!("{}/", base)
+        format!("{base}/")
     }
 }
 
@@ -288,7 +298,7 @@ impl OpenEewClient {
 
     /// List available device IDs for a country.
     pub async fn list_devices(&self, country: &str) -> Result<Vec<String>, aws_sdk_s3::Error> {
-        let prefix = format!("records/country_code={}/device_id=", country);
+        let prefix = format!("records/country_code={country}/device_id=");
         
         let resp = self.s3_client
             .list_objects_v2()
@@ -326,7 +336,12 @@ impl OpenEewClient {
             .contents()
             .iter()
             .filter_map(|obj| obj.key().map(String::from))
-            .filter(|k| k.ends_with(".jsonl") || k.ends_with(".json"))
+            .filter(|k| {
+                let path = std::path::Path::new(k);
+                path.extension().is_some_and(|ext| {
+                    ext.eq_ignore_ascii_case("jsonl") || ext.eq_ignore_ascii_case("json")
+                })
+            })
             .collect();
         
         Ok(files)
@@ -361,6 +376,7 @@ impl OpenEewClient {
     }
 
     /// Fetch records for a date across all devices and run detection.
+    #[allow(dead_code)]
     pub async fn analyze_date(
         &self,
         country: &str,
@@ -402,8 +418,8 @@ mod tests {
 
     #[test]
     fn test_pga_calculation() {
-        // 3-4-5 triangle: sqrt(9 + 16 + 0) = 5
-        assert!((StaLtaDetector::calculate_pga(3.0, 4.0, 0.0) - 5.0).abs() < 0.001);
+        // 3-4-5 triangle: sqrt(9 + 16 + 0) = 5 g = 4900 gals
+        assert!((StaLtaDetector::calculate_pga(3.0, 4.0, 0.0) - 4900.0).abs() < 0.01);
     }
 
     #[test]
@@ -0,0 +1,625 @@
+//! EMSC `SeismicPortal` client.
+//!
+//! Queries the FDSN event service of the European-Mediterranean
+//! Seismological Centre (`format=json`, a GeoJSON dialect) and normalizes
+//! the events to the USGS [`Feature`] model. [`EmscPush`] consumes the
+//! real-time WebSocket stream of the same events.
+//! See <https://www.seismicportal.eu/fdsn-wsevent.html> and
+//! <https://www.seismicportal.eu/realtime.html>.
+
+use std::collections::HashSet;
+
+use chrono::{SecondsFormat, Utc};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tokio_stream::StreamExt;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, instrument, warn};
+
+use crate::client::{FeedFetch, FeedType, async_http_client, send_async};
+use crate::errors::SeismotailError;
+use crate::models::{BodyFormat, Feature, FeatureCollection, Geometry, Properties, RawBody};
+use crate::query::parse_time;
+use crate::retry::RetryPolicy;
+use crate::source::{ChangeTracker, EventSource, SourceFuture};
+use crate::types::{EventStatus, EventType, Published};
+
+/// EMSC `SeismicPortal` base URL.
+pub const EMSC_BASE_URL: &str = "https://www.seismicportal.eu";
+
+/// EMSC real-time WebSocket endpoint.
+pub const EMSC_WS_URL: &str = "wss://www.seismicportal.eu/standing_order/websocket";
+
+/// Events requested per page (the service default is only 10).
+const PAGE_SIZE: usize = 2000;
+
+/// Pages fetched per poll; a busier window is cut short with a warning.
+const MAX_PAGES: usize = 25;
+
+/// GeoJSON response of the EMSC FDSN service.
+#[derive(Debug, Deserialize)]
+struct EmscCollection {
+    features: Vec<EmscFeature>,
+}
+
+/// A single EMSC event.
+#[derive(Debug, Deserialize)]
+struct EmscFeature {
+    properties: EmscProperties,
+}
+
+/// A message on the push stream (`action` is "create" or "update").
+#[derive(Debug, Deserialize)]
+struct PushMessage {
+    action: String,
+    data: EmscFeature,
+}
+
+/// EMSC event properties (flat, with depth positive down).
+#[derive(Debug, Deserialize)]
+struct EmscProperties {
+    unid: String,
+    time: String,
+    lastupdate: Option<String>,
+    lat: f64,
+    lon: f64,
+    depth: Option<f64>,
+    mag: Option<f64>,
+    magtype: Option<String>,
+    flynn_region: Option<String>,
+    auth: Option<String>,
+    evtype: Option<String>,
+    source_id: Option<String>,
+}
+
+/// Client for the EMSC FDSN event service.
+pub struct EmscClient {
+    client: reqwest::Client,
+    base_url: String,
+    feed: FeedType,
+    retry: RetryPolicy,
+    tracker: ChangeTracker,
+    page_size: usize,
+}
+
+impl EmscClient {
+    /// Create a client for the recent-events window of `feed`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be initialized.
+    pub fn with_base_url(base_url: &str, feed: FeedType) -> Result<Self, SeismotailError> {
+        Ok(Self {
+            client: async_http_client()?,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            feed,
+            retry: RetryPolicy::default(),
+            tracker: ChangeTracker::default(),
+            page_size: PAGE_SIZE,
+        })
+    }
+
+    /// Use a different retry policy.
+    #[must_use]
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Fetch events of the feed window, newest first.
+    ///
+    /// Large windows are fetched in pages of [`PAGE_SIZE`] events.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a request fails or a response cannot be parsed.
+    #[instrument(skip(self), fields(feed = self.feed.as_str()))]
+    pub async fn fetch_recent(&self) -> Result<FeatureCollection, SeismotailError> {
+        let start = (Utc::now() - self.feed.window()).to_rfc3339_opts(SecondsFormat::Secs, true);
+        let mut features = Vec::new();
+        let mut bodies = Vec::new();
+        let mut ids = HashSet::new();
+
+        for page in 0..MAX_PAGES {
+            let Some(body) = self.fetch_page(&start, page * self.page_size + 1).await? else {
+                break;
+            };
+            let collection: EmscCollection = serde_json::from_str(&body)?;
+            let rows = collection.features.len();
+            // Events published between pages shift the offsets; keep the first copy
+            features.extend(
+                normalize(collection)
+                    .into_iter()
+                    .filter(|f| ids.insert(f.id.clone())),
+            );
+            bodies.push(RawBody {
+                format: BodyFormat::Emsc,
+                text: body,
+            });
+            if rows < self.page_size {
+                debug!("fetched {} events in {} pages", features.len(), page + 1);
+                return Ok(FeatureCollection {
+                    bodies,
+                    ..FeatureCollection::from_features(features)
+                });
+            }
+        }
+
+        warn!(
+            "EMSC {} window has more than {} events; older events were not fetched",
+            self.feed.as_str(),
+            MAX_PAGES * self.page_size
+        );
+        Ok(FeatureCollection {
+            bodies,
+            ..FeatureCollection::from_features(features)
+        })
+    }
+
+    /// Fetch the body of one page of events starting at `offset` (1-based),
+    /// or `None` past the last event.
+    async fn fetch_page(
+        &self,
+        start: &str,
+        offset: usize,
+    ) -> Result<Option<String>, SeismotailError> {
+        let url = format!("{}/fdsnws/event/1/query", self.base_url);
+        let mut params = vec![
+            ("format", "json".to_string()),
+            ("starttime", start.to_string()),
+            ("orderby", "time".to_string()),
+            ("limit", self.page_size.to_string()),
+            ("offset", offset.to_string()),
+        ];
+        if let Some(min) = self.feed.min_magnitude() {
+            params.push(("minmagnitude", min.to_string()));
+        }
+
+        debug!("querying EMSC at {} from event {}", url, offset);
+
+        let response = send_async(&self.retry, || self.client.get(&url).query(&params)).await?;
+        // FDSN signals "no matching events" with 204 No Content
+        if response.status() == reqwest::StatusCode::NO_CONTENT {
+            return Ok(None);
+        }
+
+        Ok(Some(response.text().await?))
+    }
+}
+
+impl EventSource for EmscClient {
+    fn name(&self) -> &'static str {
+        "emsc"
+    }
+
+    fn fetch(&self) -> SourceFuture<'_, FeatureCollection> {
+        Box::pin(self.fetch_recent())
+    }
+
+    fn fetch_if_modified(&self) -> SourceFuture<'_, FeedFetch> {
+        Box::pin(async move { Ok(self.tracker.fetch_result(self.fetch_recent().await?)) })
+    }
+}
+
+/// Client for the EMSC real-time WebSocket stream.
+///
+/// Reconnects with the backoff of its retry policy whenever the stream
+/// drops; the failure count resets once a connection delivers events.
+pub struct EmscPush {
+    url: String,
+    backoff: RetryPolicy,
+    /// Consecutive failed sessions before giving up (None = never)
+    max_reconnects: Option<u32>,
+}
+
+impl EmscPush {
+    /// Create a push client for a WebSocket URL (normally [`EMSC_WS_URL`]).
+    #[must_use]
+    pub fn new(url: &str, backoff: RetryPolicy) -> Self {
+        Self {
+            url: url.to_string(),
+            backoff,
+            max_reconnects: None,
+        }
+    }
+
+    /// Give up after this many consecutive failed sessions.
+    #[cfg(test)]
+    #[must_use]
+    pub fn with_max_reconnects(mut self, max_reconnects: u32) -> Self {
+        self.max_reconnects = Some(max_reconnects);
+        self
+    }
+
+    /// Stream events into `tx` until the receiver is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns the last connection error once `max_reconnects` consecutive
+    /// sessions failed without delivering an event.
+    pub async fn run(&self, tx: mpsc::Sender<Feature>) -> Result<(), SeismotailError> {
+        let mut failures = 0u32;
+
+        loop {
+            let mut received = 0u64;
+            let err = match self.session(&tx, &mut received).await {
+                Ok(true) => return Ok(()),
+                Ok(false) => SeismotailError::WebSocket("stream closed by server".into()),
+                Err(e) => e,
+            };
+
+            if received > 0 {
+                failures = 0;
+            }
+            failures += 1;
+            if self.max_reconnects.is_some_and(|max| failures > max) {
+                return Err(err);
+            }
+
+            let delay = self.backoff.backoff(failures);
+            warn!(
+                "EMSC stream lost ({} consecutive failures), reconnecting in {:?}: {}",
+                failures, delay, err
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Run one connection, counting delivered events in `received`.
+    ///
+    /// Returns `true` if the receiver went away (stop), `false` if the
+    /// server closed the stream (reconnect).
+    async fn session(
+        &self,
+        tx: &mpsc::Sender<Feature>,
+        received: &mut u64,
+    ) -> Result<bool, SeismotailError> {
+        let (mut ws, _) = tokio_tungstenite::connect_async(self.url.as_str())
+            .await
+            .map_err(|e| SeismotailError::WebSocket(e.to_string()))?;
+        info!("connected to EMSC push stream at {}", self.url);
+
+        while let Some(message) = ws.next().await {
+            let message = message.map_err(|e| SeismotailError::WebSocket(e.to_string()))?;
+            let Message::Text(text) = message else {
+                // Pings are answered by the library; close ends the stream
+                continue;
+            };
+
+            let Some(feature) = parse_push(&text) else {
+                continue;
+            };
+            *received += 1;
+            if tx.send(feature).await.is_err() {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+/// Parse a push message into a feature, skipping malformed ones.
+fn parse_push(text: &str) -> Option<Feature> {
+    match serde_json::from_str::<PushMessage>(text) {
+        Ok(message) => {
+            debug!(
+                "EMSC push {} for {}",
+                message.action, message.data.properties.unid
+            );
+            message.data.into_feature()
+        }
+        Err(e) => {
+            warn!("skipping malformed EMSC push message: {}", e);
+            None
+        }
+    }
+}
+
+/// Convert EMSC events to features, skipping ones without a valid time.
+/// Parse a recorded EMSC response body.
+///
+/// # Errors
+///
+/// Returns an error if the body is not an EMSC collection.
+pub fn parse_body(text: &str) -> Result<FeatureCollection, SeismotailError> {
+    Ok(FeatureCollection::from_features(normalize(
+        serde_json::from_str(text)?,
+    )))
+}
+
+fn normalize(collection: EmscCollection) -> Vec<Feature> {
+    collection
+        .features
+        .into_iter()
+        .filter_map(EmscFeature::into_feature)
+        .collect()
+}
+
+impl EmscFeature {
+    /// Normalize to the USGS model; `None` if the time is invalid.
+    fn into_feature(self) -> Option<Feature> {
+        let p = self.properties;
+        let Ok(time) = parse_time(&p.time) else {
+            debug!(
+                "skipping EMSC event {} with invalid time {:?}",
+                p.unid, p.time
+            );
+            return None;
+        };
+        let time = time.timestamp_millis();
+        let updated = p
+            .lastupdate
+            .as_deref()
+            .and_then(|t| parse_time(t).ok())
+            .map_or(time, |t| t.timestamp_millis());
+        let place = p.flynn_region.as_deref().map(title_case);
+        let title = match (p.mag, &place) {
+            (Some(mag), Some(place)) => Some(format!("M {mag:.1} - {place}")),
+            _ => None,
+        };
+
+        Some(Feature {
+            type_: "Feature".into(),
+            id: p.unid.clone(),
+            geometry: Geometry {
+                type_: "Point".into(),
+                coordinates: vec![p.lon, p.lat, p.depth.unwrap_or(0.0)],
+            },
+            properties: Properties {
+                mag: p.mag,
+                mag_type: p.magtype.as_deref().map(Published::from),
+                place,
+                time,
+                updated,
+                // EMSC publishes no review state
+                status: EventStatus::Automatic.into(),
+                net: p.auth.as_deref().unwrap_or("emsc").to_lowercase(),
+                code: p.source_id.unwrap_or_else(|| p.unid.clone()),
+                sources: Some(",emsc,".into()),
+                url: Some(format!(
+                    "https://www.seismicportal.eu/eventdetails.html?unid={}",
+                    p.unid
+                )),
+                title,
+                event_type: Some(event_type(p.evtype.as_deref()).into()),
+                ..Properties::default()
+            },
+            regions: Vec::new(),
+            home: None,
+            nearest_place: None,
+            zones: Vec::new(),
+        })
+    }
+}
+
+/// Map an EMSC event type code (`ke`, `qb`, ...) to a USGS event type.
+fn event_type(code: Option<&str>) -> EventType {
+    match code {
+        Some("qb" | "sqb") => EventType::QuarryBlast,
+        Some("kx" | "sx") => EventType::Explosion,
+        Some("kr" | "sr") => EventType::RockBurst,
+        _ => EventType::Earthquake,
+    }
+}
+
+/// Turn an upper-case Flinn-Engdahl region name into title case.
+fn title_case(s: &str) -> String {
+    s.split(' ')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first
+                    .to_uppercase()
+                    .chain(chars.flat_map(char::to_lowercase))
+                    .collect()
+            })
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::annotate::Annotator;
+    use crate::filters::EventFilter;
+    use crate::live::{Emitted, LiveProcessor};
+    use crate::models::SchemaVersion;
+    use crate::output::Format;
+    use crate::test_support::{
+        Response, SAMPLE_EMSC, SAMPLE_EMSC_WS, StandInServer, start_ws_stand_in,
+    };
+
+    #[test]
+    fn test_normalize_sample() {
+        let collection: EmscCollection = serde_json::from_str(SAMPLE_EMSC).unwrap();
+        let features = normalize(collection);
+        assert_eq!(features.len(), 3);
+
+        let first = &features[0];
+        assert_eq!(first.id, "20241203_0000142");
+        assert_eq!(
+            first.properties.place.as_deref(),
+            Some("Dodecanese Islands, Greece")
+        );
+        assert_eq!(first.properties.mag, Some(4.1));
+        assert_eq!(first.properties.net, "noa");
+        assert!((first.depth_km() - 10.0).abs() < f64::EPSILON);
+        assert!(first.properties.updated > first.properties.time);
+        first.validate().unwrap();
+
+        // Missing magnitude is kept, not guessed
+        assert_eq!(features[2].properties.mag, None);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_sends_fdsn_parameters() {
+        let server = StandInServer::start(|_| Response::json(SAMPLE_EMSC));
+        let client = EmscClient::with_base_url(server.url(), FeedType::Mag25Day).unwrap();
+
+        let feed = client.fetch().await.unwrap();
+        assert_eq!(feed.features.len(), 3);
+        assert_eq!(feed.metadata.count, 3);
+
+        let requests = server.requests();
+        let req = &requests[0];
+        assert_eq!(req.path, "/fdsnws/event/1/query");
+        assert_eq!(req.param("format"), Some("json"));
+        assert_eq!(req.param("minmagnitude"), Some("2.5"));
+        assert!(req.param("starttime").is_some());
+
+        // Unchanged result on the next poll
+        assert!(matches!(
+            client.fetch_if_modified().await.unwrap(),
+            FeedFetch::Modified(_)
+        ));
+        assert!(matches!(
+            client.fetch_if_modified().await.unwrap(),
+            FeedFetch::NotModified
+        ));
+    }
+
+    /// Serve the sample events `limit` at a time from `offset` (1-based).
+    fn paged_server() -> StandInServer {
+        StandInServer::start(|req| {
+            let mut page: serde_json::Value = serde_json::from_str(SAMPLE_EMSC).unwrap();
+            let offset: usize = req.param("offset").unwrap().parse().unwrap();
+            let limit: usize = req.param("limit").unwrap().parse().unwrap();
+            let features = page["features"].as_array_mut().unwrap();
+            let rows: Vec<_> = features.drain(..).skip(offset - 1).take(limit).collect();
+            if rows.is_empty() {
+                return Response::status(204);
+            }
+            *features = rows;
+            Response::json(page.to_string())
+        })
+    }
+
+    #[tokio::test]
+    async fn test_fetch_pages_through_large_windows() {
+        let server = paged_server();
+        let mut client = EmscClient::with_base_url(server.url(), FeedType::AllMonth).unwrap();
+        client.page_size = 2;
+
+        let feed = client.fetch().await.unwrap();
+        assert_eq!(feed.features.len(), 3);
+        let offsets: Vec<_> = server
+            .requests()
+            .iter()
+            .map(|r| r.param("offset").unwrap().to_string())
+            .collect();
+        assert_eq!(offsets, ["1", "3"]);
+
+        // A full last page asks once more and gets 204
+        client.page_size = 3;
+        assert_eq!(client.fetch().await.unwrap().features.len(), 3);
+        assert_eq!(server.requests().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_stops_at_page_limit() {
+        // The same full page every time, as if the window never ended
+        let server = StandInServer::start(|_| Response::json(SAMPLE_EMSC));
+        let mut client = EmscClient::with_base_url(server.url(), FeedType::AllMonth).unwrap();
+        client.page_size = 3;
+
+        let feed = client.fetch().await.unwrap();
+        assert_eq!(server.requests().len(), MAX_PAGES);
+        // Repeated events are kept once
+        assert_eq!(feed.features.len(), 3);
+    }
+
+    /// Run a push client against scripted sessions and collect its events.
+    async fn collect_push(sessions: Vec<Vec<String>>) -> (Vec<Feature>, SeismotailError) {
+        let url = start_ws_stand_in(sessions).await;
+        let backoff = RetryPolicy {
+            base_delay: std::time::Duration::from_millis(1),
+            ..RetryPolicy::default()
+        };
+        let push = EmscPush::new(&url, backoff).with_max_reconnects(1);
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let handle = tokio::spawn(async move { push.run(tx).await });
+        let mut events = Vec::new();
+        while let Some(event) = rx.recv().await {
+            events.push(event);
+        }
+        (events, handle.await.unwrap().unwrap_err())
+    }
+
+    fn recorded_messages() -> Vec<String> {
+        SAMPLE_EMSC_WS.lines().map(String::from).collect()
+    }
+
+    #[tokio::test]
+    async fn test_push_reconnects_and_gives_up() {
+        let mut messages = recorded_messages();
+        let second = messages.split_off(2);
+        // A malformed message is skipped without dropping the session
+        let first = [messages, vec!["{\"action\":\"ping\"}".into()]].concat();
+
+        let (events, err) = collect_push(vec![first, second]).await;
+
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[0].id, "20241203_0000131");
+        assert_eq!(events[3].properties.mag, Some(4.3));
+        assert!(matches!(err, SeismotailError::WebSocket(_)));
+    }
+
+    #[tokio::test]
+    async fn test_push_output_matches_polling() {
+        let format = Format::Ndjson;
+
+        // Polling: one snapshot of the same three events
+        let snapshot: EmscCollection = serde_json::from_str(SAMPLE_EMSC).unwrap();
+        let mut poll = LiveProcessor::new(
+            EventFilter::default(),
+            Annotator::default(),
+            format,
+            SchemaVersion::V2,
+        );
+        let mut poll_out = Vec::new();
+        for event in normalize(snapshot) {
+            poll.process(&mut poll_out, &event);
+        }
+
+        // Push: three creates, an update and a re-sent create
+        let (events, _) = collect_push(vec![recorded_messages()]).await;
+        let mut push = LiveProcessor::new(
+            EventFilter::default(),
+            Annotator::default(),
+            format,
+            SchemaVersion::V2,
+        );
+        let mut push_out = Vec::new();
+        let emitted: Vec<_> = events
+            .iter()
+            .map(|e| push.process(&mut push_out, e))
+            .collect();
+        assert_eq!(
+            emitted,
+            [
+                Emitted::New,
+                Emitted::New,
+                Emitted::New,
+                Emitted::Update,
+                Emitted::Skipped
+            ]
+        );
+
+        let sorted = |out: &[u8]| {
+            let mut lines: Vec<String> = String::from_utf8_lossy(out)
+                .lines()
+                .map(String::from)
+                .collect();
+            lines.sort();
+            lines
+        };
+        let push_lines = sorted(&push_out);
+        let poll_lines = sorted(&poll_out);
+        // The creates are byte-identical to the polled events
+        assert!(poll_lines.iter().all(|line| push_lines.contains(line)));
+        assert_eq!(push_lines.len(), poll_lines.len() + 1);
+    }
+}
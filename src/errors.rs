@@ -2,6 +2,8 @@
 //!
 //! Uses `thiserror` for library-style error definitions.
 
+use std::time::Duration;
+
 use thiserror::Error;
 
 /// Errors that can occur in seismotail operations.
@@ -11,19 +13,78 @@ pub enum SeismotailError {
     #[error("HTTP request failed: {0}")]
     Http(#[from] reqwest::Error),
 
+    /// Request timed out before a response arrived
+    #[error("Request timed out: {0}")]
+    Timeout(String),
+
+    /// WebSocket connection or protocol failure
+    #[error("WebSocket error: {0}")]
+    WebSocket(String),
+
     /// JSON parsing failed
     #[error("Failed to parse JSON: {0}")]
     Parse(#[from] serde_json::Error),
 
-    /// API returned an error status
-    #[error("USGS API error (HTTP {status}): {message}")]
+    /// API rejected the request (4xx other than 429)
+    #[error("API error (HTTP {status}): {message}")]
     Api { status: u16, message: String },
 
+    /// API asked us to slow down (429, or 503 with `Retry-After`)
+    #[error("API throttled the request (HTTP {status})")]
+    Throttled {
+        status: u16,
+        retry_after: Option<Duration>,
+    },
+
+    /// API failed on its side (5xx)
+    #[error("Server error (HTTP {status}): {message}")]
+    Server { status: u16, message: String },
+
     /// Invalid response structure
     #[error("Invalid response: {0}")]
     InvalidResponse(String),
 
+    /// Failure replayed from a recorded poll
+    #[error("Recorded fetch failure: {0}")]
+    Recorded(String),
+
+    /// Local input file, directory or stdin could not be read
+    #[error("Failed to read input: {0}")]
+    Input(String),
+
     /// Event validation failed
     #[error("Invalid event data: {0}")]
     Validation(String),
 }
+
+impl SeismotailError {
+    /// Whether the same request may succeed if retried later.
+    ///
+    /// Timeouts, connection failures, throttling and 5xx are transient;
+    /// 4xx and malformed responses are not.
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Timeout(_)
+            | Self::Throttled { .. }
+            | Self::Server { .. }
+            | Self::WebSocket(_)
+            | Self::Recorded(_) => true,
+            Self::Http(e) => e.is_timeout() || e.is_connect() || e.is_request(),
+            Self::Parse(_)
+            | Self::Api { .. }
+            | Self::InvalidResponse(_)
+            | Self::Input(_)
+            | Self::Validation(_) => false,
+        }
+    }
+
+    /// Delay requested by the server before retrying, if any.
+    #[must_use]
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::Throttled { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
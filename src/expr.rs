@@ -0,0 +1,731 @@
+//! Filter expressions for `--where`.
+//!
+//! A small boolean language over event fields, e.g.
+//! `mag >= 4.5 && depth < 70 && (alert >= yellow || tsunami) && place ~ "Alaska"`.
+//! Expressions are type-checked when parsed, so a typo in a field name or a
+//! comparison that can never hold is reported up front, with the offending
+//! span underlined, rather than silently filtering out every event.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Range;
+
+use crate::models::Feature;
+use crate::types::{AlertLevel, EventStatus, EventType, MagnitudeType};
+
+/// A parsed, type-checked filter expression.
+#[derive(Debug, Clone)]
+pub struct FilterExpr {
+    root: Node,
+}
+
+impl FilterExpr {
+    /// Parse and type-check an expression.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first syntax or type error with its span in `source`.
+    pub fn parse(source: &str) -> Result<Self, ExprError> {
+        let tokens = lex(source)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let root = parser.or()?;
+        let next = parser.peek();
+        if next.tok != Tok::End {
+            return Err(ExprError::new(
+                format!("unexpected {}", next.tok),
+                next.span.clone(),
+            ));
+        }
+        Ok(Self { root })
+    }
+
+    /// Evaluate the expression against an event.
+    ///
+    /// Comparisons on a value the event does not have (e.g. `felt > 10`
+    /// without felt reports) are false.
+    #[must_use]
+    pub fn matches(&self, event: &Feature) -> bool {
+        self.root.eval(event)
+    }
+}
+
+impl std::str::FromStr for FilterExpr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s).map_err(|e| e.render(s))
+    }
+}
+
+/// A syntax or type error at a span of the expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExprError {
+    pub message: String,
+    /// Byte range of the offending text
+    pub span: Range<usize>,
+}
+
+impl ExprError {
+    fn new(message: impl Into<String>, span: Range<usize>) -> Self {
+        Self {
+            message: message.into(),
+            span,
+        }
+    }
+
+    /// Format the error with the expression and the span underlined.
+    #[must_use]
+    pub fn render(&self, source: &str) -> String {
+        let start = source
+            .get(..self.span.start)
+            .map_or(0, |s| s.chars().count());
+        let width = source
+            .get(self.span.clone())
+            .map_or(1, |s| s.chars().count().max(1));
+        format!(
+            "{}\n  {source}\n  {}{}",
+            self.message,
+            " ".repeat(start),
+            "^".repeat(width)
+        )
+    }
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (at {}..{})",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
+
+/// Event fields usable in expressions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Mag,
+    Depth,
+    Lat,
+    Lon,
+    Sig,
+    Felt,
+    Cdi,
+    Mmi,
+    Nst,
+    Gap,
+    Rms,
+    Dmin,
+    Alert,
+    Status,
+    Type,
+    MagType,
+    Tsunami,
+    Place,
+    Title,
+    Net,
+    Sources,
+    Id,
+}
+
+/// Value type of a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Number,
+    Alert,
+    Status,
+    Type,
+    MagType,
+    Bool,
+    Text,
+}
+
+impl Field {
+    /// Look up a field by name or alias.
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_lowercase().as_str() {
+            "mag" | "magnitude" => Self::Mag,
+            "depth" | "depth_km" => Self::Depth,
+            "lat" | "latitude" => Self::Lat,
+            "lon" | "longitude" => Self::Lon,
+            "sig" | "significance" => Self::Sig,
+            "felt" => Self::Felt,
+            "cdi" => Self::Cdi,
+            "mmi" => Self::Mmi,
+            "nst" => Self::Nst,
+            "gap" => Self::Gap,
+            "rms" => Self::Rms,
+            "dmin" => Self::Dmin,
+            "alert" => Self::Alert,
+            "status" => Self::Status,
+            "type" | "event_type" => Self::Type,
+            "magtype" | "mag_type" | "magnitude_type" => Self::MagType,
+            "tsunami" => Self::Tsunami,
+            "place" => Self::Place,
+            "title" => Self::Title,
+            "net" => Self::Net,
+            "sources" => Self::Sources,
+            "id" => Self::Id,
+            _ => return None,
+        })
+    }
+
+    fn kind(self) -> Kind {
+        match self {
+            Self::Mag
+            | Self::Depth
+            | Self::Lat
+            | Self::Lon
+            | Self::Sig
+            | Self::Felt
+            | Self::Cdi
+            | Self::Mmi
+            | Self::Nst
+            | Self::Gap
+            | Self::Rms
+            | Self::Dmin => Kind::Number,
+            Self::Alert => Kind::Alert,
+            Self::Status => Kind::Status,
+            Self::Type => Kind::Type,
+            Self::MagType => Kind::MagType,
+            Self::Tsunami => Kind::Bool,
+            Self::Place | Self::Title | Self::Net | Self::Sources | Self::Id => Kind::Text,
+        }
+    }
+
+    fn number(self, event: &Feature) -> Option<f64> {
+        let p = &event.properties;
+        match self {
+            Self::Mag => p.mag,
+            Self::Depth => Some(event.depth_km()),
+            Self::Lat => Some(event.latitude()),
+            Self::Lon => Some(event.longitude()),
+            Self::Sig => Some(f64::from(p.sig)),
+            Self::Felt => p.felt.map(f64::from),
+            Self::Cdi => p.cdi,
+            Self::Mmi => p.mmi,
+            Self::Nst => p.nst.map(f64::from),
+            Self::Gap => p.gap,
+            Self::Rms => p.rms,
+            Self::Dmin => p.dmin,
+            _ => None,
+        }
+    }
+
+    fn text(self, event: &Feature) -> Option<&str> {
+        let p = &event.properties;
+        match self {
+            Self::Place => p.place.as_deref(),
+            Self::Title => p.title.as_deref(),
+            Self::Net => Some(&p.net),
+            Self::Sources => p.sources.as_deref(),
+            Self::Id => Some(&event.id),
+            _ => None,
+        }
+    }
+}
+
+/// Comparison operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// Case-insensitive substring match
+    Contains,
+    NotContains,
+}
+
+impl CmpOp {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Contains => "~",
+            Self::NotContains => "!~",
+        }
+    }
+
+    /// Whether the operator holds for `lhs.cmp(rhs)`.
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Self::Eq => ordering == Ordering::Equal,
+            Self::Ne => ordering != Ordering::Equal,
+            Self::Lt => ordering == Ordering::Less,
+            Self::Le => ordering != Ordering::Greater,
+            Self::Gt => ordering == Ordering::Greater,
+            Self::Ge => ordering != Ordering::Less,
+            Self::Contains | Self::NotContains => false,
+        }
+    }
+
+    fn is_equality(self) -> bool {
+        matches!(self, Self::Eq | Self::Ne)
+    }
+
+    fn is_ordering(self) -> bool {
+        matches!(
+            self,
+            Self::Eq | Self::Ne | Self::Lt | Self::Le | Self::Gt | Self::Ge
+        )
+    }
+}
+
+/// A literal after type checking against its field.
+#[derive(Debug, Clone)]
+enum Value {
+    Number(f64),
+    Alert(AlertLevel),
+    Status(EventStatus),
+    Type(EventType),
+    MagType(MagnitudeType),
+    Bool(bool),
+    Text(String),
+}
+
+/// Expression tree.
+#[derive(Debug, Clone)]
+enum Node {
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Not(Box<Node>),
+    Compare(Field, CmpOp, Value),
+}
+
+impl Node {
+    fn eval(&self, event: &Feature) -> bool {
+        match self {
+            Self::And(a, b) => a.eval(event) && b.eval(event),
+            Self::Or(a, b) => a.eval(event) || b.eval(event),
+            Self::Not(a) => !a.eval(event),
+            Self::Compare(field, op, value) => compare(*field, *op, value, event),
+        }
+    }
+}
+
+/// Evaluate one comparison.
+fn compare(field: Field, op: CmpOp, value: &Value, event: &Feature) -> bool {
+    let p = &event.properties;
+    match value {
+        Value::Number(rhs) => field
+            .number(event)
+            .and_then(|lhs| lhs.partial_cmp(rhs))
+            .is_some_and(|o| op.holds(o)),
+        Value::Alert(rhs) => p.alert.as_deref().is_some_and(|lhs| op.holds(lhs.cmp(rhs))),
+        Value::Status(rhs) => op.holds(equality(&p.status == rhs)),
+        Value::Type(rhs) => {
+            // Events without a type are earthquakes, as in `--type`
+            let lhs = p.event_type.as_deref().unwrap_or(&EventType::Earthquake);
+            op.holds(equality(lhs == rhs))
+        }
+        Value::MagType(rhs) => p
+            .mag_type
+            .as_ref()
+            .is_some_and(|lhs| op.holds(equality(lhs == rhs))),
+        Value::Bool(rhs) => op.holds(equality((p.tsunami != 0) == *rhs)),
+        Value::Text(rhs) => field.text(event).is_some_and(|lhs| {
+            let lhs = lhs.to_lowercase();
+            match op {
+                CmpOp::Contains => lhs.contains(rhs.as_str()),
+                CmpOp::NotContains => !lhs.contains(rhs.as_str()),
+                _ => op.holds(equality(lhs == *rhs)),
+            }
+        }),
+    }
+}
+
+/// An equality outcome as an ordering, for [`CmpOp::holds`].
+fn equality(equal: bool) -> Ordering {
+    if equal {
+        Ordering::Equal
+    } else {
+        Ordering::Less
+    }
+}
+
+/// Lexical token.
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Ident(String),
+    Number(f64),
+    Str(String),
+    Op(CmpOp),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+    End,
+}
+
+impl fmt::Display for Tok {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ident(s) => write!(f, "'{s}'"),
+            Self::Number(n) => write!(f, "number {n}"),
+            Self::Str(s) => write!(f, "string \"{s}\""),
+            Self::Op(op) => write!(f, "'{}'", op.as_str()),
+            Self::And => f.write_str("'&&'"),
+            Self::Or => f.write_str("'||'"),
+            Self::Not => f.write_str("'!'"),
+            Self::LParen => f.write_str("'('"),
+            Self::RParen => f.write_str("')'"),
+            Self::End => f.write_str("end of expression"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    tok: Tok,
+    span: Range<usize>,
+}
+
+/// Split an expression into tokens, ending with [`Tok::End`].
+fn lex(source: &str) -> Result<Vec<Token>, ExprError> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+        let start = i;
+        let two = source.get(i..i + 2).unwrap_or("");
+
+        let tok = match c {
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                i += 1;
+                continue;
+            }
+            b'(' => {
+                i += 1;
+                Tok::LParen
+            }
+            b')' => {
+                i += 1;
+                Tok::RParen
+            }
+            b'"' | b'\'' => {
+                let end = source[i + 1..]
+                    .find(char::from(c))
+                    .map(|n| i + 1 + n)
+                    .ok_or_else(|| ExprError::new("unterminated string", start..source.len()))?;
+                i = end + 1;
+                Tok::Str(source[start + 1..end].to_string())
+            }
+            _ if c.is_ascii_digit()
+                || (c == b'-' || c == b'.')
+                    && bytes
+                        .get(i + 1)
+                        .is_some_and(|b| b.is_ascii_digit() || *b == b'.') =>
+            {
+                i += 1;
+                while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+                    i += 1;
+                }
+                let text = &source[start..i];
+                let n = text
+                    .parse()
+                    .map_err(|_| ExprError::new(format!("invalid number '{text}'"), start..i))?;
+                Tok::Number(n)
+            }
+            _ if c.is_ascii_alphabetic() || c == b'_' => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                match source[start..i].to_lowercase().as_str() {
+                    "and" => Tok::And,
+                    "or" => Tok::Or,
+                    "not" => Tok::Not,
+                    _ => Tok::Ident(source[start..i].to_string()),
+                }
+            }
+            _ => {
+                let (tok, len) = match two {
+                    "&&" => (Tok::And, 2),
+                    "||" => (Tok::Or, 2),
+                    "==" => (Tok::Op(CmpOp::Eq), 2),
+                    "!=" => (Tok::Op(CmpOp::Ne), 2),
+                    "<=" => (Tok::Op(CmpOp::Le), 2),
+                    ">=" => (Tok::Op(CmpOp::Ge), 2),
+                    "!~" => (Tok::Op(CmpOp::NotContains), 2),
+                    _ => match c {
+                        b'=' => (Tok::Op(CmpOp::Eq), 1),
+                        b'<' => (Tok::Op(CmpOp::Lt), 1),
+                        b'>' => (Tok::Op(CmpOp::Gt), 1),
+                        b'~' => (Tok::Op(CmpOp::Contains), 1),
+                        b'!' => (Tok::Not, 1),
+                        _ => {
+                            let ch = source[i..].chars().next().unwrap_or('?');
+                            return Err(ExprError::new(
+                                format!("unexpected character '{ch}'"),
+                                i..i + ch.len_utf8(),
+                            ));
+                        }
+                    },
+                };
+                i += len;
+                tok
+            }
+        };
+        tokens.push(Token {
+            tok,
+            span: start..i,
+        });
+    }
+
+    tokens.push(Token {
+        tok: Tok::End,
+        span: source.len()..source.len(),
+    });
+    Ok(tokens)
+}
+
+/// Recursive-descent parser; `&&` binds tighter than `||`.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        // The token list always ends with `End`, which is never consumed
+        &self.tokens[self.pos.min(self.tokens.len() - 1)]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.peek().clone();
+        if token.tok != Tok::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn or(&mut self) -> Result<Node, ExprError> {
+        let mut node = self.and()?;
+        while self.peek().tok == Tok::Or {
+            self.next();
+            node = Node::Or(Box::new(node), Box::new(self.and()?));
+        }
+        Ok(node)
+    }
+
+    fn and(&mut self) -> Result<Node, ExprError> {
+        let mut node = self.unary()?;
+        while self.peek().tok == Tok::And {
+            self.next();
+            node = Node::And(Box::new(node), Box::new(self.unary()?));
+        }
+        Ok(node)
+    }
+
+    fn unary(&mut self) -> Result<Node, ExprError> {
+        if self.peek().tok == Tok::Not {
+            self.next();
+            return Ok(Node::Not(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Node, ExprError> {
+        let token = self.next();
+        match token.tok {
+            Tok::LParen => {
+                let node = self.or()?;
+                let close = self.next();
+                if close.tok != Tok::RParen {
+                    return Err(ExprError::new(
+                        format!(
+                            "expected ')' to close '(' at {}, found {}",
+                            token.span.start, close.tok
+                        ),
+                        close.span,
+                    ));
+                }
+                Ok(node)
+            }
+            Tok::Ident(name) => self.comparison(&name, token.span),
+            other => Err(ExprError::new(
+                format!("expected a field name, found {other}"),
+                token.span,
+            )),
+        }
+    }
+
+    /// Parse `field [op literal]` and type-check it.
+    fn comparison(&mut self, name: &str, span: Range<usize>) -> Result<Node, ExprError> {
+        let field = Field::from_name(name).ok_or_else(|| {
+            ExprError::new(
+                format!(
+                    "unknown field '{name}' (expected one of: mag, depth, lat, lon, sig, felt, cdi, \
+                     mmi, nst, gap, rms, dmin, alert, status, type, magtype, tsunami, place, title, \
+                     net, sources, id)"
+                ),
+                span.clone(),
+            )
+        })?;
+
+        let Tok::Op(op) = self.peek().tok else {
+            // A bare field is only meaningful for booleans
+            if field.kind() == Kind::Bool {
+                return Ok(Node::Compare(field, CmpOp::Eq, Value::Bool(true)));
+            }
+            return Err(ExprError::new(
+                format!("'{name}' needs a comparison, e.g. '{name} == ...'"),
+                span,
+            ));
+        };
+        let op_span = self.next().span;
+
+        let allowed = match field.kind() {
+            Kind::Number | Kind::Alert => op.is_ordering(),
+            Kind::Status | Kind::Type | Kind::MagType | Kind::Bool => op.is_equality(),
+            Kind::Text => op.is_equality() || matches!(op, CmpOp::Contains | CmpOp::NotContains),
+        };
+        if !allowed {
+            return Err(ExprError::new(
+                format!("operator '{}' cannot be applied to '{name}'", op.as_str()),
+                op_span,
+            ));
+        }
+
+        let literal = self.next();
+        let value = literal_value(field, name, &literal)?;
+        Ok(Node::Compare(field, op, value))
+    }
+}
+
+/// Check a literal against the type of `field`.
+fn literal_value(field: Field, name: &str, literal: &Token) -> Result<Value, ExprError> {
+    let word = match &literal.tok {
+        Tok::Ident(s) | Tok::Str(s) => Some(s.as_str()),
+        _ => None,
+    };
+    let mismatch = |expected: &str| {
+        ExprError::new(
+            format!("'{name}' compares with {expected}, found {}", literal.tok),
+            literal.span.clone(),
+        )
+    };
+
+    match (field.kind(), &literal.tok) {
+        (Kind::Number, Tok::Number(n)) => Ok(Value::Number(*n)),
+        (Kind::Number, _) => Err(mismatch("a number")),
+        (Kind::Bool, Tok::Ident(s)) if s.eq_ignore_ascii_case("true") => Ok(Value::Bool(true)),
+        (Kind::Bool, Tok::Ident(s)) if s.eq_ignore_ascii_case("false") => Ok(Value::Bool(false)),
+        (Kind::Bool, _) => Err(mismatch("true or false")),
+        (Kind::Alert, _) => match word.map(AlertLevel::from) {
+            Some(level) if level.is_known() => Ok(Value::Alert(level)),
+            _ => Err(mismatch("an alert level (green, yellow, orange, red)")),
+        },
+        (Kind::Status, _) => match word.map(EventStatus::from) {
+            Some(EventStatus::Unknown(_)) | None => {
+                Err(mismatch("a status (automatic, reviewed, deleted)"))
+            }
+            Some(status) => Ok(Value::Status(status)),
+        },
+        // Event and magnitude types are open vocabularies
+        (Kind::Type, _) => word
+            .map(|s| Value::Type(s.into()))
+            .ok_or_else(|| mismatch("an event type")),
+        (Kind::MagType, _) => word
+            .map(|s| Value::MagType(s.into()))
+            .ok_or_else(|| mismatch("a magnitude type")),
+        (Kind::Text, _) => word
+            .map(|s| Value::Text(s.to_lowercase()))
+            .ok_or_else(|| mismatch("text")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FeatureCollection;
+    use crate::test_support::SAMPLE_FEED;
+
+    fn sample_events() -> Vec<Feature> {
+        let feed: FeatureCollection = serde_json::from_str(SAMPLE_FEED).unwrap();
+        feed.features
+    }
+
+    fn matching(expr: &str) -> Vec<String> {
+        let expr: FilterExpr = expr.parse().unwrap();
+        sample_events()
+            .into_iter()
+            .filter(|e| expr.matches(e))
+            .map(|e| e.id)
+            .collect()
+    }
+
+    #[test]
+    fn test_evaluate_against_events() {
+        let events = sample_events();
+        let big = events
+            .iter()
+            .filter(|e| e.properties.mag.is_some_and(|m| m >= 4.5))
+            .count();
+        assert_eq!(matching("mag >= 4.5").len(), big);
+        assert_eq!(matching("not mag >= 4.5").len(), events.len() - big);
+
+        assert_eq!(matching("alert >= green && tsunami"), vec!["us7000n1a2"]);
+        assert_eq!(
+            matching("place ~ 'papua' and status == reviewed"),
+            vec!["us7000n1a2"]
+        );
+        assert!(matching("alert > green").is_empty());
+        assert_eq!(
+            matching("(mag >= 6 || depth < 0) && magtype == MWW").len(),
+            matching("mag >= 6 && magtype = mww").len()
+        );
+        assert_eq!(matching("felt > 10 || !(felt > 10)").len(), events.len());
+    }
+
+    #[test]
+    fn test_missing_type_is_an_earthquake() {
+        let mut event = sample_events().remove(0);
+        event.properties.event_type = None;
+
+        let is_quake: FilterExpr = r#"type == "earthquake""#.parse().unwrap();
+        let not_quake: FilterExpr = "type != earthquake".parse().unwrap();
+        assert!(is_quake.matches(&event));
+        assert!(!not_quake.matches(&event));
+    }
+
+    #[test]
+    fn test_errors_point_at_the_problem() {
+        let err = FilterExpr::parse("mag >= 4.5 && magg < 3").unwrap_err();
+        assert!(err.message.starts_with("unknown field 'magg'"));
+        assert_eq!(err.span, 14..18);
+
+        let err = FilterExpr::parse("place >= 3").unwrap_err();
+        assert_eq!(err.message, "operator '>=' cannot be applied to 'place'");
+        assert_eq!(err.span, 6..8);
+
+        let err = FilterExpr::parse("alert >= purple").unwrap_err();
+        assert_eq!(err.span, 9..15);
+
+        let err = FilterExpr::parse("mag > \"big\"").unwrap_err();
+        assert_eq!(
+            err.message,
+            "'mag' compares with a number, found string \"big\""
+        );
+
+        assert_eq!(FilterExpr::parse("(mag > 4").unwrap_err().span, 8..8);
+        assert_eq!(FilterExpr::parse("mag").unwrap_err().span, 0..3);
+        assert_eq!(FilterExpr::parse("mag > 4 mag").unwrap_err().span, 8..11);
+        assert_eq!(FilterExpr::parse("place ~ \"Alas").unwrap_err().span, 8..13);
+
+        let rendered = "depth < 70 || @".parse::<FilterExpr>().unwrap_err();
+        assert_eq!(
+            rendered,
+            "unexpected character '@'\n  depth < 70 || @\n                ^"
+        );
+    }
+}
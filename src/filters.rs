@@ -4,12 +4,25 @@
 
 use std::f64::consts::PI;
 
+use chrono::{DateTime, Duration, Utc};
+
+use crate::expr::FilterExpr;
+use crate::geodesic::DistanceModel;
 use crate::models::Feature;
+use crate::quality::QualityGrade;
+use crate::query::parse_time;
+use crate::region::Region;
+use crate::types::{AlertLevel, EventStatus, EventType};
+use crate::zones::Zone;
 
 /// Earth radius in kilometers for haversine calculations.
 const EARTH_RADIUS_KM: f64 = 6371.0;
 
 /// Bounding box for geographic filtering.
+///
+/// Longitudes are normalized to [-180, 180]. A box with `min_lon > max_lon`
+/// wraps across the antimeridian: `-25,170,-10,-170` covers 170°E through
+/// 180° to 170°W (Fiji and Tonga).
 #[derive(Debug, Clone, Copy)]
 pub struct BBox {
     pub min_lat: f64,
@@ -33,7 +46,7 @@ impl std::str::FromStr for BBox {
         let vals: Result<Vec<f64>, _> = parts.iter().map(|p| p.trim().parse::<f64>()).collect();
         let vals = vals.map_err(|e| format!("invalid number in bbox: {e}"))?;
 
-        let bbox = Self {
+        let mut bbox = Self {
             min_lat: vals[0],
             min_lon: vals[1],
             max_lat: vals[2],
@@ -47,11 +60,21 @@ impl std::str::FromStr for BBox {
         if bbox.max_lat < -90.0 || bbox.max_lat > 90.0 {
             return Err(format!("max_lat {} out of range [-90, 90]", bbox.max_lat));
         }
-        if bbox.min_lon < -180.0 || bbox.min_lon > 180.0 {
-            return Err(format!("min_lon {} out of range [-180, 180]", bbox.min_lon));
+        // Longitudes may also be given in 0..360 (or -360..0) form, as FDSN
+        // services accept for boxes crossing the antimeridian
+        if bbox.min_lon < -360.0 || bbox.min_lon > 360.0 {
+            return Err(format!("min_lon {} out of range [-360, 360]", bbox.min_lon));
         }
-        if bbox.max_lon < -180.0 || bbox.max_lon > 180.0 {
-            return Err(format!("max_lon {} out of range [-180, 180]", bbox.max_lon));
+        if bbox.max_lon < -360.0 || bbox.max_lon > 360.0 {
+            return Err(format!("max_lon {} out of range [-360, 360]", bbox.max_lon));
+        }
+        if bbox.max_lon - bbox.min_lon >= 360.0 {
+            // Spans every longitude
+            bbox.min_lon = -180.0;
+            bbox.max_lon = 180.0;
+        } else {
+            bbox.min_lon = normalize_lon(bbox.min_lon);
+            bbox.max_lon = normalize_lon(bbox.max_lon);
         }
         if bbox.min_lat > bbox.max_lat {
             return Err(format!(
@@ -68,7 +91,30 @@ impl BBox {
     /// Check if a point is within the bounding box.
     #[must_use]
     pub fn contains(&self, lat: f64, lon: f64) -> bool {
-        lat >= self.min_lat && lat <= self.max_lat && lon >= self.min_lon && lon <= self.max_lon
+        let lon = normalize_lon(lon);
+        let lon_inside = if self.crosses_antimeridian() {
+            lon >= self.min_lon || lon <= self.max_lon
+        } else {
+            lon >= self.min_lon && lon <= self.max_lon
+        };
+        lat >= self.min_lat && lat <= self.max_lat && lon_inside
+    }
+
+    /// Whether the box wraps across ±180° longitude.
+    #[must_use]
+    pub fn crosses_antimeridian(&self) -> bool {
+        self.min_lon > self.max_lon
+    }
+}
+
+/// Map a longitude into [-180, 180], keeping both ±180 as given.
+fn normalize_lon(lon: f64) -> f64 {
+    if lon > 180.0 {
+        lon - 360.0
+    } else if lon < -180.0 {
+        lon + 360.0
+    } else {
+        lon
     }
 }
 
@@ -92,28 +138,18 @@ impl std::str::FromStr for RadiusFilter {
             ));
         }
 
-        let vals: Result<Vec<f64>, _> = parts.iter().map(|p| p.trim().parse::<f64>()).collect();
-        let vals = vals.map_err(|e| format!("invalid number in radius: {e}"))?;
+        let (center_lat, center_lon) = parse_lat_lon(parts[0], parts[1], "radius")?;
+        let radius_km = parts[2]
+            .trim()
+            .parse::<f64>()
+            .map_err(|e| format!("invalid number in radius: {e}"))?;
 
         let filter = Self {
-            center_lat: vals[0],
-            center_lon: vals[1],
-            radius_km: vals[2],
+            center_lat,
+            center_lon,
+            radius_km,
         };
 
-        // Validate
-        if filter.center_lat < -90.0 || filter.center_lat > 90.0 {
-            return Err(format!(
-                "latitude {} out of range [-90, 90]",
-                filter.center_lat
-            ));
-        }
-        if filter.center_lon < -180.0 || filter.center_lon > 180.0 {
-            return Err(format!(
-                "longitude {} out of range [-180, 180]",
-                filter.center_lon
-            ));
-        }
         if filter.radius_km <= 0.0 {
             return Err(format!("radius must be positive, got {}", filter.radius_km));
         }
@@ -123,14 +159,54 @@ impl std::str::FromStr for RadiusFilter {
 }
 
 impl RadiusFilter {
-    /// Check if a point is within the radius using haversine formula.
+    /// Check if a point is within the radius, measured with `model`.
     #[must_use]
-    pub fn contains(&self, lat: f64, lon: f64) -> bool {
-        let distance = haversine_distance(self.center_lat, self.center_lon, lat, lon);
+    pub fn contains(&self, lat: f64, lon: f64, model: DistanceModel) -> bool {
+        let distance = model.distance_km(self.center_lat, self.center_lon, lat, lon);
         distance <= self.radius_km
     }
 }
 
+/// Home location for distance annotations: `lat,lon`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Home {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl std::str::FromStr for Home {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(',').collect();
+        if parts.len() != 2 {
+            return Err(format!(
+                "home requires 2 values (lat,lon), got {}",
+                parts.len()
+            ));
+        }
+        let (lat, lon) = parse_lat_lon(parts[0], parts[1], "home")?;
+        Ok(Self { lat, lon })
+    }
+}
+
+/// Parse and range-check a latitude/longitude pair for the `what` option.
+fn parse_lat_lon(lat: &str, lon: &str, what: &str) -> Result<(f64, f64), String> {
+    let parse = |v: &str| {
+        v.trim()
+            .parse::<f64>()
+            .map_err(|e| format!("invalid number in {what}: {e}"))
+    };
+    let (lat, lon) = (parse(lat)?, parse(lon)?);
+    if !(-90.0..=90.0).contains(&lat) {
+        return Err(format!("latitude {lat} out of range [-90, 90]"));
+    }
+    if !(-180.0..=180.0).contains(&lon) {
+        return Err(format!("longitude {lon} out of range [-180, 180]"));
+    }
+    Ok((lat, lon))
+}
+
 /// Calculate the great-circle distance between two points using the haversine formula.
 ///
 /// Returns distance in kilometers.
@@ -148,14 +224,130 @@ pub fn haversine_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
     EARTH_RADIUS_KM * c
 }
 
+/// Initial bearing of the great circle from point 1 to point 2.
+///
+/// Returns degrees clockwise from north, in [0, 360).
+#[must_use]
+pub fn initial_bearing(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let lat1_rad = lat1.to_radians();
+    let lat2_rad = lat2.to_radians();
+    let delta_lon = (lon2 - lon1).to_radians();
+
+    let y = delta_lon.sin() * lat2_rad.cos();
+    let x = lat1_rad.cos() * lat2_rad.sin() - lat1_rad.sin() * lat2_rad.cos() * delta_lon.cos();
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+/// Name the 16-point compass direction of a bearing, e.g. `"NNE"`.
+#[must_use]
+pub fn compass_point(bearing: f64) -> &'static str {
+    const POINTS: [&str; 16] = [
+        "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW",
+        "NW", "NNW",
+    ];
+    // 22.5° sectors centred on each point
+    let sector = (bearing.rem_euclid(360.0) / 22.5).round();
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let index = sector as usize % POINTS.len();
+    POINTS[index]
+}
+
+/// Time bound for `--since`/`--until`: absolute, or relative to now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeBound {
+    At(DateTime<Utc>),
+    /// This long before the moment of checking, so live windows slide
+    Ago(Duration),
+}
+
+impl TimeBound {
+    /// Resolve to an instant; offsets reaching past the representable
+    /// range resolve to its start.
+    #[must_use]
+    pub fn resolve(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Self::At(t) => *t,
+            Self::Ago(d) => now
+                .checked_sub_signed(*d)
+                .unwrap_or(DateTime::<Utc>::MIN_UTC),
+        }
+    }
+}
+
+impl std::str::FromStr for TimeBound {
+    type Err = String;
+
+    /// Parse `30m`, `2h`, `7d`, `1w` (also `s`), or an absolute time.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (amount, unit) = s.split_at(split);
+        if let Ok(amount) = amount.parse::<i64>() {
+            let duration = match unit {
+                "s" => Duration::try_seconds(amount),
+                "m" => Duration::try_minutes(amount),
+                "h" => Duration::try_hours(amount),
+                "d" => Duration::try_days(amount),
+                "w" => Duration::try_weeks(amount),
+                _ => None,
+            };
+            if let Some(duration) = duration {
+                return Ok(Self::Ago(duration));
+            }
+            if ["s", "m", "h", "d", "w"].contains(&unit) {
+                return Err(format!("time offset '{s}' is too large"));
+            }
+        }
+        parse_time(s)
+            .map(Self::At)
+            .map_err(|_| format!("invalid time '{s}' (expected e.g. 30m, 2h, 7d, 1w, or a date)"))
+    }
+}
+
 /// Combined filter criteria.
 #[derive(Debug, Default, Clone)]
 pub struct EventFilter {
     pub min_magnitude: Option<f64>,
+    pub max_magnitude: Option<f64>,
+    pub min_depth: Option<f64>,
     pub max_depth: Option<f64>,
     pub bbox: Option<BBox>,
     pub radius: Option<RadiusFilter>,
+    /// Polygon regions; an event must lie in at least one
+    pub regions: Vec<Region>,
+    /// Watch zones; an event must trigger at least one
+    pub zones: Vec<Zone>,
+    /// Origin time window
+    pub since: Option<TimeBound>,
+    pub until: Option<TimeBound>,
+    /// Preferred network codes to keep (empty = any)
+    pub networks: Vec<String>,
+    pub exclude_networks: Vec<String>,
+    /// Contributing networks; an event must have at least one of these
+    pub sources: Vec<String>,
+    /// Contributing networks; an event must have none of these
+    pub exclude_sources: Vec<String>,
+    pub status: Option<EventStatus>,
+    /// Event types to keep (empty = any)
+    pub event_types: Vec<EventType>,
+    pub exclude_event_types: Vec<EventType>,
     pub significant_only: bool,
+    pub tsunami_only: bool,
+    pub min_alert: Option<AlertLevel>,
+    pub min_felt: Option<i32>,
+    pub min_mmi: Option<f64>,
+    pub min_cdi: Option<f64>,
+    /// Azimuthal gap limit (degrees)
+    pub max_gap: Option<f64>,
+    /// Travel-time residual limit (seconds)
+    pub max_rms: Option<f64>,
+    pub min_stations: Option<i32>,
+    /// Worst acceptable location quality grade
+    pub min_quality: Option<QualityGrade>,
+    /// `--where` expression, combined with the other criteria
+    pub expr: Option<FilterExpr>,
+    /// How `radius` and zone circles measure distance
+    pub distance_model: DistanceModel,
 }
 
 impl EventFilter {
@@ -166,21 +358,113 @@ impl EventFilter {
             && self.check_depth(event)
             && self.check_bbox(event)
             && self.check_radius(event)
+            && self.check_regions(event)
+            && self.check_zones(event)
+            && self.check_time(event)
+            && self.check_networks(event)
+            && self.check_status(event)
+            && self.check_event_type(event)
             && self.check_significant(event)
+            && self.check_impact(event)
+            && self.check_quality(event)
+            && self.check_expr(event)
     }
 
     fn check_magnitude(&self, event: &Feature) -> bool {
-        match self.min_magnitude {
-            None => true,
-            Some(min) => event.properties.mag.map_or(false, |m| m >= min),
-        }
+        let mag = event.properties.mag;
+        self.min_magnitude
+            .is_none_or(|min| mag.is_some_and(|m| m >= min))
+            && self
+                .max_magnitude
+                .is_none_or(|max| mag.is_some_and(|m| m <= max))
     }
 
     fn check_depth(&self, event: &Feature) -> bool {
-        match self.max_depth {
-            None => true,
-            Some(max) => event.depth_km() <= max,
+        let depth = event.depth_km();
+        self.min_depth.is_none_or(|min| depth >= min)
+            && self.max_depth.is_none_or(|max| depth <= max)
+    }
+
+    fn check_time(&self, event: &Feature) -> bool {
+        if self.since.is_none() && self.until.is_none() {
+            return true;
         }
+        let Some(time) = event.time() else {
+            return false;
+        };
+        let now = Utc::now();
+        self.since.is_none_or(|since| time >= since.resolve(now))
+            && self.until.is_none_or(|until| time <= until.resolve(now))
+    }
+
+    fn check_networks(&self, event: &Feature) -> bool {
+        let net = &event.properties.net;
+        let is_net = |code: &String| code.eq_ignore_ascii_case(net);
+        // `sources` is a list such as ",us,ak,"
+        let contributors: Vec<&str> = event
+            .properties
+            .sources
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .collect();
+        let contributed = |code: &String| contributors.iter().any(|c| code.eq_ignore_ascii_case(c));
+
+        (self.networks.is_empty() || self.networks.iter().any(is_net))
+            && !self.exclude_networks.iter().any(is_net)
+            && (self.sources.is_empty() || self.sources.iter().any(contributed))
+            && !self.exclude_sources.iter().any(contributed)
+    }
+
+    fn check_status(&self, event: &Feature) -> bool {
+        self.status
+            .as_ref()
+            .is_none_or(|s| event.properties.status == *s)
+    }
+
+    fn check_event_type(&self, event: &Feature) -> bool {
+        // Events without a type are treated as earthquakes, as USGS does
+        let event_type = event
+            .properties
+            .event_type
+            .as_deref()
+            .unwrap_or(&EventType::Earthquake);
+        (self.event_types.is_empty() || self.event_types.contains(event_type))
+            && !self.exclude_event_types.contains(event_type)
+    }
+
+    fn check_impact(&self, event: &Feature) -> bool {
+        let p = &event.properties;
+        (!self.tsunami_only || p.tsunami != 0)
+            && self
+                .min_alert
+                .as_ref()
+                .is_none_or(|min| p.alert.as_deref().is_some_and(|a| a >= min))
+            && self
+                .min_felt
+                .is_none_or(|min| p.felt.is_some_and(|f| f >= min))
+            && self
+                .min_mmi
+                .is_none_or(|min| p.mmi.is_some_and(|m| m >= min))
+            && self
+                .min_cdi
+                .is_none_or(|min| p.cdi.is_some_and(|c| c >= min))
+    }
+
+    fn check_quality(&self, event: &Feature) -> bool {
+        let p = &event.properties;
+        self.max_gap
+            .is_none_or(|max| p.gap.is_some_and(|g| g <= max))
+            && self
+                .max_rms
+                .is_none_or(|max| p.rms.is_some_and(|r| r <= max))
+            && self
+                .min_stations
+                .is_none_or(|min| p.nst.is_some_and(|n| n >= min))
+            && self
+                .min_quality
+                .is_none_or(|min| QualityGrade::of(event).is_some_and(|q| q <= min))
     }
 
     fn check_bbox(&self, event: &Feature) -> bool {
@@ -193,16 +477,59 @@ impl EventFilter {
     fn check_radius(&self, event: &Feature) -> bool {
         match &self.radius {
             None => true,
-            Some(radius) => radius.contains(event.latitude(), event.longitude()),
+            Some(radius) => {
+                radius.contains(event.latitude(), event.longitude(), self.distance_model)
+            }
         }
     }
 
+    fn check_regions(&self, event: &Feature) -> bool {
+        self.regions.is_empty()
+            || self
+                .regions
+                .iter()
+                .any(|r| r.contains(event.latitude(), event.longitude()))
+    }
+
+    fn check_zones(&self, event: &Feature) -> bool {
+        self.zones.is_empty()
+            || self
+                .zones
+                .iter()
+                .any(|z| z.matches(event, self.distance_model))
+    }
+
+    /// Record the named regions and watch zones matching `event` on it.
+    pub fn tag_matches(&self, event: &mut Feature) {
+        let (lat, lon) = (event.latitude(), event.longitude());
+        event.regions = self
+            .regions
+            .iter()
+            .filter(|r| r.contains(lat, lon))
+            .filter_map(|r| r.name.clone())
+            .collect();
+        event.zones = self
+            .zones
+            .iter()
+            .filter(|z| z.matches(event, self.distance_model))
+            .map(|z| z.name.clone())
+            .collect();
+    }
+
     fn check_significant(&self, event: &Feature) -> bool {
         if !self.significant_only {
             return true;
         }
-        // Significant = has an alert level set
-        event.properties.alert.is_some()
+        // Significant = has a PAGER alert level set
+        event
+            .properties
+            .alert
+            .as_deref()
+            .is_some_and(AlertLevel::is_known)
+    }
+
+    fn check_expr(&self, event: &Feature) -> bool {
+        self.expr.as_ref().is_none_or(|expr| expr.matches(event))
     }
 }
 
@@ -224,6 +551,43 @@ mod tests {
         assert!(!bbox.contains(50.0, -120.0)); // North of box
     }
 
+    #[test]
+    fn test_bbox_across_antimeridian() {
+        // Fiji and Tonga: 170°E to 170°W
+        let bbox: BBox = "-25,170,-10,-170".parse().unwrap();
+        assert!(bbox.crosses_antimeridian());
+        assert!(bbox.contains(-18.0, 178.0)); // Fiji
+        assert!(bbox.contains(-21.0, -175.2)); // Tonga
+        assert!(bbox.contains(-15.0, 180.0));
+        assert!(bbox.contains(-15.0, -180.0));
+        assert!(!bbox.contains(-15.0, 0.0));
+        assert!(!bbox.contains(-15.0, 160.0));
+        assert!(!bbox.contains(-30.0, 178.0));
+
+        // The same box in 0..360 form normalizes identically
+        let east: BBox = "-25,170,-10,190".parse().unwrap();
+        assert!((east.max_lon - (-170.0)).abs() < 1e-9);
+        assert!(east.contains(-21.0, -175.2));
+
+        // Aleutians
+        let aleutians: BBox = "50,165,56,-160".parse().unwrap();
+        assert!(aleutians.contains(52.0, 174.0));
+        assert!(aleutians.contains(53.0, -168.0));
+        assert!(!aleutians.contains(53.0, -150.0));
+
+        let world: BBox = "-90,0,90,360".parse().unwrap();
+        assert!(!world.crosses_antimeridian());
+        assert!(world.contains(0.0, -90.0));
+
+        assert!("-25,170,-10,400".parse::<BBox>().is_err());
+        assert!(
+            !"32.5,-124.5,42.0,-114.0"
+                .parse::<BBox>()
+                .unwrap()
+                .crosses_antimeridian()
+        );
+    }
+
     #[test]
     fn test_radius_parse() {
         let radius: RadiusFilter = "37.77,-122.41,500".parse().unwrap();
@@ -242,8 +606,177 @@ mod tests {
     fn test_radius_contains() {
         let radius: RadiusFilter = "37.77,-122.41,100".parse().unwrap();
         // SF to Oakland is ~15km
-        assert!(radius.contains(37.80, -122.27));
+        assert!(radius.contains(37.80, -122.27, DistanceModel::Haversine));
         // SF to LA is ~560km
-        assert!(!radius.contains(34.05, -118.24));
+        assert!(!radius.contains(34.05, -118.24, DistanceModel::Wgs84));
+
+        // One degree of latitude at the equator: 111.2 km on the sphere,
+        // 110.6 km on the ellipsoid
+        let edge: RadiusFilter = "0,0,111".parse().unwrap();
+        assert!(!edge.contains(1.0, 0.0, DistanceModel::Haversine));
+        assert!(edge.contains(1.0, 0.0, DistanceModel::Wgs84));
+    }
+
+    fn sample_event() -> Feature {
+        let feed: crate::models::FeatureCollection =
+            serde_json::from_str(crate::test_support::SAMPLE_FEED).unwrap();
+        // M6.1 mww, 42.3 km, net us, reviewed, green alert, tsunami, 12 felt
+        feed.features
+            .into_iter()
+            .find(|e| e.id == "us7000n1a2")
+            .unwrap()
+    }
+
+    #[test]
+    fn test_attribute_filters() {
+        let event = sample_event();
+        let passes = |filter: EventFilter| filter.matches(&event);
+
+        assert!(passes(EventFilter {
+            min_magnitude: Some(6.0),
+            max_magnitude: Some(6.1),
+            min_depth: Some(40.0),
+            max_depth: Some(50.0),
+            networks: vec!["US".into()],
+            sources: vec!["ak".into(), "us".into()],
+            status: Some(EventStatus::Reviewed),
+            exclude_event_types: vec![EventType::QuarryBlast, EventType::Explosion],
+            tsunami_only: true,
+            min_alert: Some(AlertLevel::Green),
+            min_felt: Some(12),
+            min_mmi: Some(5.5),
+            min_cdi: Some(4.0),
+            ..Default::default()
+        }));
+
+        assert!(!passes(EventFilter {
+            max_magnitude: Some(6.0),
+            ..Default::default()
+        }));
+        assert!(!passes(EventFilter {
+            min_depth: Some(50.0),
+            ..Default::default()
+        }));
+        assert!(!passes(EventFilter {
+            exclude_networks: vec!["us".into()],
+            ..Default::default()
+        }));
+        assert!(!passes(EventFilter {
+            exclude_sources: vec!["us".into()],
+            ..Default::default()
+        }));
+        assert!(!passes(EventFilter {
+            sources: vec!["ak".into()],
+            ..Default::default()
+        }));
+        assert!(!passes(EventFilter {
+            status: Some(EventStatus::Automatic),
+            ..Default::default()
+        }));
+        assert!(!passes(EventFilter {
+            event_types: vec![EventType::QuarryBlast],
+            ..Default::default()
+        }));
+        assert!(!passes(EventFilter {
+            min_alert: Some(AlertLevel::Yellow),
+            ..Default::default()
+        }));
+        assert!(!passes(EventFilter {
+            min_felt: Some(13),
+            ..Default::default()
+        }));
+        assert!(!passes(EventFilter {
+            min_cdi: Some(5.0),
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    fn test_time_window() {
+        let event = sample_event(); // 2024-12-03T08:00:00Z
+        let window = |since: &str, until: &str| EventFilter {
+            since: Some(since.parse().unwrap()),
+            until: Some(until.parse().unwrap()),
+            ..Default::default()
+        };
+
+        assert!(window("2024-12-03", "2024-12-04").matches(&event));
+        assert!(!window("2024-12-03T09:00:00", "2024-12-04").matches(&event));
+        // Relative bounds are measured back from now
+        assert!(!window("1d", "0s").matches(&event));
+        assert!(window("520w", "1h").matches(&event));
+
+        assert_eq!(
+            "90m".parse::<TimeBound>().unwrap(),
+            TimeBound::Ago(Duration::minutes(90))
+        );
+        assert!("2x".parse::<TimeBound>().is_err());
+
+        // Out-of-range offsets are rejected or clamped, never panic
+        assert!("99999999999999w".parse::<TimeBound>().is_err());
+        assert!(window("999999999w", "0s").matches(&event));
+        assert_eq!(
+            "999999999w"
+                .parse::<TimeBound>()
+                .unwrap()
+                .resolve(Utc::now()),
+            DateTime::<Utc>::MIN_UTC
+        );
+    }
+
+    #[test]
+    fn test_home_and_bearing() {
+        let home: Home = "37.77,-122.42".parse().unwrap();
+        assert!((home.lon + 122.42).abs() < 1e-9);
+        assert!("37.77".parse::<Home>().is_err());
+        assert!("95,0".parse::<Home>().is_err());
+
+        // Due east along the equator, due north along a meridian
+        assert!((initial_bearing(0.0, 0.0, 0.0, 10.0) - 90.0).abs() < 1e-9);
+        assert!(initial_bearing(0.0, 0.0, 10.0, 0.0).abs() < 1e-9);
+        // San Francisco to Los Angeles heads south-east
+        assert_eq!(
+            compass_point(initial_bearing(37.77, -122.42, 34.05, -118.24)),
+            "SE"
+        );
+
+        assert_eq!(compass_point(0.0), "N");
+        assert_eq!(compass_point(11.0), "N");
+        assert_eq!(compass_point(12.0), "NNE");
+        assert_eq!(compass_point(359.0), "N");
+        assert_eq!(compass_point(247.5), "WSW");
+    }
+
+    #[test]
+    fn test_location_quality_filters() {
+        let mut event = sample_event();
+        let p = &mut event.properties;
+        (p.nst, p.gap, p.dmin, p.rms) = (Some(12), Some(120.0), Some(0.05), Some(0.4));
+        let passes = |filter: EventFilter| filter.matches(&event);
+
+        assert!(passes(EventFilter {
+            max_gap: Some(120.0),
+            max_rms: Some(0.5),
+            min_stations: Some(10),
+            min_quality: Some(QualityGrade::C),
+            ..Default::default()
+        }));
+        assert!(!passes(EventFilter {
+            max_gap: Some(90.0),
+            ..Default::default()
+        }));
+        assert!(!passes(EventFilter {
+            max_rms: Some(0.3),
+            ..Default::default()
+        }));
+        assert!(!passes(EventFilter {
+            min_stations: Some(20),
+            ..Default::default()
+        }));
+        // QD B (gap 120°) and QS C (rms 0.4) grade C
+        assert!(!passes(EventFilter {
+            min_quality: Some(QualityGrade::B),
+            ..Default::default()
+        }));
     }
 }
@@ -0,0 +1,174 @@
+//! Offline gazetteer for nearest-place annotations.
+//!
+//! The built-in list (`data/cities.tsv`) holds national capitals and towns in
+//! seismically active regions. `--gazetteer` loads a larger file instead,
+//! either in the same `name, country, lat, lon` layout or a GeoNames dump
+//! such as `cities15000.txt`.
+
+use std::path::Path;
+
+use crate::errors::SeismotailError;
+use crate::filters::haversine_distance;
+use crate::geodesic::DistanceModel;
+use crate::models::{NearbyPlace, Proximity};
+
+/// Built-in place list.
+const EMBEDDED: &str = include_str!("../data/cities.tsv");
+
+/// Spherical distances within this factor of the closest are re-measured.
+const SHORTLIST_MARGIN: f64 = 1.01;
+
+/// Columns in a GeoNames `geoname` table dump.
+const GEONAMES_COLUMNS: usize = 19;
+
+/// A populated place.
+#[derive(Debug, Clone)]
+pub struct Place {
+    pub name: String,
+    /// ISO 3166 country code
+    pub country: String,
+    pub lat: f64,
+    pub lon: f64,
+}
+
+/// Set of places searched for the one nearest to an event.
+#[derive(Debug, Clone, Default)]
+pub struct Gazetteer {
+    places: Vec<Place>,
+}
+
+impl Gazetteer {
+    /// The built-in place list.
+    #[must_use]
+    pub fn embedded() -> Self {
+        // Checked by the tests, so never empty in practice
+        Self::parse(EMBEDDED).unwrap_or_default()
+    }
+
+    /// Load places from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or has a malformed line.
+    pub fn load(path: &Path) -> Result<Self, SeismotailError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| SeismotailError::Input(format!("{}: {e}", path.display())))?;
+        Self::parse(&text).map_err(|e| SeismotailError::Input(format!("{}: {e}", path.display())))
+    }
+
+    /// Parse tab-separated places, one per line; blank and `#` lines are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the first malformed line, or of an empty list.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut places = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let place = parse_place(line).map_err(|e| format!("line {}: {e}", index + 1))?;
+            places.push(place);
+        }
+        if places.is_empty() {
+            return Err("no places found".into());
+        }
+        Ok(Self { places })
+    }
+
+    /// Find the place nearest to a point, with the point's position from it.
+    #[must_use]
+    pub fn nearest(&self, lat: f64, lon: f64, model: DistanceModel) -> Option<NearbyPlace> {
+        let spherical: Vec<f64> = self
+            .places
+            .iter()
+            .map(|p| haversine_distance(p.lat, p.lon, lat, lon))
+            .collect();
+        let closest = spherical.iter().copied().min_by(f64::total_cmp)?;
+        // The sphere is within 1% of the ellipsoid, so only places that close
+        // to the spherical minimum can be nearest under another model
+        let (place, proximity) = self
+            .places
+            .iter()
+            .zip(&spherical)
+            .filter(|&(_, &d)| d <= closest * SHORTLIST_MARGIN)
+            .map(|(p, _)| (p, Proximity::between(model, p.lat, p.lon, lat, lon)))
+            .min_by(|(_, a), (_, b)| a.distance_km.total_cmp(&b.distance_km))?;
+        Some(NearbyPlace {
+            name: place.name.clone(),
+            country: place.country.clone(),
+            proximity,
+        })
+    }
+}
+
+/// Parse one line: `name, country, lat, lon`, or a GeoNames record.
+fn parse_place(line: &str) -> Result<Place, String> {
+    let fields: Vec<&str> = line.split('\t').collect();
+    let (name, country, lat, lon) = match fields.len() {
+        4 => (fields[0], fields[1], fields[2], fields[3]),
+        // geonameid, name, asciiname, alternatenames, latitude, longitude,
+        // feature class, feature code, country code, ...
+        GEONAMES_COLUMNS => (fields[1], fields[8], fields[4], fields[5]),
+        n => {
+            return Err(format!(
+                "expected 4 (or GeoNames' {GEONAMES_COLUMNS}) tab-separated columns, got {n}"
+            ));
+        }
+    };
+    let coordinate = |v: &str, what: &str, limit: f64| {
+        v.trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|c| c.abs() <= limit)
+            .ok_or_else(|| format!("invalid {what} '{v}'"))
+    };
+    Ok(Place {
+        name: name.trim().to_string(),
+        country: country.trim().to_string(),
+        lat: coordinate(lat, "latitude", 90.0)?,
+        lon: coordinate(lon, "longitude", 180.0)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_gazetteer() {
+        let gazetteer = Gazetteer::parse(EMBEDDED).unwrap();
+        assert!(gazetteer.places.len() > 500);
+
+        // The sample M6.1 "87 km SE of Kokopo, Papua New Guinea"
+        let near = gazetteer
+            .nearest(-4.9, 152.9, DistanceModel::Haversine)
+            .unwrap();
+        assert_eq!(
+            (near.name.as_str(), near.country.as_str()),
+            ("Kokopo", "PG")
+        );
+        assert_eq!(near.proximity.direction, "SE");
+        assert!((near.proximity.distance_km - 94.0).abs() < 2.0);
+    }
+
+    #[test]
+    fn test_geonames_format() {
+        let line = "2179537\tWellington\tWellington\tWelington\t-41.28664\t174.77557\tP\tPPLC\tNZ\t\t\
+                    G2\t\t\t\t381900\t\t26\tPacific/Auckland\t2023-01-01";
+        let gazetteer = Gazetteer::parse(line).unwrap();
+        let near = gazetteer
+            .nearest(-41.3, 174.8, DistanceModel::Wgs84)
+            .unwrap();
+        assert_eq!(near.name, "Wellington");
+        assert_eq!(near.country, "NZ");
+
+        assert!(Gazetteer::parse("# nothing\n").is_err());
+        assert!(
+            Gazetteer::parse("Nowhere\tXX\t91\t0")
+                .unwrap_err()
+                .contains("line 1")
+        );
+    }
+}
@@ -0,0 +1,277 @@
+//! Distances and bearings on the WGS84 ellipsoid.
+//!
+//! Uses Vincenty's inverse formula, which is accurate to well under a
+//! millimetre. It does not converge for nearly antipodal points, so those
+//! fall back to the spherical formulas (off by at most about 0.5%).
+
+use crate::filters::{haversine_distance, initial_bearing};
+
+/// Reference ellipsoid: equatorial radius and flattening.
+#[derive(Debug, Clone, Copy)]
+pub struct Ellipsoid {
+    pub a: f64,
+    pub f: f64,
+}
+
+/// The WGS84 ellipsoid used by GPS and the earthquake catalogs.
+pub const WGS84: Ellipsoid = Ellipsoid {
+    a: 6_378_137.0,
+    f: 1.0 / 298.257_223_563,
+};
+
+/// Convergence threshold for λ (radians, about 0.006 mm).
+const TOLERANCE: f64 = 1e-12;
+
+/// Iteration cap; only nearly antipodal points need more than a handful.
+const MAX_ITERATIONS: usize = 200;
+
+/// Result of the inverse geodesic problem.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Geodesic {
+    pub distance_m: f64,
+    /// Forward azimuth at the first point, degrees clockwise from north in [0, 360)
+    pub initial_bearing: f64,
+    /// Forward azimuth at the second point, degrees clockwise from north in [0, 360)
+    pub final_bearing: f64,
+}
+
+/// Solve the inverse problem between two points (degrees) with Vincenty's formula.
+///
+/// Returns `None` if the iteration does not converge (nearly antipodal points).
+#[must_use]
+pub fn inverse(
+    ellipsoid: Ellipsoid,
+    lat1: f64,
+    lon1: f64,
+    lat2: f64,
+    lon2: f64,
+) -> Option<Geodesic> {
+    let Ellipsoid { a, f } = ellipsoid;
+    let b = a * (1.0 - f);
+
+    let l = (lon2 - lon1).to_radians();
+    let u1 = ((1.0 - f) * lat1.to_radians().tan()).atan();
+    let u2 = ((1.0 - f) * lat2.to_radians().tan()).atan();
+    let (sin_u1, cos_u1) = u1.sin_cos();
+    let (sin_u2, cos_u2) = u2.sin_cos();
+
+    let mut lambda = l;
+    let mut converged = false;
+    let (mut sin_sigma, mut cos_sigma, mut sigma) = (0.0, 0.0, 0.0);
+    let (mut cos_sq_alpha, mut cos_2sigma_m) = (0.0, 0.0);
+    for _ in 0..MAX_ITERATIONS {
+        let (sin_lambda, cos_lambda) = lambda.sin_cos();
+        sin_sigma = ((cos_u2 * sin_lambda).powi(2)
+            + (cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda).powi(2))
+        .sqrt();
+        if sin_sigma == 0.0 {
+            // Coincident points
+            return Some(Geodesic {
+                distance_m: 0.0,
+                initial_bearing: 0.0,
+                final_bearing: 0.0,
+            });
+        }
+        cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+        sigma = sin_sigma.atan2(cos_sigma);
+        let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+        cos_sq_alpha = 1.0 - sin_alpha * sin_alpha;
+        // Zero on an equatorial line
+        cos_2sigma_m = if cos_sq_alpha == 0.0 {
+            0.0
+        } else {
+            cos_sigma - 2.0 * sin_u1 * sin_u2 / cos_sq_alpha
+        };
+        let c = f / 16.0 * cos_sq_alpha * (4.0 + f * (4.0 - 3.0 * cos_sq_alpha));
+        let previous = lambda;
+        lambda = l
+            + (1.0 - c)
+                * f
+                * sin_alpha
+                * (sigma
+                    + c * sin_sigma
+                        * (cos_2sigma_m + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))));
+        if (lambda - previous).abs() < TOLERANCE {
+            converged = true;
+            break;
+        }
+    }
+    if !converged {
+        return None;
+    }
+
+    let u_sq = cos_sq_alpha * (a * a - b * b) / (b * b);
+    let big_a = 1.0 + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+    let big_b = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+    let delta_sigma = big_b
+        * sin_sigma
+        * (cos_2sigma_m
+            + big_b / 4.0
+                * (cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))
+                    - big_b / 6.0
+                        * cos_2sigma_m
+                        * (-3.0 + 4.0 * sin_sigma.powi(2))
+                        * (-3.0 + 4.0 * cos_2sigma_m.powi(2))));
+
+    let (sin_lambda, cos_lambda) = lambda.sin_cos();
+    let alpha1 = (cos_u2 * sin_lambda).atan2(cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda);
+    let alpha2 = (cos_u1 * sin_lambda).atan2(-sin_u1 * cos_u2 + cos_u1 * sin_u2 * cos_lambda);
+
+    Some(Geodesic {
+        distance_m: b * big_a * (sigma - delta_sigma),
+        initial_bearing: alpha1.to_degrees().rem_euclid(360.0),
+        final_bearing: alpha2.to_degrees().rem_euclid(360.0),
+    })
+}
+
+/// How distances and bearings are computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DistanceModel {
+    /// Sphere of radius 6371 km (haversine); fast, up to ~0.5% off
+    #[default]
+    Haversine,
+    /// WGS84 ellipsoid (Vincenty)
+    Wgs84,
+}
+
+impl std::str::FromStr for DistanceModel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "haversine" | "sphere" => Ok(Self::Haversine),
+            "wgs84" | "geodesic" | "vincenty" => Ok(Self::Wgs84),
+            _ => Err(format!(
+                "unknown distance model: {s} (expected: haversine, wgs84)"
+            )),
+        }
+    }
+}
+
+impl DistanceModel {
+    /// Distance in kilometers between two points.
+    #[must_use]
+    pub fn distance_km(self, lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+        match self.geodesic(lat1, lon1, lat2, lon2) {
+            Some(g) => g.distance_m / 1000.0,
+            None => haversine_distance(lat1, lon1, lat2, lon2),
+        }
+    }
+
+    /// Initial bearing from point 1 to point 2, degrees clockwise from north.
+    #[must_use]
+    pub fn bearing(self, lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+        match self.geodesic(lat1, lon1, lat2, lon2) {
+            Some(g) => g.initial_bearing,
+            None => initial_bearing(lat1, lon1, lat2, lon2),
+        }
+    }
+
+    fn geodesic(self, lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> Option<Geodesic> {
+        match self {
+            Self::Haversine => None,
+            Self::Wgs84 => inverse(WGS84, lat1, lon1, lat2, lon2),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bessel 1841, the ellipsoid of Vincenty's (1975) test lines.
+    const BESSEL: Ellipsoid = Ellipsoid {
+        a: 6_377_397.155,
+        f: 1.0 / 299.152_812_8,
+    };
+
+    fn dms(d: f64, m: f64, s: f64) -> f64 {
+        d.signum() * (d.abs() + m / 60.0 + s / 3600.0)
+    }
+
+    fn assert_geodesic(g: Geodesic, distance_m: f64, azimuth1: f64, azimuth2: f64) {
+        assert!(
+            (g.distance_m - distance_m).abs() < 1e-3,
+            "{} != {distance_m}",
+            g.distance_m
+        );
+        // Azimuths to 0.001″
+        assert!(
+            (g.initial_bearing - azimuth1).abs() * 3600.0 < 1e-3,
+            "{g:?}"
+        );
+        assert!((g.final_bearing - azimuth2).abs() * 3600.0 < 1e-3, "{g:?}");
+    }
+
+    #[test]
+    fn test_vincenty_test_line() {
+        // Vincenty (1975), Survey Review XXIII, line (a)
+        let g = inverse(
+            BESSEL,
+            dms(55.0, 45.0, 0.0),
+            0.0,
+            dms(-33.0, 26.0, 0.0),
+            dms(108.0, 13.0, 0.0),
+        )
+        .unwrap();
+        assert_geodesic(
+            g,
+            14_110_526.170,
+            dms(96.0, 36.0, 8.79960),
+            dms(137.0, 52.0, 22.01454),
+        );
+    }
+
+    #[test]
+    fn test_flinders_peak_to_buninyong() {
+        // Geoscience Australia's worked example (GRS80, within 0.1 mm of WGS84)
+        let g = inverse(
+            WGS84,
+            -dms(37.0, 57.0, 3.72030),
+            dms(144.0, 25.0, 29.52440),
+            -dms(37.0, 39.0, 10.15610),
+            dms(143.0, 55.0, 35.38390),
+        )
+        .unwrap();
+        assert!((g.distance_m - 54_972.271).abs() < 1e-3, "{g:?}");
+        // Published to 0.01″; the reverse azimuth is the final bearing reversed
+        assert!(
+            (g.initial_bearing - dms(306.0, 52.0, 5.37)).abs() * 3600.0 < 0.01,
+            "{g:?}"
+        );
+        assert!(
+            (g.final_bearing - 180.0 - dms(127.0, 10.0, 25.07)).abs() * 3600.0 < 0.01,
+            "{g:?}"
+        );
+    }
+
+    #[test]
+    fn test_distance_models() {
+        // One degree of latitude is ~110.6 km at the equator and ~111.7 km at
+        // the poles on the ellipsoid, but 111.2 km everywhere on the sphere
+        let equator = DistanceModel::Wgs84.distance_km(0.0, 0.0, 1.0, 0.0);
+        let pole = DistanceModel::Wgs84.distance_km(89.0, 0.0, 90.0, 0.0);
+        let sphere = DistanceModel::Haversine.distance_km(0.0, 0.0, 1.0, 0.0);
+        assert!((equator - 110.574).abs() < 0.001, "{equator}");
+        assert!((pole - 111.694).abs() < 0.001, "{pole}");
+        assert!((sphere - 111.195).abs() < 0.001, "{sphere}");
+
+        assert!(
+            DistanceModel::Wgs84
+                .distance_km(10.0, 20.0, 10.0, 20.0)
+                .abs()
+                < 1e-9
+        );
+        // Nearly antipodal points fall back to the sphere instead of failing
+        assert!(inverse(WGS84, 0.0, 0.0, 0.5, 179.7).is_none());
+        let far = DistanceModel::Wgs84.distance_km(0.0, 0.0, 0.5, 179.7);
+        assert!((far - 19_975.0).abs() < 50.0, "{far}");
+
+        assert!((DistanceModel::Wgs84.bearing(0.0, 0.0, 0.0, 10.0) - 90.0).abs() < 1e-9);
+        assert_eq!(
+            "WGS84".parse::<DistanceModel>().unwrap(),
+            DistanceModel::Wgs84
+        );
+        assert!("flat".parse::<DistanceModel>().is_err());
+    }
+}
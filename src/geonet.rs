@@ -0,0 +1,278 @@
+//! `GeoNet` (New Zealand) quake API client.
+//!
+//! The `/quake` endpoint returns the most recent New Zealand events as
+//! GeoJSON with a schema of its own; events are normalized to the USGS
+//! [`Feature`] model and filtered to the feed window client-side.
+//! See <https://api.geonet.org.nz/>.
+
+use chrono::{DateTime, Utc};
+use reqwest::header::ACCEPT;
+use serde::Deserialize;
+use tracing::{debug, instrument};
+
+use crate::client::{FeedFetch, FeedType, async_http_client, send_async};
+use crate::errors::SeismotailError;
+use crate::models::{BodyFormat, Feature, FeatureCollection, Geometry, Properties};
+use crate::query::parse_time;
+use crate::retry::RetryPolicy;
+use crate::source::{ChangeTracker, EventSource, SourceFuture};
+use crate::types::{EventStatus, EventType};
+
+/// `GeoNet` API base URL.
+pub const GEONET_BASE_URL: &str = "https://api.geonet.org.nz";
+
+/// Media type selecting the version 2 GeoJSON API.
+const GEONET_ACCEPT: &str = "application/vnd.geo+json;version=2";
+
+/// GeoJSON response of the quake endpoint.
+#[derive(Debug, Deserialize)]
+struct GeoNetCollection {
+    features: Vec<GeoNetFeature>,
+}
+
+/// A single `GeoNet` quake.
+#[derive(Debug, Deserialize)]
+struct GeoNetFeature {
+    geometry: Geometry,
+    properties: GeoNetProperties,
+}
+
+/// `GeoNet` quake properties.
+#[derive(Debug, Deserialize)]
+struct GeoNetProperties {
+    #[serde(rename = "publicID")]
+    public_id: String,
+    time: String,
+    depth: Option<f64>,
+    magnitude: Option<f64>,
+    /// Modelled intensity; -1 when not computed
+    mmi: Option<i32>,
+    locality: Option<String>,
+    /// best, preliminary, automatic or deleted
+    quality: Option<String>,
+}
+
+/// Client for the `GeoNet` quake API.
+pub struct GeoNetClient {
+    client: reqwest::Client,
+    base_url: String,
+    feed: FeedType,
+    retry: RetryPolicy,
+    tracker: ChangeTracker,
+}
+
+impl GeoNetClient {
+    /// Create a client for the recent-events window of `feed`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be initialized.
+    pub fn with_base_url(base_url: &str, feed: FeedType) -> Result<Self, SeismotailError> {
+        Ok(Self {
+            client: async_http_client()?,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            feed,
+            retry: RetryPolicy::default(),
+            tracker: ChangeTracker::default(),
+        })
+    }
+
+    /// Use a different retry policy.
+    #[must_use]
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Fetch events of the feed window, newest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or response cannot be parsed.
+    #[instrument(skip(self), fields(feed = self.feed.as_str()))]
+    pub async fn fetch_recent(&self) -> Result<FeatureCollection, SeismotailError> {
+        let url = format!("{}/quake", self.base_url);
+
+        debug!("querying GeoNet at {}", url);
+
+        let response = send_async(&self.retry, || {
+            self.client
+                .get(&url)
+                // MMI -1 selects every quake, including unfelt ones
+                .query(&[("MMI", "-1")])
+                .header(ACCEPT, GEONET_ACCEPT)
+        })
+        .await?;
+        let body = response.text().await?;
+        let feed = parse_body(&body, self.feed, Utc::now())?;
+
+        debug!("fetched {} events", feed.features.len());
+        Ok(feed.with_body(BodyFormat::GeoNet { feed: self.feed }, body))
+    }
+}
+
+impl EventSource for GeoNetClient {
+    fn name(&self) -> &'static str {
+        "geonet"
+    }
+
+    fn fetch(&self) -> SourceFuture<'_, FeatureCollection> {
+        Box::pin(self.fetch_recent())
+    }
+
+    fn fetch_if_modified(&self) -> SourceFuture<'_, FeedFetch> {
+        Box::pin(async move { Ok(self.tracker.fetch_result(self.fetch_recent().await?)) })
+    }
+}
+
+/// Parse a `GeoNet` response body as fetched at `now` for `feed`.
+///
+/// # Errors
+///
+/// Returns an error if the body is not a `GeoNet` collection.
+pub fn parse_body(
+    text: &str,
+    feed: FeedType,
+    now: DateTime<Utc>,
+) -> Result<FeatureCollection, SeismotailError> {
+    let collection: GeoNetCollection = serde_json::from_str(text)?;
+    Ok(FeatureCollection::from_features(normalize(
+        collection, feed, now,
+    )))
+}
+
+/// Convert quakes to features, keeping those inside the feed window and
+/// above its magnitude threshold. Deleted quakes are dropped.
+fn normalize(collection: GeoNetCollection, feed: FeedType, now: DateTime<Utc>) -> Vec<Feature> {
+    let since = (now - feed.window()).timestamp_millis();
+    let min_magnitude = feed.min_magnitude();
+
+    collection
+        .features
+        .into_iter()
+        .filter_map(|f| {
+            let p = f.properties;
+            let quality = p.quality.as_deref().unwrap_or("automatic");
+            if quality == "deleted" {
+                return None;
+            }
+            let Ok(time) = parse_time(&p.time) else {
+                debug!(
+                    "skipping GeoNet quake {} with invalid time {:?}",
+                    p.public_id, p.time
+                );
+                return None;
+            };
+            let time = time.timestamp_millis();
+            if time < since {
+                return None;
+            }
+            if let Some(min) = min_magnitude
+                && p.magnitude.is_none_or(|m| m < min)
+            {
+                return None;
+            }
+
+            let lon = f.geometry.coordinates.first().copied().unwrap_or(0.0);
+            let lat = f.geometry.coordinates.get(1).copied().unwrap_or(0.0);
+            let title = match (p.magnitude, &p.locality) {
+                (Some(mag), Some(place)) => Some(format!("M {mag:.1} - {place}")),
+                _ => None,
+            };
+
+            Some(Feature {
+                type_: "Feature".into(),
+                id: p.public_id.clone(),
+                geometry: Geometry {
+                    type_: "Point".into(),
+                    coordinates: vec![lon, lat, p.depth.unwrap_or(0.0)],
+                },
+                properties: Properties {
+                    mag: p.magnitude,
+                    place: p.locality,
+                    time,
+                    // The quake endpoint carries no modification time
+                    updated: time,
+                    status: if quality == "best" {
+                        EventStatus::Reviewed.into()
+                    } else {
+                        EventStatus::Automatic.into()
+                    },
+                    net: "nz".into(),
+                    code: p.public_id.clone(),
+                    sources: Some(",nz,".into()),
+                    url: Some(format!(
+                        "https://www.geonet.org.nz/earthquake/{}",
+                        p.public_id
+                    )),
+                    title,
+                    mmi: p.mmi.filter(|&m| m >= 0).map(f64::from),
+                    event_type: Some(EventType::Earthquake.into()),
+                    ..Properties::default()
+                },
+                regions: Vec::new(),
+                home: None,
+                nearest_place: None,
+                zones: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{Response, SAMPLE_GEONET, StandInServer};
+
+    fn sample() -> GeoNetCollection {
+        serde_json::from_str(SAMPLE_GEONET).unwrap()
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        parse_time(s).unwrap()
+    }
+
+    #[test]
+    fn test_normalize_drops_deleted_and_maps_quality() {
+        let features = normalize(sample(), FeedType::AllDay, at("2024-12-03T12:00:00Z"));
+        assert_eq!(features.len(), 2);
+
+        let first = &features[0];
+        assert_eq!(first.id, "2024p912871");
+        assert_eq!(first.properties.status, EventStatus::Reviewed);
+        assert_eq!(first.properties.mmi, Some(4.0));
+        assert!((first.longitude() - 176.0383).abs() < f64::EPSILON);
+        assert!((first.depth_km() - 5.1).abs() < f64::EPSILON);
+        first.validate().unwrap();
+
+        assert_eq!(features[1].properties.status, EventStatus::Automatic);
+        assert_eq!(features[1].properties.mmi, None);
+    }
+
+    #[test]
+    fn test_normalize_applies_feed_window_and_threshold() {
+        let now = at("2024-12-03T09:00:00Z");
+
+        let hour = normalize(sample(), FeedType::AllHour, now);
+        assert_eq!(hour.len(), 1);
+
+        let strong = normalize(sample(), FeedType::Mag25Day, now);
+        assert_eq!(strong.len(), 1);
+        assert_eq!(strong[0].id, "2024p912871");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_requests_v2_geojson() {
+        let server = StandInServer::start(|_| Response::json(SAMPLE_GEONET));
+        let client = GeoNetClient::with_base_url(server.url(), FeedType::AllMonth).unwrap();
+
+        // The recorded quakes are older than any feed window
+        let feed = client.fetch().await.unwrap();
+        assert!(feed.features.is_empty());
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/quake");
+        assert_eq!(requests[0].param("MMI"), Some("-1"));
+        assert_eq!(requests[0].header("Accept"), Some(GEONET_ACCEPT));
+    }
+}
@@ -0,0 +1,288 @@
+//! Offline input: feed dumps read from disk or stdin.
+//!
+//! A [`FileSource`] is an [`EventSource`] over a USGS GeoJSON
+//! `FeatureCollection` (or QuakeML document) in a file, a directory of such
+//! snapshots, or stdin, so `tail` and `live` apply the usual filters and
+//! output to feeds carried onto air-gapped machines.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::SystemTime;
+
+use tokio::io::AsyncReadExt;
+use tracing::debug;
+
+use crate::client::FeedFetch;
+use crate::errors::SeismotailError;
+use crate::models::{BodyFormat, FeatureCollection};
+use crate::quakeml;
+use crate::source::{ChangeTracker, EventSource, SourceFuture};
+
+/// Path that selects stdin.
+pub const STDIN: &str = "-";
+
+/// Snapshot file extensions picked up from a directory.
+const SNAPSHOT_EXTENSIONS: [&str; 3] = ["json", "geojson", "xml"];
+
+/// What an input path refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Input {
+    Stdin,
+    File(PathBuf),
+    Dir(PathBuf),
+}
+
+/// Events read from a file, a snapshot directory or stdin.
+///
+/// Files are re-read on every fetch; a directory yields the merge of all
+/// snapshots, and when polled only the snapshots added or modified since the
+/// previous poll. Stdin is read once.
+pub struct FileSource {
+    input: Input,
+    tracker: ChangeTracker,
+    /// Modification times of directory snapshots already polled
+    seen: Mutex<HashMap<PathBuf, SystemTime>>,
+    /// Stdin has been read to the end
+    consumed: AtomicBool,
+}
+
+impl FileSource {
+    /// Open a file, directory or `-` for stdin.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path does not exist.
+    pub fn open(path: &Path) -> Result<Self, SeismotailError> {
+        let input = if path.as_os_str() == STDIN {
+            Input::Stdin
+        } else {
+            let meta = std::fs::metadata(path).map_err(|e| input_error(path, &e))?;
+            if meta.is_dir() {
+                Input::Dir(path.to_path_buf())
+            } else {
+                Input::File(path.to_path_buf())
+            }
+        };
+
+        Ok(Self {
+            input,
+            tracker: ChangeTracker::default(),
+            seen: Mutex::new(HashMap::new()),
+            consumed: AtomicBool::new(false),
+        })
+    }
+
+    /// Read every event of the input.
+    async fn read_all(&self) -> Result<FeatureCollection, SeismotailError> {
+        match &self.input {
+            Input::Stdin => {
+                let mut text = String::new();
+                tokio::io::stdin()
+                    .read_to_string(&mut text)
+                    .await
+                    .map_err(|e| input_error(Path::new("stdin"), &e))?;
+                self.consumed.store(true, Ordering::Relaxed);
+                parse_snapshot(text)
+            }
+            Input::File(path) => read_snapshot(path).await,
+            Input::Dir(dir) => {
+                let snapshots = list_snapshots(dir).await?;
+                read_merged(snapshots.into_iter().map(|(path, _)| path)).await
+            }
+        }
+    }
+
+    /// Read directory snapshots not polled before, or modified since.
+    async fn read_new(&self, dir: &Path) -> Result<FeedFetch, SeismotailError> {
+        let snapshots = list_snapshots(dir).await?;
+        let fresh: Vec<PathBuf> = {
+            let Ok(mut seen) = self.seen.lock() else {
+                return Ok(FeedFetch::NotModified);
+            };
+            snapshots
+                .into_iter()
+                .filter(|(path, modified)| seen.insert(path.clone(), *modified) != Some(*modified))
+                .map(|(path, _)| path)
+                .collect()
+        };
+
+        if fresh.is_empty() {
+            return Ok(FeedFetch::NotModified);
+        }
+        debug!("{} new snapshots in {}", fresh.len(), dir.display());
+        Ok(FeedFetch::Modified(read_merged(fresh).await?))
+    }
+}
+
+impl EventSource for FileSource {
+    fn name(&self) -> &'static str {
+        match self.input {
+            Input::Stdin => "stdin",
+            Input::File(_) => "file",
+            Input::Dir(_) => "directory",
+        }
+    }
+
+    fn fetch(&self) -> SourceFuture<'_, FeatureCollection> {
+        Box::pin(self.read_all())
+    }
+
+    fn fetch_if_modified(&self) -> SourceFuture<'_, FeedFetch> {
+        Box::pin(async move {
+            match &self.input {
+                Input::Dir(dir) => self.read_new(dir).await,
+                Input::Stdin | Input::File(_) => {
+                    Ok(self.tracker.fetch_result(self.read_all().await?))
+                }
+            }
+        })
+    }
+
+    fn exhausted(&self) -> bool {
+        self.input == Input::Stdin && self.consumed.load(Ordering::Relaxed)
+    }
+}
+
+/// Read snapshots in order, keeping the latest revision of each event.
+async fn read_merged(
+    paths: impl IntoIterator<Item = PathBuf>,
+) -> Result<FeatureCollection, SeismotailError> {
+    let mut snapshots = Vec::new();
+    for path in paths {
+        snapshots.push(read_snapshot(&path).await?);
+    }
+    Ok(FeatureCollection::merge(snapshots))
+}
+
+/// Snapshot files of `dir` with their modification times, sorted by name.
+async fn list_snapshots(dir: &Path) -> Result<Vec<(PathBuf, SystemTime)>, SeismotailError> {
+    let mut entries = tokio::fs::read_dir(dir)
+        .await
+        .map_err(|e| input_error(dir, &e))?;
+
+    let mut snapshots = Vec::new();
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| input_error(dir, &e))?
+    {
+        let path = entry.path();
+        let is_snapshot = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| SNAPSHOT_EXTENSIONS.contains(&e));
+        let Ok(meta) = entry.metadata().await else {
+            continue;
+        };
+        if is_snapshot && meta.is_file() {
+            snapshots.push((path, meta.modified().unwrap_or(SystemTime::UNIX_EPOCH)));
+        }
+    }
+
+    snapshots.sort();
+    Ok(snapshots)
+}
+
+/// Read and parse one snapshot file.
+async fn read_snapshot(path: &Path) -> Result<FeatureCollection, SeismotailError> {
+    let text = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| input_error(path, &e))?;
+    parse_snapshot(text).map_err(|e| SeismotailError::Input(format!("{}: {e}", path.display())))
+}
+
+/// Parse a GeoJSON feature collection, or QuakeML if the text is XML.
+fn parse_snapshot(text: String) -> Result<FeatureCollection, SeismotailError> {
+    if text.trim_start().starts_with('<') {
+        return Ok(quakeml::parse_quakeml(&text)?.with_body(BodyFormat::QuakeMl, text));
+    }
+    Ok(FeatureCollection::from_json(&text)?.with_body(BodyFormat::GeoJson, text))
+}
+
+/// Wrap an I/O error with the path it concerns.
+fn input_error(path: &Path, e: &std::io::Error) -> SeismotailError {
+    SeismotailError::Input(format!("{}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{SAMPLE_FEED, SAMPLE_QUAKEML};
+
+    /// Fresh scratch directory for one test.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("seismotail-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_file_source_reads_geojson_and_quakeml() {
+        let dir = scratch_dir("file");
+        let json = dir.join("feed.json");
+        let xml = dir.join("feed.xml");
+        std::fs::write(&json, SAMPLE_FEED).unwrap();
+        std::fs::write(&xml, SAMPLE_QUAKEML).unwrap();
+
+        let source = FileSource::open(&json).unwrap();
+        assert_eq!(source.name(), "file");
+        assert_eq!(source.fetch().await.unwrap().features.len(), 8);
+        assert!(matches!(
+            source.fetch_if_modified().await.unwrap(),
+            FeedFetch::Modified(_)
+        ));
+        assert!(matches!(
+            source.fetch_if_modified().await.unwrap(),
+            FeedFetch::NotModified
+        ));
+
+        let source = FileSource::open(&xml).unwrap();
+        assert_eq!(source.fetch().await.unwrap().features.len(), 3);
+
+        assert!(matches!(
+            FileSource::open(&dir.join("missing.json")),
+            Err(SeismotailError::Input(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_directory_merges_and_polls_new_snapshots() {
+        let dir = scratch_dir("dir");
+        let feed: serde_json::Value = serde_json::from_str(SAMPLE_FEED).unwrap();
+        std::fs::write(dir.join("001.json"), SAMPLE_FEED).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a snapshot").unwrap();
+
+        let source = FileSource::open(&dir).unwrap();
+        let FeedFetch::Modified(first) = source.fetch_if_modified().await.unwrap() else {
+            panic!("expected the first snapshot");
+        };
+        assert_eq!(first.features.len(), 8);
+        assert!(matches!(
+            source.fetch_if_modified().await.unwrap(),
+            FeedFetch::NotModified
+        ));
+
+        // A later snapshot revises one event
+        let mut revised = feed.clone();
+        let updated = revised["features"][0]["properties"]["updated"]
+            .as_i64()
+            .unwrap();
+        revised["features"][0]["properties"]["updated"] = (updated + 60_000).into();
+        revised["features"][0]["properties"]["mag"] = 6.4.into();
+        std::fs::write(dir.join("002.json"), revised.to_string()).unwrap();
+
+        let FeedFetch::Modified(second) = source.fetch_if_modified().await.unwrap() else {
+            panic!("expected the new snapshot");
+        };
+        assert_eq!(second.features.len(), 8);
+
+        // One-shot reads merge all snapshots, latest revision winning
+        let merged = source.fetch().await.unwrap();
+        assert_eq!(merged.features.len(), 8);
+        assert_eq!(merged.features[0].properties.mag, Some(6.4));
+        assert_eq!(merged.features[0].properties.updated, updated + 60_000);
+    }
+}
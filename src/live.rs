@@ -0,0 +1,147 @@
+//! Live stream event processing.
+//!
+//! Polling and push ingestion both feed events through a single
+//! [`LiveProcessor`], so filtering, deduplication and output are identical
+//! whichever way the events arrived.
+
+use std::io::Write;
+
+use crate::annotate::Annotator;
+use crate::dedup::DedupeRing;
+use crate::filters::EventFilter;
+use crate::models::{Feature, SchemaVersion};
+use crate::output::{self, Format};
+
+/// What the processor did with an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emitted {
+    /// Filtered out or already seen
+    Skipped,
+    /// First sighting, written
+    New,
+    /// Newer revision of a seen event, written
+    Update,
+}
+
+/// Filter, dedupe and write live events.
+pub struct LiveProcessor {
+    filter: EventFilter,
+    annotator: Annotator,
+    /// Bounded deduplication ring (NASA Power of 10: bounded resources)
+    dedup: DedupeRing,
+    format: Format,
+    schema: SchemaVersion,
+}
+
+impl LiveProcessor {
+    /// Create a processor with an empty dedup ring.
+    #[must_use]
+    pub fn new(
+        filter: EventFilter,
+        annotator: Annotator,
+        format: Format,
+        schema: SchemaVersion,
+    ) -> Self {
+        Self {
+            filter,
+            annotator,
+            dedup: DedupeRing::with_default_capacity(),
+            format,
+            schema,
+        }
+    }
+
+    /// Process one event, writing it to `out` if it is new or updated.
+    ///
+    /// Write failures are logged, not returned, so one bad event cannot stop
+    /// the stream.
+    pub fn process<W: Write>(&mut self, out: &mut W, event: &Feature) -> Emitted {
+        // Apply filters first (before dedup check)
+        if !self.filter.matches(event) {
+            return Emitted::Skipped;
+        }
+
+        // Check deduplication with update detection
+        let (dedup_result, changes) = self.dedup.check_revision(event);
+        if !dedup_result.should_emit() {
+            return Emitted::Skipped;
+        }
+
+        let mut event = event.clone();
+        self.filter.tag_matches(&mut event);
+        self.annotator.annotate(&mut event);
+        let (emitted, written) = match &changes {
+            Some(changes) => (
+                Emitted::Update,
+                output::write_update(out, &event, changes, self.format, self.schema),
+            ),
+            None => (
+                Emitted::New,
+                output::write_events(out, std::slice::from_ref(&event), self.format, self.schema),
+            ),
+        };
+        if let Err(e) = written {
+            tracing::warn!("failed to write event: {}", e);
+        }
+
+        // Flush after each event for real-time output
+        let _ = out.flush();
+        emitted
+    }
+
+    /// Fraction of processed events that were duplicates.
+    #[must_use]
+    pub fn dupe_rate(&self) -> f64 {
+        self.dedup.dupe_rate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FeatureCollection;
+    use crate::test_support::SAMPLE_FEED;
+
+    fn sample_events() -> Vec<Feature> {
+        let feed: FeatureCollection = serde_json::from_str(SAMPLE_FEED).unwrap();
+        feed.features
+    }
+
+    #[test]
+    fn test_process_filters_dedupes_and_flags_updates() {
+        let filter = EventFilter {
+            min_magnitude: Some(4.0),
+            ..Default::default()
+        };
+        let mut processor = LiveProcessor::new(
+            filter,
+            Annotator::default(),
+            Format::Ndjson,
+            SchemaVersion::V1,
+        );
+        let mut events = sample_events();
+        let mut out = Vec::new();
+
+        let first: Vec<_> = events
+            .iter()
+            .map(|e| processor.process(&mut out, e))
+            .collect();
+        let written = first.iter().filter(|e| **e == Emitted::New).count();
+        assert!(written > 0 && written < events.len());
+
+        // Second pass: everything is a duplicate
+        for event in &events {
+            assert_eq!(processor.process(&mut out, event), Emitted::Skipped);
+        }
+
+        events[0].properties.updated += 1;
+        events[0].properties.mag = Some(6.4);
+        assert_eq!(processor.process(&mut out, &events[0]), Emitted::Update);
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().count(), written + 1);
+
+        let update: serde_json::Value = serde_json::from_str(out.lines().last().unwrap()).unwrap();
+        assert_eq!(update["changes"]["mag"]["to"], 6.4);
+        assert!(update["changes"].get("status").is_none());
+    }
+}
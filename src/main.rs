@@ -5,25 +5,55 @@
 
 use std::io::{self, Write};
 use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
+use chrono::Utc;
 use clap::Parser;
 use tracing::error;
 
+mod annotate;
 mod cli;
 mod client;
 mod dedup;
+mod detail;
 mod eew;
+mod emsc;
 mod errors;
+mod expr;
 mod filters;
+mod gazetteer;
+mod geodesic;
+mod geonet;
+mod input;
+mod live;
+mod mock;
 mod models;
 mod output;
+mod quakeml;
+mod quality;
+mod query;
+mod record;
+mod region;
+mod retry;
 mod server;
+mod source;
+#[cfg(test)]
+mod test_support;
+mod types;
+mod zones;
 
 use cli::{Cli, Command};
-use client::UsgsClient;
+use client::{FeedFetch, UsgsClient};
+use emsc::EmscPush;
 use filters::EventFilter;
+use live::{Emitted, LiveProcessor};
 use models::Feature;
+use query::{EventQuery, OrderBy};
+use record::ReplaySource;
+use retry::RetryPolicy;
+use source::{EventSource, SourceConfig, SourceKind};
 
 fn main() -> ExitCode {
     match run() {
@@ -42,12 +72,29 @@ fn run() -> Result<()> {
     // Initialize tracing based on verbosity
     init_tracing(cli.verbose, cli.quiet);
 
+    let usgs_url = cli.usgs_url.as_str();
+    let retry = RetryPolicy {
+        max_attempts: cli.max_attempts,
+        ..RetryPolicy::default()
+    };
+
+    let sources = SourceConfig {
+        usgs_url: cli.usgs_url.clone(),
+        emsc_url: cli.emsc_url.clone(),
+        emsc_ws_url: cli.emsc_ws_url.clone(),
+        geonet_url: cli.geonet_url.clone(),
+        retry,
+        ..SourceConfig::default()
+    };
+
     match cli.command {
-        Command::Tail(args) => cmd_tail(args),
-        Command::Live(args) => cmd_live(args),
-        Command::Query(args) => cmd_query(args),
-        Command::Ui(args) => cmd_ui(args),
-        Command::Detect(args) => cmd_detect(args),
+        Command::Tail(args) => cmd_tail(&args, &sources),
+        Command::Live(args) => cmd_live(&args, &sources),
+        Command::Query(args) => cmd_query(&args, usgs_url, retry),
+        Command::Event(args) => cmd_event(&args, usgs_url, retry),
+        Command::Ui(args) => cmd_ui(&args, &sources),
+        Command::Detect(args) => cmd_detect(&args),
+        Command::MockServer(args) => cmd_mock_server(&args),
     }
 }
 
@@ -71,12 +118,24 @@ fn init_tracing(verbose: bool, quiet: bool) {
 }
 
 /// Execute the `tail` command - one-shot fetch of recent earthquakes.
-fn cmd_tail(args: cli::TailArgs) -> Result<()> {
-    let client = UsgsClient::new().context("failed to create USGS client")?;
+fn cmd_tail(args: &cli::TailArgs, sources: &SourceConfig) -> Result<()> {
+    if args.sort == output::SortKey::Distance && args.location.home.is_none() {
+        anyhow::bail!("--sort distance requires --home");
+    }
+
+    let source = SourceConfig {
+        kind: args.source,
+        feed: args.feed,
+        input: args.input.clone(),
+        ..sources.clone()
+    }
+    .connect()
+    .context("failed to create event source")?;
 
-    let feed = client
-        .fetch_feed(args.feed)
-        .context("failed to fetch earthquake feed")?;
+    let runtime = tokio::runtime::Runtime::new().context("failed to create tokio runtime")?;
+    let feed = runtime
+        .block_on(source.fetch())
+        .with_context(|| format!("failed to fetch earthquakes from {}", source.name()))?;
 
     // Build filter from args
     let filter = EventFilter {
@@ -84,42 +143,78 @@ fn cmd_tail(args: cli::TailArgs) -> Result<()> {
         max_depth: args.max_depth,
         bbox: args.bbox,
         radius: args.radius,
+        regions: args.region.clone(),
         significant_only: args.significant,
+        expr: args.filter_expr.clone(),
+        distance_model: args.location.distance_model,
+        ..args.filters.to_filter()
     };
+    let annotator = args.location.annotator();
 
-    // Filter events
-    let mut events: Vec<&Feature> = feed
+    let mut events: Vec<Feature> = feed
         .features
-        .iter()
+        .into_iter()
         .filter(|e| filter.matches(e))
         .collect();
 
-    // Sort by time descending (most recent first)
-    events.sort_by(|a, b| b.properties.time.cmp(&a.properties.time));
+    match args.sort {
+        output::SortKey::Distance => annotator.sort_by_distance(&mut events),
+        key => output::sort_events(&mut events, key),
+    }
 
     // Limit results
     events.truncate(args.limit);
 
-    // Convert back to owned for output
-    let events: Vec<Feature> = events.into_iter().cloned().collect();
+    // Note matching regions, distances and nearest places of the survivors
+    for event in &mut events {
+        filter.tag_matches(event);
+        annotator.annotate(event);
+    }
 
     // Write output
     let stdout = io::stdout();
     let mut handle = stdout.lock();
-    output::write_events(&mut handle, &events, args.format)?;
+    output::write_events(&mut handle, &events, args.format, args.schema)?;
 
     Ok(())
 }
 
 /// Execute the `live` command - real-time streaming.
-fn cmd_live(args: cli::LiveArgs) -> Result<()> {
+fn cmd_live(args: &cli::LiveArgs, sources: &SourceConfig) -> Result<()> {
+    if args.push && args.source != SourceKind::Emsc {
+        anyhow::bail!("--push requires --source emsc");
+    }
+    if !args.format.is_incremental() {
+        anyhow::bail!("live cannot write QuakeML (one document per stream); use -f ndjson");
+    }
+
     // Validate poll interval
     let poll_interval = args.poll_interval.max(30);
     if poll_interval != args.poll_interval {
         tracing::warn!("poll interval clamped to minimum of 30 seconds");
     }
 
-    let client = UsgsClient::new().context("failed to create USGS client")?;
+    let source: Arc<dyn EventSource> = if let Some(dir) = &args.replay {
+        let replay = ReplaySource::open(dir, args.speed).context("failed to open recording")?;
+        tracing::info!(
+            "replaying {} polls from {}",
+            replay.poll_count(),
+            dir.display()
+        );
+        Arc::new(replay)
+    } else {
+        SourceConfig {
+            kind: args.source,
+            feed: args.feed,
+            input: args.input.clone(),
+            record: args.record.clone(),
+            ..sources.clone()
+        }
+        .connect()
+        .context("failed to create event source")?
+    };
+    // Polls are driven one at a time from this thread
+    let runtime = tokio::runtime::Runtime::new().context("failed to create tokio runtime")?;
 
     // Build filter from args
     let filter = EventFilter {
@@ -127,16 +222,32 @@ fn cmd_live(args: cli::LiveArgs) -> Result<()> {
         max_depth: args.max_depth,
         bbox: args.bbox,
         radius: args.radius,
+        regions: args.region.clone(),
         significant_only: args.significant,
+        expr: args.filter_expr.clone(),
+        distance_model: args.location.distance_model,
+        ..args.filters.to_filter()
     };
+    let mut processor =
+        LiveProcessor::new(filter, args.location.annotator(), args.format, args.schema);
 
-    // Bounded deduplication ring (NASA Power of 10: bounded resources)
-    let mut dedup = dedup::DedupeRing::with_default_capacity();
-
+    let mode = if args.push {
+        "Push: WebSocket".to_string()
+    } else if args.replay.is_some() {
+        format!("Replay: {}x", args.speed)
+    } else {
+        format!("Poll: {poll_interval}s")
+    };
+    // With --input or --replay the feed type is meaningless; show the path instead
+    let feed = args.replay.as_ref().or(args.input.as_ref()).map_or_else(
+        || args.feed.as_str().to_string(),
+        |p| p.display().to_string(),
+    );
     tracing::info!(
-        "streaming earthquakes from {} feed (poll every {}s)",
-        args.feed.as_str(),
-        poll_interval
+        "streaming earthquakes from {} {} ({})",
+        source.name(),
+        feed,
+        mode
     );
 
     // Print startup banner
@@ -144,90 +255,311 @@ fn cmd_live(args: cli::LiveArgs) -> Result<()> {
         let stdout = io::stdout();
         let mut handle = stdout.lock();
         writeln!(handle, "\x1b[1m🌍 SeismoTail Live Stream\x1b[0m")?;
-        writeln!(handle, "\x1b[2mFeed: {} | Poll: {}s | Press Ctrl+C to stop\x1b[0m", 
-                 args.feed.as_str(), poll_interval)?;
+        if args.push {
+            writeln!(
+                handle,
+                "\x1b[2mSource: {} | Push: WebSocket | Press Ctrl+C to stop\x1b[0m",
+                source.name()
+            )?;
+        } else {
+            writeln!(
+                handle,
+                "\x1b[2mSource: {} | Feed: {} | {} | Press Ctrl+C to stop\x1b[0m",
+                source.name(),
+                feed,
+                mode
+            )?;
+        }
         writeln!(handle, "\x1b[2m─────────────────────────────────────────────────────────────────────\x1b[0m")?;
     }
 
+    if args.push {
+        live_push(
+            &runtime,
+            &sources.emsc_ws_url,
+            sources.retry,
+            &mut processor,
+        )
+    } else if args.replay.is_some() {
+        // The replay paces polls itself on the recorded timeline
+        live_poll(&runtime, source.as_ref(), Duration::ZERO, &mut processor)
+    } else {
+        live_poll(
+            &runtime,
+            source.as_ref(),
+            Duration::from_secs(poll_interval),
+            &mut processor,
+        )
+    }
+}
+
+/// Poll the source until it is exhausted, writing new and updated events.
+fn live_poll(
+    runtime: &tokio::runtime::Runtime,
+    source: &dyn EventSource,
+    poll_interval: Duration,
+    processor: &mut LiveProcessor,
+) -> Result<()> {
     let mut poll_count = 0u64;
+    // Polls in a row whose fetch failed even after the client's retries
+    let mut consecutive_failures = 0u32;
+    let mut total_rejected = 0u64;
 
     loop {
         poll_count += 1;
-        
-        match client.fetch_feed(args.feed) {
-            Ok(feed) => {
+
+        let fetch = runtime.block_on(source.fetch_if_modified());
+        if fetch.is_ok() && consecutive_failures > 0 {
+            tracing::info!("feed recovered after {} failed polls", consecutive_failures);
+            consecutive_failures = 0;
+        }
+
+        match fetch {
+            Ok(FeedFetch::NotModified) => {
+                // Nothing regenerated upstream: skip filtering and dedup entirely
+                tracing::debug!("poll #{}: feed not modified", poll_count);
+            }
+            Ok(FeedFetch::Modified(feed)) => {
                 let stdout = io::stdout();
                 let mut handle = stdout.lock();
                 let mut new_count = 0u64;
                 let mut update_count = 0u64;
 
                 for event in &feed.features {
-                    // Apply filters first (before dedup check)
-                    if !filter.matches(event) {
-                        continue;
-                    }
-
-                    // Check deduplication with update detection
-                    let dedup_result = dedup.check_and_mark(&event.id, event.properties.updated);
-                    
-                    if !dedup_result.should_emit() {
-                        continue;
-                    }
-
-                    if dedup_result.is_update() {
-                        update_count += 1;
-                        // Optionally show update indicator
-                        write!(handle, "\x1b[2m↻ UPDATE: \x1b[0m")?;
-                    } else {
-                        new_count += 1;
-                    }
-
-                    // Output event
-                    if let Err(e) = output::write_events(&mut handle, &[event.clone()], args.format) {
-                        tracing::warn!("failed to write event: {}", e);
+                    match processor.process(&mut handle, event) {
+                        Emitted::New => new_count += 1,
+                        Emitted::Update => update_count += 1,
+                        Emitted::Skipped => {}
                     }
-
-                    // Flush after each event for real-time output
-                    let _ = handle.flush();
                 }
 
+                // Malformed features skipped by the lenient parser
+                let rejected_count = feed.report.rejected.len() as u64;
+                total_rejected += rejected_count;
+
                 // Log poll stats at debug level
-                if new_count > 0 || update_count > 0 {
+                if new_count > 0 || update_count > 0 || rejected_count > 0 {
                     tracing::debug!(
-                        "poll #{}: {} new, {} updates (dedup rate: {:.1}%)",
+                        "poll #{}: {} new, {} updates, {} rejected ({} total) (dedup rate: {:.1}%)",
                         poll_count,
                         new_count,
                         update_count,
-                        dedup.dupe_rate() * 100.0
+                        rejected_count,
+                        total_rejected,
+                        processor.dupe_rate() * 100.0
                     );
                 }
             }
             Err(e) => {
-                tracing::warn!("fetch failed, will retry: {}", e);
+                // A bad response or a transient outage should not end the
+                // monitor; the next poll gets a fresh chance
+                consecutive_failures += 1;
+                if e.is_retryable() {
+                    tracing::warn!(
+                        "fetch failed ({} consecutive), will retry next poll: {}",
+                        consecutive_failures,
+                        e
+                    );
+                } else {
+                    tracing::error!(
+                        "poll #{} failed ({} consecutive), will try again next poll: {}",
+                        poll_count,
+                        consecutive_failures,
+                        e
+                    );
+                }
             }
         }
 
-        std::thread::sleep(std::time::Duration::from_secs(poll_interval));
+        if source.exhausted() {
+            tracing::info!("{} exhausted after {} polls", source.name(), poll_count);
+            return Ok(());
+        }
+        std::thread::sleep(poll_interval);
     }
 }
 
+/// Stream events pushed over the EMSC WebSocket until the stream gives up.
+fn live_push(
+    runtime: &tokio::runtime::Runtime,
+    url: &str,
+    backoff: RetryPolicy,
+    processor: &mut LiveProcessor,
+) -> Result<()> {
+    let push = EmscPush::new(url, backoff);
+    let (tx, mut rx) = tokio::sync::mpsc::channel(256);
+    let task = runtime.spawn(async move { push.run(tx).await });
+
+    while let Some(event) = runtime.block_on(rx.recv()) {
+        let stdout = io::stdout();
+        let mut handle = stdout.lock();
+        processor.process(&mut handle, &event);
+    }
+
+    // The sender is gone: the push client stopped for good
+    runtime
+        .block_on(task)
+        .context("push task panicked")?
+        .context("EMSC push stream failed")
+}
+
+/// Execute the `event` command - one event with its products.
+fn cmd_event(args: &cli::EventArgs, usgs_url: &str, retry: RetryPolicy) -> Result<()> {
+    let client = UsgsClient::with_base_url(usgs_url)
+        .context("failed to create USGS client")?
+        .with_retry(retry);
+
+    let detail = client
+        .fetch_event_detail(&args.id)
+        .with_context(|| format!("failed to fetch event {}", args.id))?;
+
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    output::write_event_detail(&mut handle, &detail, args.format, args.schema)?;
+
+    Ok(())
+}
+
 /// Execute the `query` command - historical search.
-fn cmd_query(_args: cli::QueryArgs) -> Result<()> {
-    // TODO: Implement FDSN query in Phase 3
-    anyhow::bail!("query command not yet implemented (Phase 3)")
+///
+/// Results larger than the per-request cap are fetched in time windows
+/// and streamed to stdout one window at a time.
+fn cmd_query(args: &cli::QueryArgs, usgs_url: &str, retry: RetryPolicy) -> Result<()> {
+    let client = UsgsClient::with_base_url(usgs_url)
+        .context("failed to create USGS client")?
+        .with_retry(retry);
+
+    let start = args.start;
+    let end = args.end.unwrap_or_else(Utc::now);
+    let query = EventQuery {
+        start: Some(start),
+        end: Some(end),
+        min_magnitude: args.min_magnitude,
+        max_magnitude: args.max_magnitude,
+        min_depth: args.min_depth,
+        max_depth: args.max_depth,
+        bbox: args.bbox,
+        radius: args.radius,
+        order_by: args.order_by,
+        limit: None,
+    };
+    query.validate().map_err(anyhow::Error::msg)?;
+
+    let count = client
+        .count(&query)
+        .context("failed to count matching events")?;
+    let cap = count.max_allowed.map_or(query::MAX_QUERY_LIMIT, |m| {
+        usize::try_from(m).unwrap_or(usize::MAX)
+    });
+    let limit = if args.limit == 0 {
+        usize::MAX
+    } else {
+        args.limit
+    };
+    let wanted = limit.min(usize::try_from(count.count).unwrap_or(usize::MAX));
+
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    let mut stream = output::EventStream::new(&mut handle, args.format, args.schema);
+
+    // Small enough for one request: let the service apply ordering and limit
+    if wanted <= cap {
+        let feed = client
+            .query(&EventQuery {
+                limit: Some(wanted.max(1)),
+                ..query
+            })
+            .context("failed to query earthquake catalog")?;
+        stream.write(&feed.features)?;
+        stream.finish()?;
+        return Ok(());
+    }
+
+    let cap_u64 = u64::try_from(cap).unwrap_or(u64::MAX);
+    let mut windows = query::plan_windows(start, end, count.count, cap_u64, &mut |s, e| {
+        client
+            .count(&EventQuery {
+                start: Some(s),
+                end: Some(e),
+                ..query.clone()
+            })
+            .map(|c| c.count)
+    })
+    .context("failed to count events while splitting the time range")?;
+
+    match args.order_by {
+        OrderBy::Time => windows.reverse(),
+        OrderBy::TimeAsc => {}
+        OrderBy::Magnitude | OrderBy::MagnitudeAsc => tracing::warn!(
+            "result exceeds {} events; magnitude ordering applies within each time window only",
+            cap
+        ),
+    }
+
+    tracing::info!(
+        "{} matching events exceed the {}-event cap; fetching {} time windows",
+        count.count,
+        cap,
+        windows.len()
+    );
+
+    let total = windows.len();
+    for (i, window) in windows.iter().enumerate() {
+        let remaining = wanted - stream.written();
+        if remaining == 0 {
+            break;
+        }
+        if window.count == 0 {
+            continue;
+        }
+
+        tracing::info!(
+            "window {}/{}: {} → {} ({} events)",
+            i + 1,
+            total,
+            window.start.to_rfc3339(),
+            window.end.to_rfc3339(),
+            window.count
+        );
+
+        let feed = client
+            .query(&EventQuery {
+                start: Some(window.start),
+                end: Some(window.end),
+                limit: Some(remaining.min(cap)),
+                ..query.clone()
+            })
+            .with_context(|| format!("failed to fetch window {}/{}", i + 1, total))?;
+        stream.write(&feed.features)?;
+    }
+
+    let written = stream.written();
+    stream.finish()?;
+    tracing::info!("fetched {} events", written);
+
+    Ok(())
 }
 
 /// Execute the `ui` command - start web server.
-fn cmd_ui(args: cli::UiArgs) -> Result<()> {
+fn cmd_ui(args: &cli::UiArgs, sources: &SourceConfig) -> Result<()> {
     // Build server config
     let config = server::ServerConfig {
         port: args.port,
         host: args.host.clone(),
-        feed_type: args.feed,
         poll_interval: args.poll_interval.max(30),
         filter: EventFilter {
             min_magnitude: args.min_magnitude,
-            ..Default::default()
+            expr: args.filter_expr.clone(),
+            regions: args.region.clone(),
+            distance_model: args.location.distance_model,
+            ..args.filters.to_filter()
+        },
+        annotator: args.location.annotator(),
+        source: SourceConfig {
+            kind: args.source,
+            feed: args.feed,
+            record: args.record.clone(),
+            ..sources.clone()
         },
     };
 
@@ -235,7 +567,8 @@ fn cmd_ui(args: cli::UiArgs) -> Result<()> {
     let url = format!("http://{}:{}", args.host, args.port);
     println!("\x1b[1m🌍 SeismoTail Web UI\x1b[0m");
     println!("\x1b[2m───────────────────────────────────────\x1b[0m");
-    println!("  Local:   \x1b[96m{}\x1b[0m", url);
+    println!("  Local:   \x1b[96m{url}\x1b[0m");
+    println!("  Source:  {}", args.source.as_str());
     println!("  Feed:    {}", args.feed.as_str());
     println!("  Poll:    {}s", args.poll_interval);
     println!("\x1b[2m───────────────────────────────────────\x1b[0m");
@@ -257,9 +590,37 @@ fn cmd_ui(args: cli::UiArgs) -> Result<()> {
         .block_on(server::run_server(config))
 }
 
+/// Execute the `mock-server` command - serve recorded feeds locally.
+fn cmd_mock_server(args: &cli::MockServerArgs) -> Result<()> {
+    let state = mock::MockState::load(&args.dir)?;
+    let config = mock::MockConfig {
+        host: args.host.clone(),
+        port: args.port,
+        mutate_every: args.mutate_every,
+        seed: args.seed,
+    };
+
+    let url = format!("http://{}:{}", args.host, args.port);
+    println!("\x1b[1m🌍 SeismoTail Mock USGS Server\x1b[0m");
+    println!("\x1b[2m───────────────────────────────────────\x1b[0m");
+    println!("  Local:   \x1b[96m{url}\x1b[0m");
+    println!("  Feeds:   {}", state.feed_names().join(", "));
+    match args.mutate_every {
+        Some(secs) => println!("  Mutate:  every {secs}s (seed {})", args.seed),
+        None => println!("  Mutate:  off"),
+    }
+    println!("\x1b[2m───────────────────────────────────────\x1b[0m");
+    println!("\x1b[2mTry: seismotail --usgs-url {url} live\x1b[0m\n");
+
+    tokio::runtime::Runtime::new()
+        .context("failed to create tokio runtime")?
+        .block_on(mock::run_mock_server(config, state))
+}
+
 /// Run the EEW detection demo.
-fn cmd_detect(args: cli::DetectArgs) -> Result<()> {
-    use crate::eew::{AccelerometerRecord, AlertLevel, Detection, OpenEewClient, StaLtaDetector};
+#[allow(clippy::too_many_lines)]
+fn cmd_detect(args: &cli::DetectArgs) -> Result<()> {
+    use crate::eew::{AccelerometerRecord, OpenEewClient, StaLtaDetector};
 
     println!("\x1b[1m🚨 SeismoTail EEW Detection\x1b[0m");
     println!("\x1b[2m───────────────────────────────────────\x1b[0m");
@@ -267,34 +628,6 @@ fn cmd_detect(args: cli::DetectArgs) -> Result<()> {
     println!("  Threshold: {}", args.threshold);
     println!("\x1b[2m───────────────────────────────────────\x1b[0m\n");
 
-    // Helper to print detections
-    fn print_detections(detections: &[Detection]) {
-        if detections.is_empty() {
-            println!("  \x1b[92m✓ No significant seismic activity detected\x1b[0m");
-        } else {
-            println!("  \x1b[93mFound {} detection(s):\x1b[0m\n", detections.len());
-            for det in detections {
-                let alert_color = match det.alert_level {
-                    AlertLevel::Severe => "\x1b[95m",
-                    AlertLevel::Strong => "\x1b[91m",
-                    AlertLevel::Moderate => "\x1b[93m",
-                    AlertLevel::Light => "\x1b[92m",
-                    _ => "\x1b[0m",
-                };
-
-                println!("  \x1b[1m{} EARTHQUAKE DETECTED!\x1b[0m", det.alert_level.emoji());
-                println!("  ├─ Device:    {}", det.device_id);
-                println!("  ├─ PGA:       {:.2} gals (cm/s²)", det.pga);
-                println!("  ├─ STA/LTA:   {:.2}", det.sta_lta_ratio);
-                println!("  ├─ Alert:     {}{}\x1b[0m", alert_color, det.alert_level.as_str().to_uppercase());
-                if let Some(mag) = det.estimated_magnitude {
-                    println!("  └─ Est. Mag:  ~M{:.1}", mag);
-                }
-                println!();
-            }
-        }
-    }
-
     if args.simulate {
         // Simulate earthquake detection with synthetic waveform
         println!("\x1b[93m▶ Running detection on synthetic waveform...\x1b[0m\n");
@@ -317,6 +650,7 @@ fn cmd_detect(args: cli::DetectArgs) -> Result<()> {
         y.extend(vec![0.002; 10]);
         z.extend(vec![0.002; 10]);
 
+        #[allow(clippy::cast_precision_loss)]
         let record = AccelerometerRecord {
             device_id: "demo-sensor-001".to_string(),
             timestamp: chrono::Utc::now().timestamp() as f64,
@@ -345,9 +679,9 @@ fn cmd_detect(args: cli::DetectArgs) -> Result<()> {
         // Real data mode - fetch from OpenEEW S3
         println!("\x1b[93m▶ Fetching real data from OpenEEW (AWS S3)...\x1b[0m\n");
         println!("  Country: {}", args.country);
-        println!("  Date:    {}", date);
+        println!("  Date:    {date}");
         if let Some(hour) = &args.hour {
-            println!("  Hour:    {}:00 UTC", hour);
+            println!("  Hour:    {hour}:00 UTC");
         }
         println!("  Bucket:  s3://grillo-openeew/\n");
 
@@ -370,14 +704,14 @@ fn cmd_detect(args: cli::DetectArgs) -> Result<()> {
                     let device_limit = 5;
                     let files_per_device = 12; // Cover full hour (5-min intervals)
                     println!("  \x1b[92m✓ Found {} devices\x1b[0m", devices.len());
-                    println!("  Analyzing {} devices, {} files each...\n", device_limit, files_per_device);
+                    println!("  Analyzing {device_limit} devices, {files_per_device} files each...\n");
 
                     let mut all_detections = Vec::new();
                     let mut records_processed = 0;
                     let mut files_processed = 0;
 
                     for device_id in devices.iter().take(device_limit) {
-                        print!("  Device {}... ", device_id);
+                        print!("  Device {device_id}... ");
                         
                         match client.list_files(&args.country, device_id, date, args.hour.as_deref()).await {
                             Ok(files) => {
@@ -389,29 +723,26 @@ fn cmd_detect(args: cli::DetectArgs) -> Result<()> {
                                 let mut device_records = 0;
                                 let mut max_pga: f32 = 0.0;
                                 for key in files.iter().take(files_per_device) {
-                                    match client.fetch_records(key).await {
-                                        Ok(records) => {
-                                            device_records += records.len();
-                                            records_processed += records.len();
-                                            files_processed += 1;
-                                            
-                                            for record in &records {
-                                                // Track max PGA seen
-                                                for i in 0..record.x.len().min(record.y.len()).min(record.z.len()) {
-                                                    let pga = eew::StaLtaDetector::calculate_pga(
-                                                        record.x[i], record.y[i], record.z[i]
-                                                    );
-                                                    if pga > max_pga { max_pga = pga; }
-                                                }
-                                                
-                                                let dets = detector.detect(record);
-                                                all_detections.extend(dets);
+                                    if let Ok(records) = client.fetch_records(key).await {
+                                        device_records += records.len();
+                                        records_processed += records.len();
+                                        files_processed += 1;
+                                        
+                                        for record in &records {
+                                            // Track max PGA seen
+                                            for i in 0..record.x.len().min(record.y.len()).min(record.z.len()) {
+                                                let pga = eew::StaLtaDetector::calculate_pga(
+                                                    record.x[i], record.y[i], record.z[i]
+                                                );
+                                                if pga > max_pga { max_pga = pga; }
                                             }
+                                            
+                                            let dets = detector.detect(record);
+                                            all_detections.extend(dets);
                                         }
-                                        Err(_) => continue,
                                     }
                                 }
-                                println!("\x1b[92m{} records, max PGA: {:.1} gals\x1b[0m", device_records, max_pga);
+                                println!("\x1b[92m{device_records} records, max PGA: {max_pga:.1} gals\x1b[0m");
                             }
                             Err(_) => {
                                 println!("\x1b[2mskipped\x1b[0m");
@@ -421,14 +752,14 @@ fn cmd_detect(args: cli::DetectArgs) -> Result<()> {
 
                     println!("\n\x1b[2m───────────────────────────────────────\x1b[0m");
                     println!("\x1b[1mResults:\x1b[0m");
-                    println!("  Files processed:   {}", files_processed);
-                    println!("  Records processed: {}", records_processed);
+                    println!("  Files processed:   {files_processed}");
+                    println!("  Records processed: {records_processed}");
                     println!("  Detections found:  {}\n", all_detections.len());
 
                     print_detections(&all_detections);
                 }
                 Err(e) => {
-                    println!("  \x1b[91m✗ Error: {}\x1b[0m", e);
+                    println!("  \x1b[91m✗ Error: {e}\x1b[0m");
                     println!("\n  \x1b[2mMake sure the date format is YYYY-MM-DD\x1b[0m");
                 }
             }
@@ -444,3 +775,33 @@ fn cmd_detect(args: cli::DetectArgs) -> Result<()> {
 
     Ok(())
 }
+
+/// Print EEW detections with alert-level coloring.
+fn print_detections(detections: &[eew::Detection]) {
+    use crate::eew::AlertLevel;
+
+    if detections.is_empty() {
+        println!("  \x1b[92m✓ No significant seismic activity detected\x1b[0m");
+    } else {
+        println!("  \x1b[93mFound {} detection(s):\x1b[0m\n", detections.len());
+        for det in detections {
+            let alert_color = match det.alert_level {
+                AlertLevel::Severe => "\x1b[95m",
+                AlertLevel::Strong => "\x1b[91m",
+                AlertLevel::Moderate => "\x1b[93m",
+                AlertLevel::Light => "\x1b[92m",
+                _ => "\x1b[0m",
+            };
+
+            println!("  \x1b[1m{} EARTHQUAKE DETECTED!\x1b[0m", det.alert_level.emoji());
+            println!("  ├─ Device:    {}", det.device_id);
+            println!("  ├─ PGA:       {:.2} gals (cm/s²)", det.pga);
+            println!("  ├─ STA/LTA:   {:.2}", det.sta_lta_ratio);
+            println!("  ├─ Alert:     {}{}\x1b[0m", alert_color, det.alert_level.as_str().to_uppercase());
+            if let Some(mag) = det.estimated_magnitude {
+                println!("  └─ Est. Mag:  ~M{mag:.1}");
+            }
+            println!();
+        }
+    }
+}
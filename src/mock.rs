@@ -0,0 +1,383 @@
+//! Mock USGS feed server.
+//!
+//! Serves recorded GeoJSON summary feeds from a directory under the real
+//! `/earthquakes/feed/v1.0/summary/*.geojson` paths, so `live` and `ui` can
+//! be exercised offline via `--usgs-url`. Optionally mutates the feeds over
+//! time (new events, magnitude revisions) to drive the update paths.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use axum::{
+    Json, Router,
+    extract::{Path as UrlPath, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use serde_json::{Value, json};
+
+use crate::client::FeedType;
+
+/// Upper bound on events added per feed by mutation (bounded memory).
+const MAX_ADDED_EVENTS: usize = 500;
+
+/// Mock server configuration.
+#[derive(Debug, Clone)]
+pub struct MockConfig {
+    pub host: String,
+    pub port: u16,
+    /// Mutate feeds every this many seconds (None = serve as recorded)
+    pub mutate_every: Option<u64>,
+    /// Seed for the deterministic mutation sequence
+    pub seed: u64,
+}
+
+/// A recorded feed plus its mutation bookkeeping.
+#[derive(Debug, Clone)]
+struct MockFeed {
+    doc: Value,
+    recorded_count: usize,
+}
+
+/// Shared mock server state.
+#[derive(Clone)]
+pub struct MockState {
+    feeds: Arc<RwLock<HashMap<&'static str, MockFeed>>>,
+}
+
+impl MockState {
+    /// Load every `<feed>.geojson` (or `sample_<feed>.json`) found in `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be read or is not a feature collection,
+    /// or if the directory holds no feeds at all.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let mut feeds = HashMap::new();
+
+        for feed in FeedType::ALL {
+            let candidates = [
+                dir.join(format!("{}.geojson", feed.as_str())),
+                dir.join(format!("sample_{}.json", feed.as_str())),
+            ];
+            let Some(path) = candidates.iter().find(|p| p.is_file()) else {
+                continue;
+            };
+
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            let doc: Value = serde_json::from_str(&text)
+                .with_context(|| format!("failed to parse {}", path.display()))?;
+            let recorded_count = doc["features"]
+                .as_array()
+                .map(Vec::len)
+                .with_context(|| format!("{} has no features array", path.display()))?;
+
+            tracing::info!("serving {} from {}", feed.as_str(), path.display());
+            feeds.insert(
+                feed.as_str(),
+                MockFeed {
+                    doc,
+                    recorded_count,
+                },
+            );
+        }
+
+        anyhow::ensure!(
+            !feeds.is_empty(),
+            "no recorded feeds (<feed>.geojson) found in {}",
+            dir.display()
+        );
+
+        Ok(Self {
+            feeds: Arc::new(RwLock::new(feeds)),
+        })
+    }
+
+    /// Names of the feeds being served.
+    #[must_use]
+    pub fn feed_names(&self) -> Vec<&'static str> {
+        let mut names: Vec<_> = self
+            .feeds
+            .read()
+            .map(|f| f.keys().copied().collect())
+            .unwrap_or_default();
+        names.sort_unstable();
+        names
+    }
+
+    /// Apply one round of mutations to every feed.
+    fn mutate(&self, rng: &mut Rng, now_ms: i64) {
+        if let Ok(mut feeds) = self.feeds.write() {
+            for feed in feeds.values_mut() {
+                mutate_feed(feed, rng, now_ms);
+            }
+        }
+    }
+}
+
+/// Create the mock router.
+pub fn create_router(state: MockState) -> Router {
+    Router::new()
+        .route("/earthquakes/feed/v1.0/summary/{file}", get(feed_handler))
+        .with_state(state)
+}
+
+/// Run the mock server until interrupted.
+///
+/// # Errors
+///
+/// Returns an error if the listener cannot be bound.
+pub async fn run_mock_server(config: MockConfig, state: MockState) -> Result<()> {
+    if let Some(secs) = config.mutate_every {
+        let mutate_state = state.clone();
+        let mut rng = Rng::new(config.seed);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(secs.max(1)));
+            // The first tick completes immediately; serve the recording first
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                mutate_state.mutate(&mut rng, chrono::Utc::now().timestamp_millis());
+                tracing::debug!("mutated mock feeds");
+            }
+        });
+    }
+
+    let addr = format!("{}:{}", config.host, config.port);
+    let listener = tokio::net::TcpListener::bind(&addr)
+        .await
+        .with_context(|| format!("failed to bind {addr}"))?;
+    axum::serve(listener, create_router(state)).await?;
+
+    Ok(())
+}
+
+/// Serve a summary feed by file name (`<feed>.geojson`).
+///
+/// Like the real CDN, responses carry an `ETag` (derived from
+/// `metadata.generated`) and honor `If-None-Match` with 304.
+async fn feed_handler(
+    State(state): State<MockState>,
+    UrlPath(file): UrlPath<String>,
+    headers: HeaderMap,
+) -> Response {
+    let name = file.strip_suffix(".geojson").unwrap_or(&file);
+    let doc = state
+        .feeds
+        .read()
+        .ok()
+        .and_then(|feeds| feeds.get(name).map(|f| f.doc.clone()));
+
+    match doc {
+        Some(doc) => {
+            let etag = format!("\"{}\"", doc["metadata"]["generated"]);
+            let if_none_match = headers
+                .get(header::IF_NONE_MATCH)
+                .and_then(|v| v.to_str().ok());
+            if if_none_match == Some(etag.as_str()) {
+                return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+            }
+            ([(header::ETAG, etag)], Json(doc)).into_response()
+        }
+        None => (
+            StatusCode::NOT_FOUND,
+            format!(
+                "feed '{name}' not recorded (available: {})",
+                state.feed_names().join(", ")
+            ),
+        )
+            .into_response(),
+    }
+}
+
+/// Add a synthetic event and revise an existing one.
+fn mutate_feed(feed: &mut MockFeed, rng: &mut Rng, now_ms: i64) {
+    let Some(features) = feed.doc["features"].as_array_mut() else {
+        return;
+    };
+    if features.is_empty() {
+        return;
+    }
+
+    // Revise a random existing event: magnitude drift, reviewed status
+    let idx = rng.below(features.len());
+    let props = &mut features[idx]["properties"];
+    if let Some(mag) = props["mag"].as_f64() {
+        let delta = rng.range(-0.4, 0.4);
+        props["mag"] = json!(round1(mag + delta));
+    }
+    props["magType"] = json!("mww");
+    props["status"] = json!("reviewed");
+    props["updated"] = json!(now_ms);
+
+    // Add a new event near a random existing one
+    let template = features[rng.below(features.len())].clone();
+    let serial = features.len() + 1;
+    let mut event = template;
+    let id = format!("mock{now_ms}{serial}");
+    event["id"] = json!(id);
+    if let Some(coords) = event["geometry"]["coordinates"].as_array_mut()
+        && coords.len() == 3
+    {
+        let lon = coords[0].as_f64().unwrap_or(0.0) + rng.range(-0.5, 0.5);
+        let lat = coords[1].as_f64().unwrap_or(0.0) + rng.range(-0.5, 0.5);
+        coords[0] = json!(lon.clamp(-180.0, 180.0));
+        coords[1] = json!(lat.clamp(-90.0, 90.0));
+    }
+    let props = &mut event["properties"];
+    let mag = round1(rng.range(2.5, 6.0));
+    props["mag"] = json!(mag);
+    props["magType"] = json!("ml");
+    props["time"] = json!(now_ms);
+    props["updated"] = json!(now_ms);
+    props["status"] = json!("automatic");
+    props["alert"] = Value::Null;
+    props["code"] = json!(id);
+    props["ids"] = json!(format!(",{id},"));
+    props["title"] = json!(format!("M {mag} - mock event"));
+    features.insert(0, event);
+
+    // Bounded growth: drop the oldest events from the end
+    features.truncate(feed.recorded_count + MAX_ADDED_EVENTS);
+
+    let count = features.len();
+    feed.doc["metadata"]["count"] = json!(count);
+    feed.doc["metadata"]["generated"] = json!(now_ms);
+}
+
+fn round1(v: f64) -> f64 {
+    (v * 10.0).round() / 10.0
+}
+
+/// Small deterministic PRNG (xorshift64*), so mutation runs are reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform float in `[lo, hi)`.
+    #[allow(clippy::cast_precision_loss)]
+    fn range(&mut self, lo: f64, hi: f64) -> f64 {
+        let unit = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        lo + unit * (hi - lo)
+    }
+
+    /// Uniform index in `[0, n)`.
+    #[allow(clippy::cast_possible_truncation)]
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{AsyncUsgsClient, FeedFetch};
+
+    fn sample_state() -> MockState {
+        MockState::load(Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tools"))).unwrap()
+    }
+
+    #[test]
+    fn test_load_recorded_feeds() {
+        assert_eq!(sample_state().feed_names(), vec!["2.5_day"]);
+    }
+
+    #[test]
+    fn test_mutation_adds_and_revises_events() {
+        let state = sample_state();
+        let before = state.feeds.read().unwrap()["2.5_day"].doc.clone();
+
+        state.mutate(&mut Rng::new(7), 1_800_000_000_000);
+
+        let after = state.feeds.read().unwrap()["2.5_day"].doc.clone();
+        let features = after["features"].as_array().unwrap();
+        assert_eq!(
+            features.len(),
+            before["features"].as_array().unwrap().len() + 1
+        );
+        assert_eq!(after["metadata"]["count"], json!(features.len()));
+        assert_eq!(after["metadata"]["generated"], json!(1_800_000_000_000_i64));
+
+        // Newest event first, plus at least one revised existing event
+        assert_eq!(
+            features[0]["properties"]["time"],
+            json!(1_800_000_000_000_i64)
+        );
+        let revised = features[1..]
+            .iter()
+            .filter(|f| f["properties"]["updated"] == json!(1_800_000_000_000_i64))
+            .count();
+        assert_eq!(revised, 1);
+    }
+
+    #[test]
+    fn test_mutation_is_bounded() {
+        let state = sample_state();
+        let mut rng = Rng::new(1);
+        for i in 0..(MAX_ADDED_EVENTS + 50) {
+            state.mutate(&mut rng, 1_800_000_000_000 + i64::try_from(i).unwrap());
+        }
+        let feeds = state.feeds.read().unwrap();
+        let feed = &feeds["2.5_day"];
+        assert_eq!(
+            feed.doc["features"].as_array().unwrap().len(),
+            feed.recorded_count + MAX_ADDED_EVENTS
+        );
+    }
+
+    #[tokio::test]
+    async fn test_serves_feed_to_client() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, create_router(sample_state()))
+                .await
+                .unwrap();
+        });
+
+        let client = AsyncUsgsClient::with_base_url(&format!("http://{addr}")).unwrap();
+        let feed = client.fetch_feed(FeedType::Mag25Day).await.unwrap();
+        assert_eq!(feed.features.len(), 8);
+
+        let missing = client.fetch_feed(FeedType::AllHour).await;
+        assert!(missing.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_conditional_fetch_gets_304() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, create_router(sample_state()))
+                .await
+                .unwrap();
+        });
+
+        let client = AsyncUsgsClient::with_base_url(&format!("http://{addr}")).unwrap();
+        let first = client
+            .fetch_feed_if_modified(FeedType::Mag25Day)
+            .await
+            .unwrap();
+        assert!(matches!(first, FeedFetch::Modified(_)));
+        let second = client
+            .fetch_feed_if_modified(FeedType::Mag25Day)
+            .await
+            .unwrap();
+        assert!(matches!(second, FeedFetch::NotModified));
+    }
+}
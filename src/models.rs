@@ -1,17 +1,29 @@
 //! Data models for USGS earthquake API responses.
 //!
-//! These structures match the GeoJSON format from USGS feeds.
+//! These structures match the GeoJSON format from USGS feeds and serialize
+//! back to it (e.g. for recorded polls).
 //! See RFC 002 for full contract details.
 
+use std::collections::HashMap;
+
 use chrono::{DateTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
+use tracing::warn;
 
+use crate::client::FeedType;
 use crate::errors::SeismotailError;
+use crate::filters;
+use crate::geodesic::DistanceModel;
+use crate::quality::QualityGrade;
+use crate::types::{AlertLevel, EventStatus, EventType, MagnitudeType, Published};
 
 /// Top-level GeoJSON response from USGS feeds.
-#[derive(Debug, Clone, Deserialize)]
+///
+/// Mirrors the wire format, so not every field is consumed.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeatureCollection {
-    /// Always "FeatureCollection"
+    /// Always `"FeatureCollection"`
     #[serde(rename = "type")]
     pub type_: String,
 
@@ -20,9 +32,162 @@ pub struct FeatureCollection {
 
     /// Earthquake events
     pub features: Vec<Feature>,
+
+    /// Features dropped while parsing
+    #[serde(skip)]
+    pub report: FeedReport,
+
+    /// Response bodies the collection was parsed from, for `--record`
+    #[serde(skip)]
+    pub bodies: Vec<RawBody>,
+}
+
+/// A response body exactly as a source received it.
+#[derive(Debug, Clone)]
+pub struct RawBody {
+    pub format: BodyFormat,
+    pub text: String,
+}
+
+/// Wire format of a [`RawBody`], with what it takes to parse it again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "lowercase")]
+pub enum BodyFormat {
+    /// USGS GeoJSON feed or query result
+    GeoJson,
+    QuakeMl,
+    /// EMSC FDSN `format=json`
+    Emsc,
+    /// `GeoNet` quake API, limited to the window of `feed`
+    GeoNet {
+        feed: FeedType,
+    },
+}
+
+/// Wire form used for lenient parsing: features are decoded one by one.
+#[derive(Deserialize)]
+struct RawCollection {
+    #[serde(rename = "type")]
+    type_: String,
+    metadata: Metadata,
+    features: Vec<serde_json::Value>,
 }
 
 impl FeatureCollection {
+    /// Create an empty collection (e.g. for queries with no matches).
+    #[must_use]
+    pub fn empty() -> Self {
+        Self {
+            type_: "FeatureCollection".into(),
+            metadata: Metadata::default(),
+            features: Vec::new(),
+            report: FeedReport::default(),
+            bodies: Vec::new(),
+        }
+    }
+
+    /// Keep the response body this collection was parsed from.
+    #[must_use]
+    pub fn with_body(mut self, format: BodyFormat, text: String) -> Self {
+        self.bodies.push(RawBody { format, text });
+        self
+    }
+
+    /// Merge collections, keeping the latest revision of each event at the
+    /// position it first appeared, and the reports and bodies of all.
+    #[must_use]
+    pub fn merge(collections: impl IntoIterator<Item = Self>) -> Self {
+        let mut features: Vec<Feature> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut report = FeedReport::default();
+        let mut bodies = Vec::new();
+
+        for collection in collections {
+            for feature in collection.features {
+                match index.get(&feature.id) {
+                    Some(&at) if features[at].properties.updated >= feature.properties.updated => {}
+                    Some(&at) => features[at] = feature,
+                    None => {
+                        index.insert(feature.id.clone(), features.len());
+                        features.push(feature);
+                    }
+                }
+            }
+            report.rejected.extend(collection.report.rejected);
+            bodies.extend(collection.bodies);
+        }
+
+        Self {
+            report,
+            bodies,
+            ..Self::from_features(features)
+        }
+    }
+
+    /// Parse a GeoJSON feed, skipping features that fail to decode or
+    /// validate instead of rejecting the whole collection.
+    ///
+    /// Skipped features are listed in [`FeatureCollection::report`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the collection itself is malformed.
+    pub fn from_json(text: &str) -> Result<Self, SeismotailError> {
+        let raw: RawCollection = serde_json::from_str(text)?;
+        let mut features = Vec::with_capacity(raw.features.len());
+        let mut report = FeedReport::default();
+
+        for (index, value) in raw.features.into_iter().enumerate() {
+            let id = value
+                .get("id")
+                .and_then(serde_json::Value::as_str)
+                .map(String::from);
+            let parsed = serde_json::from_value::<Feature>(value)
+                .map_err(SeismotailError::from)
+                .and_then(|f| f.validate().map(|()| f));
+            match parsed {
+                Ok(feature) => features.push(feature),
+                Err(e) => {
+                    warn!(
+                        "skipping feature #{} ({}): {}",
+                        index,
+                        id.as_deref().unwrap_or("no id"),
+                        e
+                    );
+                    report.rejected.push(RejectedFeature {
+                        index,
+                        id,
+                        reason: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        let feed = Self {
+            type_: raw.type_,
+            metadata: raw.metadata,
+            features,
+            report,
+            bodies: Vec::new(),
+        };
+        feed.validate()?;
+        Ok(feed)
+    }
+
+    /// Wrap events normalized from another provider.
+    #[must_use]
+    pub fn from_features(features: Vec<Feature>) -> Self {
+        Self {
+            metadata: Metadata {
+                generated: Utc::now().timestamp_millis(),
+                count: features.len(),
+                ..Metadata::default()
+            },
+            features,
+            ..Self::empty()
+        }
+    }
+
     /// Validate the response structure.
     pub fn validate(&self) -> Result<(), SeismotailError> {
         if self.type_ != "FeatureCollection" {
@@ -35,8 +200,28 @@ impl FeatureCollection {
     }
 }
 
+/// A feature skipped by [`FeatureCollection::from_json`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RejectedFeature {
+    /// Position in the `features` array
+    pub index: usize,
+
+    /// Event ID, if the feature had a readable one
+    pub id: Option<String>,
+
+    /// Why the feature was rejected
+    pub reason: String,
+}
+
+/// Features skipped while parsing a feed.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FeedReport {
+    pub rejected: Vec<RejectedFeature>,
+}
+
 /// Metadata about the feed response.
-#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Metadata {
     /// When this feed was generated (ms since epoch)
     pub generated: i64,
@@ -57,8 +242,20 @@ pub struct Metadata {
     pub count: usize,
 }
 
+/// Response from the FDSN `count` endpoint (`format=geojson`).
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct CountResponse {
+    /// Number of matching events
+    pub count: u64,
+
+    /// Per-request event cap advertised by the service
+    #[serde(rename = "maxAllowed")]
+    pub max_allowed: Option<u64>,
+}
+
 /// A single earthquake event.
-#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Feature {
     /// Always "Feature"
     #[serde(rename = "type")]
@@ -72,6 +269,22 @@ pub struct Feature {
 
     /// Event properties
     pub properties: Properties,
+
+    /// Names of the `--region`s containing the event (not part of the feed)
+    #[serde(skip)]
+    pub regions: Vec<String>,
+
+    /// Distance and bearing from `--home` (not part of the feed)
+    #[serde(skip)]
+    pub home: Option<Proximity>,
+
+    /// Nearest gazetteer place (not part of the feed)
+    #[serde(skip)]
+    pub nearest_place: Option<NearbyPlace>,
+
+    /// Names of the watch zones the event triggers (not part of the feed)
+    #[serde(skip)]
+    pub zones: Vec<String>,
 }
 
 impl Feature {
@@ -114,26 +327,62 @@ impl Feature {
     }
 }
 
+/// Distance and direction from a reference point to an event.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Proximity {
+    pub distance_km: f64,
+    /// Initial bearing, degrees clockwise from north
+    pub bearing_deg: f64,
+    /// 16-point compass direction, e.g. `"NNE"`
+    pub direction: &'static str,
+}
+
+impl Proximity {
+    /// Measure from `(from_lat, from_lon)` to `(lat, lon)`, rounded to 0.1 km and 0.1°.
+    #[must_use]
+    pub fn between(model: DistanceModel, from_lat: f64, from_lon: f64, lat: f64, lon: f64) -> Self {
+        let distance = model.distance_km(from_lat, from_lon, lat, lon);
+        let bearing = model.bearing(from_lat, from_lon, lat, lon);
+        Self {
+            distance_km: (distance * 10.0).round() / 10.0,
+            bearing_deg: (bearing * 10.0).round() / 10.0,
+            direction: filters::compass_point(bearing),
+        }
+    }
+}
+
+/// Populated place nearest to an event; the event lies `proximity` from it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NearbyPlace {
+    pub name: String,
+    /// ISO 3166 country code
+    pub country: String,
+    #[serde(flatten)]
+    pub proximity: Proximity,
+}
+
 /// Geographic geometry for an event.
-#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Geometry {
     /// Always "Point"
     #[serde(rename = "type")]
     pub type_: String,
 
-    /// Coordinates: [longitude, latitude, depth_km]
+    /// Coordinates: `[longitude, latitude, depth_km]`
     pub coordinates: Vec<f64>,
 }
 
 /// Event properties from USGS API.
-#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Properties {
     /// Magnitude value
     pub mag: Option<f64>,
 
     /// Magnitude type (mb, Ml, Mw, etc.)
     #[serde(rename = "magType")]
-    pub mag_type: Option<String>,
+    pub mag_type: Option<Published<MagnitudeType>>,
 
     /// Human-readable place description
     pub place: Option<String>,
@@ -145,10 +394,10 @@ pub struct Properties {
     pub updated: i64,
 
     /// Event status: "automatic" or "reviewed"
-    pub status: String,
+    pub status: Published<EventStatus>,
 
     /// Alert level: null, "green", "yellow", "orange", "red"
-    pub alert: Option<String>,
+    pub alert: Option<Published<AlertLevel>>,
 
     /// Tsunami flag: 0 or 1
     pub tsunami: i32,
@@ -203,7 +452,32 @@ pub struct Properties {
 
     /// Event type (earthquake, quarry, etc.)
     #[serde(rename = "type")]
-    pub event_type: Option<String>,
+    pub event_type: Option<Published<EventType>>,
+}
+
+/// Version of the JSON/NDJSON event contract.
+///
+/// v1 is the original flat event. v2 adds the remaining feed properties and
+/// a `"schema": 2` marker; see `schema/event.v2.schema.json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchemaVersion {
+    /// Original fields only (default, for existing consumers)
+    #[default]
+    V1,
+    /// Full event with `"schema": 2`
+    V2,
+}
+
+impl std::str::FromStr for SchemaVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "v1" | "1" => Ok(Self::V1),
+            "v2" | "2" => Ok(Self::V2),
+            _ => Err(format!("unknown schema version: {s} (expected: v1, v2)")),
+        }
+    }
 }
 
 /// Simplified event for output.
@@ -211,29 +485,78 @@ pub struct Properties {
 /// This is the normalized structure we emit in JSON/NDJSON output.
 #[derive(Debug, Clone, Serialize)]
 pub struct OutputEvent {
+    /// Schema version; absent in v1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<u8>,
     pub id: String,
     pub time: String,
     pub magnitude: Option<f64>,
-    pub magnitude_type: Option<String>,
+    pub magnitude_type: Option<Published<MagnitudeType>>,
     pub depth_km: f64,
     pub latitude: f64,
     pub longitude: f64,
     pub place: Option<String>,
-    pub alert: Option<String>,
+    pub alert: Option<Published<AlertLevel>>,
     pub tsunami: bool,
-    pub status: String,
+    pub status: Published<EventStatus>,
     pub significance: i32,
     pub url: Option<String>,
+    /// Named `--region`s containing the event; omitted when none
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub regions: Vec<String>,
+    /// Distance and bearing from `--home`; omitted without it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub home: Option<Proximity>,
+    /// Nearest gazetteer place; omitted without `--nearest`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nearest_place: Option<NearbyPlace>,
+    /// Watch zones the event triggers; omitted when none
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub zones: Vec<String>,
+    /// Fields added in v2
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub extended: Option<ExtendedFields>,
 }
 
-impl From<&Feature> for OutputEvent {
-    fn from(f: &Feature) -> Self {
+/// Event fields emitted from schema v2 on.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtendedFields {
+    /// Last modification time
+    pub updated: String,
+    pub event_type: Option<Published<EventType>>,
+    /// Network that contributed the preferred solution
+    pub net: String,
+    /// Contributing networks
+    pub sources: Vec<String>,
+    /// All catalog IDs of the event
+    pub ids: Vec<String>,
+    pub felt: Option<i32>,
+    pub cdi: Option<f64>,
+    pub mmi: Option<f64>,
+    pub nst: Option<i32>,
+    pub gap: Option<f64>,
+    pub rms: Option<f64>,
+    pub dmin: Option<f64>,
+    /// Location quality grade derived from `nst`, `gap`, `dmin` and `rms`
+    pub quality: Option<QualityGrade>,
+    /// Detail GeoJSON URL
+    pub detail: Option<String>,
+}
+
+impl OutputEvent {
+    /// Build the output form of `f` for a schema version.
+    #[must_use]
+    pub fn new(f: &Feature, schema: SchemaVersion) -> Self {
+        let extended = match schema {
+            SchemaVersion::V1 => None,
+            SchemaVersion::V2 => Some(ExtendedFields::from(f)),
+        };
         Self {
+            schema: extended.as_ref().map(|_| 2),
             id: f.id.clone(),
             time: f
                 .time()
-                .map(|t| t.to_rfc3339())
-                .unwrap_or_else(|| "unknown".into()),
+                .map_or_else(|| "unknown".into(), |t| t.to_rfc3339()),
             magnitude: f.properties.mag,
             magnitude_type: f.properties.mag_type.clone(),
             depth_km: f.depth_km(),
@@ -245,10 +568,49 @@ impl From<&Feature> for OutputEvent {
             status: f.properties.status.clone(),
             significance: f.properties.sig,
             url: f.properties.url.clone(),
+            regions: f.regions.clone(),
+            home: f.home.clone(),
+            nearest_place: f.nearest_place.clone(),
+            zones: f.zones.clone(),
+            extended,
         }
     }
 }
 
+impl From<&Feature> for ExtendedFields {
+    fn from(f: &Feature) -> Self {
+        let p = &f.properties;
+        Self {
+            updated: Utc
+                .timestamp_millis_opt(p.updated)
+                .single()
+                .map_or_else(|| "unknown".into(), |t| t.to_rfc3339()),
+            event_type: p.event_type.clone(),
+            net: p.net.clone(),
+            sources: split_codes(p.sources.as_deref()),
+            ids: split_codes(p.ids.as_deref()),
+            felt: p.felt,
+            cdi: p.cdi,
+            mmi: p.mmi,
+            nst: p.nst,
+            gap: p.gap,
+            rms: p.rms,
+            dmin: p.dmin,
+            quality: QualityGrade::of(f),
+            detail: p.detail.clone(),
+        }
+    }
+}
+
+/// Split a USGS code list such as `",us,ak,"` into its entries.
+fn split_codes(list: Option<&str>) -> Vec<String> {
+    list.unwrap_or_default()
+        .split(',')
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -268,4 +630,37 @@ mod tests {
             assert!(!feature.id.is_empty());
         }
     }
+
+    #[test]
+    fn test_from_json_skips_bad_features() {
+        let mut doc: serde_json::Value =
+            serde_json::from_str(crate::test_support::SAMPLE_FEED).unwrap();
+        let features = doc["features"].as_array_mut().unwrap();
+        features[1]["properties"]["time"] = serde_json::Value::Null;
+        features[2]["properties"]
+            .as_object_mut()
+            .unwrap()
+            .remove("net");
+        features[3]["geometry"]["coordinates"] = serde_json::json!([-122.8, 38.8]);
+        let total = features.len();
+
+        let feed = FeatureCollection::from_json(&doc.to_string()).unwrap();
+        assert_eq!(feed.features.len(), total - 3);
+
+        let rejected = &feed.report.rejected;
+        assert_eq!(
+            rejected.iter().map(|r| r.index).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        assert_eq!(rejected[0].id.as_deref(), doc["features"][1]["id"].as_str());
+        assert!(rejected[1].reason.contains("net"), "{}", rejected[1].reason);
+        assert!(
+            rejected[2].reason.contains("coordinates"),
+            "{}",
+            rejected[2].reason
+        );
+
+        // The collection itself must still be well-formed
+        assert!(FeatureCollection::from_json(r#"{"type":"Feature"}"#).is_err());
+    }
 }
@@ -1,10 +1,17 @@
 //! Output formatters for earthquake events.
 //!
-//! Supports human-readable (with colors), JSON, and NDJSON formats.
+//! Supports human-readable (with colors), JSON, NDJSON and QuakeML formats.
 
 use std::io::{self, Write};
 
-use crate::models::{Feature, OutputEvent};
+use serde::Serialize;
+
+use crate::dedup::RevisionDiff;
+use crate::detail::{EventDetail, EventProducts, NodalPlane};
+use crate::models::{Feature, OutputEvent, SchemaVersion};
+use crate::quakeml;
+use crate::quality::QualityGrade;
+use crate::types::{AlertLevel, Published};
 
 // ANSI color codes
 const RESET: &str = "\x1b[0m";
@@ -39,6 +46,19 @@ pub enum Format {
     Json,
     /// Newline-delimited JSON (one object per line)
     Ndjson,
+    /// QuakeML 1.2 XML document
+    QuakeMl,
+}
+
+impl Format {
+    /// Whether events can be written one at a time, as `live` does.
+    ///
+    /// QuakeML wraps every event in a single document, so a stream of
+    /// per-event writes would not parse.
+    #[must_use]
+    pub fn is_incremental(self) -> bool {
+        !matches!(self, Self::QuakeMl)
+    }
 }
 
 impl std::str::FromStr for Format {
@@ -49,11 +69,56 @@ impl std::str::FromStr for Format {
             "human" => Ok(Self::Human),
             "json" => Ok(Self::Json),
             "ndjson" => Ok(Self::Ndjson),
-            _ => Err(format!("unknown format: {s} (expected: human, json, ndjson)")),
+            "quakeml" => Ok(Self::QuakeMl),
+            _ => Err(format!(
+                "unknown format: {s} (expected: human, json, ndjson, quakeml)"
+            )),
+        }
+    }
+}
+
+/// Ordering of `tail` results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    /// Most recent first (default)
+    #[default]
+    Time,
+    /// Largest first
+    Magnitude,
+    /// Nearest to `--home` first
+    Distance,
+}
+
+impl std::str::FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "time" => Ok(Self::Time),
+            "magnitude" | "mag" => Ok(Self::Magnitude),
+            "distance" => Ok(Self::Distance),
+            _ => Err(format!(
+                "unknown sort order: {s} (expected: time, magnitude, distance)"
+            )),
         }
     }
 }
 
+/// Sort events by `key`; events lacking the key (no magnitude, no distance) go last.
+pub fn sort_events(events: &mut [Feature], key: SortKey) {
+    match key {
+        SortKey::Time => events.sort_by_key(|e| std::cmp::Reverse(e.properties.time)),
+        SortKey::Magnitude => events.sort_by(|a, b| {
+            let mag = |e: &Feature| e.properties.mag.unwrap_or(f64::NEG_INFINITY);
+            mag(b).total_cmp(&mag(a))
+        }),
+        SortKey::Distance => events.sort_by(|a, b| {
+            let distance = |e: &Feature| e.home.as_ref().map_or(f64::INFINITY, |h| h.distance_km);
+            distance(a).total_cmp(&distance(b))
+        }),
+    }
+}
+
 /// Get the color code for a magnitude value.
 fn magnitude_color(mag: Option<f64>) -> &'static str {
     match mag {
@@ -78,13 +143,13 @@ fn magnitude_label(mag: Option<f64>) -> &'static str {
 }
 
 /// Format alert level with color.
-fn format_alert(alert: Option<&str>) -> String {
+fn format_alert(alert: Option<&AlertLevel>) -> String {
     match alert {
-        Some("red") => format!(" {ALERT_RED} RED {RESET}"),
-        Some("orange") => format!(" {ALERT_ORANGE} ORANGE {RESET}"),
-        Some("yellow") => format!(" {ALERT_YELLOW} YELLOW {RESET}"),
-        Some("green") => format!(" {ALERT_GREEN} GREEN {RESET}"),
-        _ => String::new(),
+        Some(AlertLevel::Red) => format!(" {ALERT_RED} RED {RESET}"),
+        Some(AlertLevel::Orange) => format!(" {ALERT_ORANGE} ORANGE {RESET}"),
+        Some(AlertLevel::Yellow) => format!(" {ALERT_YELLOW} YELLOW {RESET}"),
+        Some(AlertLevel::Green) => format!(" {ALERT_GREEN} GREEN {RESET}"),
+        Some(AlertLevel::Unknown(_)) | None => String::new(),
     }
 }
 
@@ -97,21 +162,19 @@ fn format_alert(alert: Option<&str>) -> String {
 /// Returns an error if writing fails.
 pub fn write_human<W: Write>(writer: &mut W, events: &[Feature]) -> io::Result<()> {
     for event in events {
-        let time = event
-            .time()
-            .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
-            .unwrap_or_else(|| "unknown".into());
+        let time = event.time().map_or_else(
+            || "unknown".into(),
+            |t| t.format("%Y-%m-%d %H:%M:%S").to_string(),
+        );
 
         let mag = event.properties.mag;
-        let mag_str = mag
-            .map(|m| format!("{m:.1}"))
-            .unwrap_or_else(|| "?".into());
+        let mag_str = mag.map_or_else(|| "?".into(), |m| format!("{m:.1}"));
 
         let mag_type = event
             .properties
             .mag_type
-            .as_deref()
-            .unwrap_or("?");
+            .as_ref()
+            .map_or("?", Published::as_str);
 
         let depth = event.depth_km();
         let place = event
@@ -120,6 +183,8 @@ pub fn write_human<W: Write>(writer: &mut W, events: &[Feature]) -> io::Result<(
             .as_deref()
             .unwrap_or("Unknown location");
 
+        let quality = QualityGrade::of(event).map_or("-", QualityGrade::as_str);
+
         let color = magnitude_color(mag);
         let label = magnitude_label(mag);
         let alert = format_alert(event.properties.alert.as_deref());
@@ -131,8 +196,38 @@ pub fn write_human<W: Write>(writer: &mut W, events: &[Feature]) -> io::Result<(
             String::new()
         };
 
+        // Nearest known place and distance from home
+        let nearest = event.nearest_place.as_ref().map_or_else(String::new, |p| {
+            format!(
+                " {DIM}({:.0} km {} of {}, {}){RESET}",
+                p.proximity.distance_km, p.proximity.direction, p.name, p.country
+            )
+        });
+        let home = event.home.as_ref().map_or_else(String::new, |h| {
+            format!(" {DIM}⌂ {:.0} km {}{RESET}", h.distance_km, h.direction)
+        });
+
+        // Triggered watch zones
+        let zones = if event.zones.is_empty() {
+            String::new()
+        } else {
+            format!(" {BOLD}⚑ {}{RESET}", event.zones.join(", "))
+        };
+
+        // Matching named regions
+        let regions = if event.regions.is_empty() {
+            String::new()
+        } else {
+            format!(" {DIM}[{}]{RESET}", event.regions.join(", "))
+        };
+
         // Alert indicator
-        let alert_icon = if event.properties.alert.is_some() {
+        let alert_icon = if event
+            .properties
+            .alert
+            .as_deref()
+            .is_some_and(AlertLevel::is_known)
+        {
             format!(" {ICON_ALERT}")
         } else {
             String::new()
@@ -142,9 +237,9 @@ pub fn write_human<W: Write>(writer: &mut W, events: &[Feature]) -> io::Result<(
             writer,
             "{ICON_QUAKE} {color}{BOLD}M{mag_str}{RESET} {DIM}{mag_type}{RESET} │ \
              {color}{label:8}{RESET} │ \
-             {DIM}{depth:>5.0}km{RESET} │ \
+             {DIM}{depth:>5.0}km{RESET} {quality} │ \
              {time} UTC │ \
-             {place}{tsunami}{alert_icon}{alert}"
+             {place}{nearest}{home}{regions}{zones}{tsunami}{alert_icon}{alert}"
         )?;
     }
     Ok(())
@@ -155,8 +250,12 @@ pub fn write_human<W: Write>(writer: &mut W, events: &[Feature]) -> io::Result<(
 /// # Errors
 ///
 /// Returns an error if serialization or writing fails.
-pub fn write_json<W: Write>(writer: &mut W, events: &[Feature]) -> io::Result<()> {
-    let output: Vec<OutputEvent> = events.iter().map(OutputEvent::from).collect();
+pub fn write_json<W: Write>(
+    writer: &mut W,
+    events: &[Feature],
+    schema: SchemaVersion,
+) -> io::Result<()> {
+    let output: Vec<OutputEvent> = events.iter().map(|e| OutputEvent::new(e, schema)).collect();
     let json = serde_json::to_string_pretty(&output)
         .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
     writeln!(writer, "{json}")
@@ -169,9 +268,13 @@ pub fn write_json<W: Write>(writer: &mut W, events: &[Feature]) -> io::Result<()
 /// # Errors
 ///
 /// Returns an error if serialization or writing fails.
-pub fn write_ndjson<W: Write>(writer: &mut W, events: &[Feature]) -> io::Result<()> {
+pub fn write_ndjson<W: Write>(
+    writer: &mut W,
+    events: &[Feature],
+    schema: SchemaVersion,
+) -> io::Result<()> {
     for event in events {
-        let output = OutputEvent::from(event);
+        let output = OutputEvent::new(event, schema);
         let json = serde_json::to_string(&output)
             .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
         writeln!(writer, "{json}")?;
@@ -179,28 +282,583 @@ pub fn write_ndjson<W: Write>(writer: &mut W, events: &[Feature]) -> io::Result<
     Ok(())
 }
 
+/// Incremental writer for results that arrive in chunks.
+///
+/// Line-oriented formats are passed straight to [`write_events`]. JSON is
+/// emitted as a single array across all chunks, byte-identical to what
+/// [`write_json`] would produce for the concatenated events, so callers never
+/// need to hold the full result set in memory. QuakeML likewise becomes a
+/// single document.
+pub struct EventStream<'a, W: Write> {
+    writer: &'a mut W,
+    format: Format,
+    schema: SchemaVersion,
+    written: usize,
+}
+
+impl<'a, W: Write> EventStream<'a, W> {
+    /// Start a stream over `writer`.
+    pub fn new(writer: &'a mut W, format: Format, schema: SchemaVersion) -> Self {
+        Self {
+            writer,
+            format,
+            schema,
+            written: 0,
+        }
+    }
+
+    /// Write the next chunk of events.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization or writing fails.
+    pub fn write(&mut self, events: &[Feature]) -> io::Result<()> {
+        if self.format == Format::QuakeMl {
+            for event in events {
+                if self.written == 0 {
+                    quakeml::write_header(self.writer)?;
+                }
+                quakeml::write_event(self.writer, event)?;
+                self.written += 1;
+            }
+            return Ok(());
+        }
+        if self.format != Format::Json {
+            self.written += events.len();
+            return write_events(self.writer, events, self.format, self.schema);
+        }
+
+        for event in events {
+            let json = serde_json::to_string_pretty(&OutputEvent::new(event, self.schema))
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let separator = if self.written == 0 { "[\n" } else { ",\n" };
+            // Indent to match the element nesting of a pretty-printed array
+            write!(self.writer, "{separator}  {}", json.replace('\n', "\n  "))?;
+            self.written += 1;
+        }
+        Ok(())
+    }
+
+    /// Number of events written so far.
+    #[must_use]
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Close the stream, terminating the JSON array or QuakeML document.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    pub fn finish(self) -> io::Result<()> {
+        if self.format == Format::QuakeMl {
+            if self.written == 0 {
+                quakeml::write_header(self.writer)?;
+            }
+            return quakeml::write_footer(self.writer);
+        }
+        if self.format != Format::Json {
+            return Ok(());
+        }
+        if self.written == 0 {
+            writeln!(self.writer, "[]")
+        } else {
+            writeln!(self.writer, "\n]")
+        }
+    }
+}
+
+/// Event detail as emitted in JSON/NDJSON: the usual event fields plus
+/// the preferred products.
+#[derive(Serialize)]
+struct DetailOutput {
+    #[serde(flatten)]
+    event: OutputEvent,
+    products: EventProducts,
+}
+
+/// Write a single event with its products in the specified format.
+///
+/// # Errors
+///
+/// Returns an error if serialization or writing fails.
+pub fn write_event_detail<W: Write>(
+    writer: &mut W,
+    detail: &EventDetail,
+    format: Format,
+    schema: SchemaVersion,
+) -> io::Result<()> {
+    let feature = detail.feature();
+    let products = detail.products();
+
+    match format {
+        Format::Human => {
+            write_human(writer, std::slice::from_ref(&feature))?;
+            return write_products_human(writer, &products);
+        }
+        // QuakeML carries the event itself; products have no BED form here
+        Format::QuakeMl => return quakeml::write_quakeml(writer, std::slice::from_ref(&feature)),
+        Format::Json | Format::Ndjson => {}
+    }
+
+    let output = DetailOutput {
+        event: OutputEvent::new(&feature, schema),
+        products,
+    };
+    let json = if format == Format::Json {
+        serde_json::to_string_pretty(&output)
+    } else {
+        serde_json::to_string(&output)
+    }
+    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    writeln!(writer, "{json}")
+}
+
+/// Format an optional number with a fixed precision.
+fn opt_num(v: Option<f64>, precision: usize) -> String {
+    v.map_or_else(|| "?".into(), |v| format!("{v:.precision$}"))
+}
+
+/// Write nodal planes as indented lines.
+fn write_nodal_planes<W: Write>(writer: &mut W, planes: &[NodalPlane]) -> io::Result<()> {
+    for (i, plane) in planes.iter().enumerate() {
+        writeln!(
+            writer,
+            "  {DIM}│{RESET}   Nodal plane {}: strike {:>5.1}°  dip {:>4.1}°  rake {:>6.1}°",
+            i + 1,
+            plane.strike,
+            plane.dip,
+            plane.rake
+        )?;
+    }
+    Ok(())
+}
+
+/// Write the product summary below the event line.
+fn write_products_human<W: Write>(writer: &mut W, products: &EventProducts) -> io::Result<()> {
+    if let Some(pager) = &products.losspager {
+        writeln!(
+            writer,
+            "  {DIM}│{RESET} {BOLD}PAGER{RESET}         {} max MMI {}",
+            format_alert(pager.alert_level.as_ref()),
+            opt_num(pager.max_mmi, 1)
+        )?;
+    }
+    if let Some(mt) = &products.moment_tensor {
+        writeln!(
+            writer,
+            "  {DIM}│{RESET} {BOLD}Moment tensor{RESET}  {} {} at {} km, M0 {} N·m, DC {}% {DIM}({}){RESET}",
+            mt.magnitude_type.as_deref().unwrap_or("M?"),
+            opt_num(mt.magnitude, 2),
+            opt_num(mt.depth_km, 1),
+            mt.scalar_moment
+                .map_or_else(|| "?".into(), |m| format!("{m:.3e}")),
+            opt_num(mt.percent_double_couple.map(|p| p * 100.0), 0),
+            mt.source
+        )?;
+        write_nodal_planes(writer, &mt.nodal_planes)?;
+    }
+    if let Some(fm) = &products.focal_mechanism {
+        writeln!(
+            writer,
+            "  {DIM}│{RESET} {BOLD}Focal mech.{RESET}    {} stations {DIM}({}){RESET}",
+            fm.num_stations
+                .map_or_else(|| "?".into(), |n| n.to_string()),
+            fm.source
+        )?;
+        write_nodal_planes(writer, &fm.nodal_planes)?;
+    }
+    if let Some(shakemap) = &products.shakemap {
+        writeln!(
+            writer,
+            "  {DIM}│{RESET} {BOLD}ShakeMap{RESET}       max MMI {}, PGA {} %g, PGV {} cm/s {DIM}(v{}){RESET}",
+            opt_num(shakemap.max_mmi, 1),
+            opt_num(shakemap.max_pga, 1),
+            opt_num(shakemap.max_pgv, 1),
+            shakemap.version.as_deref().unwrap_or("?")
+        )?;
+    }
+    if let Some(dyfi) = &products.dyfi {
+        writeln!(
+            writer,
+            "  {DIM}│{RESET} {BOLD}DYFI{RESET}           max CDI {} from {} responses",
+            opt_num(dyfi.max_cdi, 1),
+            dyfi.num_responses
+                .map_or_else(|| "?".into(), |n| n.to_string())
+        )?;
+    }
+    if let Some(origin) = &products.origin {
+        writeln!(
+            writer,
+            "  {DIM}│{RESET} {BOLD}Origin{RESET}         {} stations, gap {}°, {} {DIM}({}){RESET}",
+            origin
+                .num_stations
+                .map_or_else(|| "?".into(), |n| n.to_string()),
+            opt_num(origin.azimuthal_gap, 0),
+            origin.review_status.as_deref().unwrap_or("unknown"),
+            origin.source
+        )?;
+    }
+    Ok(())
+}
+
+/// Updated event as emitted in JSON/NDJSON: the event plus what changed.
+#[derive(Serialize)]
+struct UpdateOutput<'a> {
+    #[serde(flatten)]
+    event: OutputEvent,
+    changes: &'a RevisionDiff,
+}
+
+/// Write a revised event in the specified format.
+///
+/// Human output prefixes the event with a line such as
+/// `↻ us7000abcd M4.2 ml → M4.6 mww, status automatic → reviewed`; JSON and
+/// NDJSON add a `changes` object to the event.
+///
+/// # Errors
+///
+/// Returns an error if serialization or writing fails.
+pub fn write_update<W: Write>(
+    writer: &mut W,
+    event: &Feature,
+    changes: &RevisionDiff,
+    format: Format,
+    schema: SchemaVersion,
+) -> io::Result<()> {
+    let json = match format {
+        Format::Human => {
+            let summary = if changes.is_empty() {
+                "updated".to_string()
+            } else {
+                changes.to_string()
+            };
+            writeln!(writer, "{DIM}↻ {} {summary}{RESET}", event.id)?;
+            return write_human(writer, std::slice::from_ref(event));
+        }
+        Format::QuakeMl => return quakeml::write_quakeml(writer, std::slice::from_ref(event)),
+        Format::Json => serde_json::to_string_pretty(&UpdateOutput {
+            event: OutputEvent::new(event, schema),
+            changes,
+        }),
+        Format::Ndjson => serde_json::to_string(&UpdateOutput {
+            event: OutputEvent::new(event, schema),
+            changes,
+        }),
+    }
+    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    writeln!(writer, "{json}")
+}
+
 /// Write events in the specified format.
 ///
 /// # Errors
 ///
 /// Returns an error if writing fails.
-pub fn write_events<W: Write>(writer: &mut W, events: &[Feature], format: Format) -> io::Result<()> {
+pub fn write_events<W: Write>(
+    writer: &mut W,
+    events: &[Feature],
+    format: Format,
+    schema: SchemaVersion,
+) -> io::Result<()> {
     match format {
         Format::Human => write_human(writer, events),
-        Format::Json => write_json(writer, events),
-        Format::Ndjson => write_ndjson(writer, events),
+        Format::Json => write_json(writer, events, schema),
+        Format::Ndjson => write_ndjson(writer, events, schema),
+        Format::QuakeMl => quakeml::write_quakeml(writer, events),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::dedup::Revision;
+    use serde_json::Value;
 
     #[test]
     fn test_format_parse() {
         assert_eq!("human".parse::<Format>().unwrap(), Format::Human);
         assert_eq!("json".parse::<Format>().unwrap(), Format::Json);
         assert_eq!("ndjson".parse::<Format>().unwrap(), Format::Ndjson);
+        assert_eq!("QuakeML".parse::<Format>().unwrap(), Format::QuakeMl);
         assert!("invalid".parse::<Format>().is_err());
     }
+
+    #[test]
+    fn test_incremental_formats() {
+        assert!(Format::Human.is_incremental());
+        assert!(Format::Ndjson.is_incremental());
+        assert!(!Format::QuakeMl.is_incremental());
+    }
+
+    fn sample_events() -> Vec<Feature> {
+        let feed: crate::models::FeatureCollection =
+            serde_json::from_str(crate::test_support::SAMPLE_FEED).unwrap();
+        feed.features
+    }
+
+    #[test]
+    fn test_stream_json_matches_single_write() {
+        let events = sample_events();
+
+        let mut expected = Vec::new();
+        write_json(&mut expected, &events, SchemaVersion::V2).unwrap();
+
+        let mut streamed = Vec::new();
+        let mut stream = EventStream::new(&mut streamed, Format::Json, SchemaVersion::V2);
+        for chunk in events.chunks(3) {
+            stream.write(chunk).unwrap();
+        }
+        assert_eq!(stream.written(), events.len());
+        stream.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(streamed).unwrap(),
+            String::from_utf8(expected).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_stream_quakeml_is_one_document() {
+        let events = sample_events();
+
+        let mut expected = Vec::new();
+        write_events(&mut expected, &events, Format::QuakeMl, SchemaVersion::V1).unwrap();
+
+        let mut streamed = Vec::new();
+        let mut stream = EventStream::new(&mut streamed, Format::QuakeMl, SchemaVersion::V1);
+        stream.write(&[]).unwrap();
+        for chunk in events.chunks(3) {
+            stream.write(chunk).unwrap();
+        }
+        stream.finish().unwrap();
+        assert_eq!(streamed, expected);
+
+        let mut empty = Vec::new();
+        EventStream::new(&mut empty, Format::QuakeMl, SchemaVersion::V1)
+            .finish()
+            .unwrap();
+        let parsed = quakeml::parse_quakeml(&String::from_utf8(empty).unwrap()).unwrap();
+        assert!(parsed.features.is_empty());
+    }
+
+    #[test]
+    fn test_stream_empty_json_is_empty_array() {
+        let mut out = Vec::new();
+        EventStream::new(&mut out, Format::Json, SchemaVersion::V1)
+            .finish()
+            .unwrap();
+        assert_eq!(out, b"[]\n");
+    }
+
+    #[test]
+    fn test_event_detail_json_has_products() {
+        let detail: EventDetail = serde_json::from_str(crate::test_support::SAMPLE_DETAIL).unwrap();
+
+        let mut out = Vec::new();
+        write_event_detail(&mut out, &detail, Format::Ndjson, SchemaVersion::V1).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(value["id"], "us7000n1a2");
+        assert_eq!(value["products"]["losspager"]["alert_level"], "green");
+        assert_eq!(value["products"]["moment_tensor"]["magnitude_type"], "Mww");
+
+        let mut human = Vec::new();
+        write_event_detail(&mut human, &detail, Format::Human, SchemaVersion::V1).unwrap();
+        let human = String::from_utf8(human).unwrap();
+        assert!(human.contains("Moment tensor"));
+        assert!(human.contains("Nodal plane 2"));
+    }
+
+    #[test]
+    fn test_stream_ndjson_one_line_per_event() {
+        let events = sample_events();
+        let mut out = Vec::new();
+        let mut stream = EventStream::new(&mut out, Format::Ndjson, SchemaVersion::V1);
+        stream.write(&events[..2]).unwrap();
+        stream.write(&events[2..]).unwrap();
+        stream.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap().lines().count(),
+            events.len()
+        );
+    }
+
+    #[test]
+    fn test_schema_versions() {
+        let mut events = sample_events();
+        events[0].properties.mag_type = Some("ML".into());
+
+        let mut v1 = Vec::new();
+        write_ndjson(&mut v1, &events[..1], SchemaVersion::V1).unwrap();
+        let v1: serde_json::Value = serde_json::from_slice(&v1).unwrap();
+        assert!(v1.get("schema").is_none());
+        assert!(v1.get("updated").is_none());
+
+        let mut v2 = Vec::new();
+        write_ndjson(&mut v2, &events[..1], SchemaVersion::V2).unwrap();
+        let v2: serde_json::Value = serde_json::from_slice(&v2).unwrap();
+        assert_eq!(v2["schema"], 2);
+        assert_eq!(v2["id"], v1["id"]);
+        assert!(v2["updated"].as_str().unwrap().ends_with("+00:00"));
+        assert!(v2["sources"].is_array());
+        // Attributes keep the provider's spelling in both versions
+        assert_eq!(v1["magnitude_type"], "ML");
+        assert_eq!(v2["magnitude_type"], "ML");
+
+        // The published JSON Schema describes exactly the emitted fields
+        let doc: serde_json::Value =
+            serde_json::from_str(include_str!("../schema/event.v2.schema.json")).unwrap();
+        let documented = doc["properties"].as_object().unwrap();
+        let mut required: Vec<&str> = doc["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r.as_str().unwrap())
+            .collect();
+        let mut emitted: Vec<&str> = v2.as_object().unwrap().keys().map(String::as_str).collect();
+        required.sort_unstable();
+        emitted.sort_unstable();
+        assert_eq!(required, emitted);
+        // Optional fields are documented too
+        assert!(documented.contains_key("regions"));
+        assert!(documented.contains_key("zones"));
+        assert!(documented.contains_key("home"));
+        assert!(documented.contains_key("nearest_place"));
+
+        assert_eq!("v2".parse::<SchemaVersion>().unwrap(), SchemaVersion::V2);
+        assert!("v3".parse::<SchemaVersion>().is_err());
+    }
+
+    /// Check `value` against the subset of JSON Schema the published
+    /// schema uses, returning the path of the first mismatch.
+    fn check_schema(schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+        if let Some(types) = schema.get("type") {
+            let types: Vec<&str> = match types {
+                Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+                t => t.as_str().into_iter().collect(),
+            };
+            let matches = |t: &&str| match *t {
+                "object" => value.is_object(),
+                "array" => value.is_array(),
+                "string" => value.is_string(),
+                "number" => value.is_number(),
+                "integer" => value.is_i64() || value.is_u64(),
+                "boolean" => value.is_boolean(),
+                "null" => value.is_null(),
+                _ => false,
+            };
+            if !types.iter().any(matches) {
+                return Err(format!("{path}: {value} is not {types:?}"));
+            }
+        }
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+            && !allowed.contains(value)
+        {
+            return Err(format!("{path}: {value} is not one of {allowed:?}"));
+        }
+        if let Value::Array(items) = value
+            && let Some(item_schema) = schema.get("items")
+        {
+            for (i, item) in items.iter().enumerate() {
+                check_schema(item_schema, item, &format!("{path}[{i}]"))?;
+            }
+        }
+        if let Value::Object(fields) = value {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for required in schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                let required = required.as_str().unwrap();
+                if !fields.contains_key(required) {
+                    return Err(format!("{path}: missing {required}"));
+                }
+            }
+            for (key, field) in fields {
+                match properties.and_then(|p| p.get(key)) {
+                    Some(field_schema) => {
+                        check_schema(field_schema, field, &format!("{path}.{key}"))?;
+                    }
+                    None if schema["additionalProperties"] == false => {
+                        return Err(format!("{path}: undocumented field {key}"));
+                    }
+                    None => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_output_matches_schema() {
+        let schema: serde_json::Value =
+            serde_json::from_str(include_str!("../schema/event.v2.schema.json")).unwrap();
+        let events = sample_events();
+        let mut revised = events[0].clone();
+        revised.properties.updated += 60_000;
+        revised.properties.mag = Some(6.4);
+        revised.properties.mag_type = Some("mb_lg".into());
+        revised.geometry.coordinates[0] += 0.5;
+        revised.geometry.coordinates[2] += 3.0;
+        revised.properties.status = crate::types::EventStatus::Deleted.into();
+        revised.properties.alert = Some(crate::types::AlertLevel::Orange.into());
+        let changes = Revision::of(&events[0]).diff(&Revision::of(&revised));
+
+        let mut out = Vec::new();
+        write_update(
+            &mut out,
+            &revised,
+            &changes,
+            Format::Ndjson,
+            SchemaVersion::V2,
+        )
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        let emitted = json["changes"].as_object().unwrap();
+        assert_eq!(emitted.len(), 6, "every tracked field changed: {emitted:?}");
+        check_schema(&schema, &json, "$").unwrap();
+
+        // A bare v2 event passes as well, and unknown fields are caught
+        let mut out = Vec::new();
+        write_ndjson(&mut out, &events, SchemaVersion::V2).unwrap();
+        for line in String::from_utf8(out).unwrap().lines() {
+            check_schema(&schema, &serde_json::from_str(line).unwrap(), "$").unwrap();
+        }
+        let mut bogus = json;
+        bogus["changes"]["mag"]["was"] = 4.2.into();
+        assert!(check_schema(&schema, &bogus, "$").is_err());
+    }
+
+    #[test]
+    fn test_sort_by_distance() {
+        let annotator = crate::annotate::Annotator {
+            home: Some("61.2,-149.9".parse().unwrap()), // Anchorage
+            ..Default::default()
+        };
+        let mut events = sample_events();
+        for event in &mut events {
+            annotator.annotate(event);
+        }
+
+        sort_events(&mut events, "distance".parse().unwrap());
+        assert_eq!(events[0].id, "ak0247xyz1");
+        let home = events[0].home.as_ref().unwrap();
+        assert_eq!(home.direction, "SW");
+        assert!(events.windows(2).all(|w| {
+            w[0].home.as_ref().unwrap().distance_km <= w[1].home.as_ref().unwrap().distance_km
+        }));
+
+        let mut out = Vec::new();
+        write_ndjson(&mut out, &events[..1], SchemaVersion::V1).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(json["home"]["direction"], "SW");
+        assert!(json.get("nearest_place").is_none());
+
+        sort_events(&mut events, SortKey::Magnitude);
+        assert_eq!(events[0].id, "us7000n1a2");
+        assert!("nearest".parse::<SortKey>().is_err());
+    }
 }
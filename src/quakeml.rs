@@ -0,0 +1,667 @@
+//! QuakeML 1.2 serialization and parsing.
+//!
+//! Events are written as QuakeML BED documents with one origin and one
+//! magnitude each. Properties without a BED counterpart (alert, felt
+//! reports, URLs, ...) go into elements of a `seismotail` extension
+//! namespace, which QuakeML readers such as `ObsPy` preserve as extra data,
+//! so a document written here parses back into the same [`Feature`].
+//! The parser also reads FDSN `format=quakeml` responses from USGS, EMSC
+//! and other catalogs.
+//! See <https://quake.utah.edu/quakeml/> and RFC 002.
+
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+use chrono::{SecondsFormat, TimeZone, Utc};
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::ResolveResult;
+use quick_xml::reader::NsReader;
+use tracing::debug;
+
+use crate::errors::SeismotailError;
+use crate::models::{Feature, FeatureCollection, Geometry, Properties};
+use crate::query::parse_time;
+use crate::types::{EventStatus, EventType, MagnitudeType, Published};
+
+/// QuakeML BED namespace (default namespace of documents).
+const BED_NS: &str = "http://quakeml.org/xmlns/bed/1.2";
+
+/// QuakeML root element namespace.
+const QUAKEML_NS: &str = "http://quakeml.org/xmlns/quakeml/1.2";
+
+/// ANSS catalog namespace carrying network and event code.
+const CATALOG_NS: &str = "http://anss.org/xmlns/catalog/0.1";
+
+/// Extension namespace for properties QuakeML has no element for.
+const SEISMOTAIL_NS: &str = "urn:seismotail:quakeml:1.0";
+
+/// Prefix of resource identifiers we mint.
+const ID_PREFIX: &str = "smi:local";
+
+/// Write a complete QuakeML document containing `events`.
+///
+/// # Errors
+///
+/// Returns an error if writing fails.
+pub fn write_quakeml<W: Write>(writer: &mut W, events: &[Feature]) -> io::Result<()> {
+    write_header(writer)?;
+    for event in events {
+        write_event(writer, event)?;
+    }
+    write_footer(writer)
+}
+
+/// Write the XML declaration and open `eventParameters`.
+///
+/// # Errors
+///
+/// Returns an error if writing fails.
+pub fn write_header<W: Write>(writer: &mut W) -> io::Result<()> {
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<q:quakeml xmlns="{BED_NS}" xmlns:q="{QUAKEML_NS}" xmlns:catalog="{CATALOG_NS}" xmlns:st="{SEISMOTAIL_NS}">"#
+    )?;
+    writeln!(
+        writer,
+        r#"  <eventParameters publicID="{ID_PREFIX}/eventParameters">"#
+    )
+}
+
+/// Close the elements opened by [`write_header`].
+///
+/// # Errors
+///
+/// Returns an error if writing fails.
+pub fn write_footer<W: Write>(writer: &mut W) -> io::Result<()> {
+    writeln!(writer, "  </eventParameters>")?;
+    writeln!(writer, "</q:quakeml>")
+}
+
+/// Write one `event` element with its preferred origin and magnitude.
+///
+/// # Errors
+///
+/// Returns an error if writing fails.
+pub fn write_event<W: Write>(writer: &mut W, event: &Feature) -> io::Result<()> {
+    let p = &event.properties;
+    let id = escape(&event.id);
+    let origin_id = format!("{ID_PREFIX}/origin/{id}");
+    let magnitude_id = format!("{ID_PREFIX}/magnitude/{id}");
+    let (mode, evaluation) = evaluation(&p.status);
+
+    writeln!(
+        writer,
+        r#"    <event publicID="{ID_PREFIX}/event/{id}" catalog:eventsource="{}" catalog:eventid="{}">"#,
+        escape(&p.net),
+        escape(&p.code)
+    )?;
+    if let Some(place) = &p.place {
+        writeln!(writer, "      <description>")?;
+        writeln!(writer, "        <type>earthquake name</type>")?;
+        writeln!(writer, "        <text>{}</text>", escape(place))?;
+        writeln!(writer, "      </description>")?;
+    }
+
+    writeln!(writer, r#"      <origin publicID="{origin_id}">"#)?;
+    writeln!(
+        writer,
+        "        <time><value>{}</value></time>",
+        format_millis(p.time)
+    )?;
+    writeln!(
+        writer,
+        "        <latitude><value>{}</value></latitude>",
+        event.latitude()
+    )?;
+    writeln!(
+        writer,
+        "        <longitude><value>{}</value></longitude>",
+        event.longitude()
+    )?;
+    // QuakeML depths are in meters
+    writeln!(
+        writer,
+        "        <depth><value>{}</value></depth>",
+        event.depth_km() * 1000.0
+    )?;
+    if p.nst.is_some() || p.rms.is_some() || p.gap.is_some() || p.dmin.is_some() {
+        writeln!(writer, "        <quality>")?;
+        write_opt(writer, 10, "usedStationCount", p.nst)?;
+        write_opt(writer, 10, "standardError", p.rms)?;
+        write_opt(writer, 10, "azimuthalGap", p.gap)?;
+        write_opt(writer, 10, "minimumDistance", p.dmin)?;
+        writeln!(writer, "        </quality>")?;
+    }
+    writeln!(writer, "        <evaluationMode>{mode}</evaluationMode>")?;
+    writeln!(
+        writer,
+        "        <evaluationStatus>{evaluation}</evaluationStatus>"
+    )?;
+    writeln!(writer, "      </origin>")?;
+
+    if p.mag.is_some() || p.mag_type.is_some() {
+        writeln!(writer, r#"      <magnitude publicID="{magnitude_id}">"#)?;
+        if let Some(mag) = p.mag {
+            writeln!(writer, "        <mag><value>{mag}</value></mag>")?;
+        }
+        write_opt(
+            writer,
+            8,
+            "type",
+            p.mag_type.as_ref().map(|t| escape(t.as_str())),
+        )?;
+        writeln!(writer, "        <originID>{origin_id}</originID>")?;
+        writeln!(writer, "      </magnitude>")?;
+    }
+    writeln!(
+        writer,
+        "      <preferredOriginID>{origin_id}</preferredOriginID>"
+    )?;
+    if p.mag.is_some() || p.mag_type.is_some() {
+        writeln!(
+            writer,
+            "      <preferredMagnitudeID>{magnitude_id}</preferredMagnitudeID>"
+        )?;
+    }
+    write_opt(
+        writer,
+        6,
+        "type",
+        p.event_type
+            .as_deref()
+            .map(|t| escape(EventType::as_str(t))),
+    )?;
+    writeln!(writer, "      <creationInfo>")?;
+    writeln!(
+        writer,
+        "        <creationTime>{}</creationTime>",
+        format_millis(p.updated)
+    )?;
+    writeln!(writer, "      </creationInfo>")?;
+    write_extensions(writer, event)?;
+    writeln!(writer, "    </event>")
+}
+
+/// Write extension elements for the properties QuakeML has no place for.
+fn write_extensions<W: Write>(writer: &mut W, event: &Feature) -> io::Result<()> {
+    let p = &event.properties;
+    write_opt(writer, 6, "st:title", p.title.as_deref().map(escape))?;
+    write_opt(writer, 6, "st:url", p.url.as_deref().map(escape))?;
+    write_opt(writer, 6, "st:detail", p.detail.as_deref().map(escape))?;
+    write_opt(
+        writer,
+        6,
+        "st:alert",
+        p.alert.as_deref().map(|a| escape(a.as_str())),
+    )?;
+    write_opt(writer, 6, "st:tsunami", Some(p.tsunami))?;
+    write_opt(writer, 6, "st:sig", Some(p.sig))?;
+    write_opt(writer, 6, "st:felt", p.felt)?;
+    write_opt(writer, 6, "st:cdi", p.cdi)?;
+    write_opt(writer, 6, "st:mmi", p.mmi)?;
+    write_opt(writer, 6, "st:ids", p.ids.as_deref().map(escape))?;
+    write_opt(writer, 6, "st:sources", p.sources.as_deref().map(escape))?;
+    write_opt(writer, 6, "st:types", p.types.as_deref().map(escape))?;
+    for zone in &event.zones {
+        write_opt(writer, 6, "st:zone", Some(escape(zone)))?;
+    }
+    Ok(())
+}
+
+/// Write `<name>value</name>` at `indent` if the value is present.
+fn write_opt<W: Write, T: std::fmt::Display>(
+    writer: &mut W,
+    indent: usize,
+    name: &str,
+    value: Option<T>,
+) -> io::Result<()> {
+    match value {
+        Some(value) => writeln!(writer, "{:indent$}<{name}>{value}</{name}>", ""),
+        None => Ok(()),
+    }
+}
+
+/// Format epoch milliseconds as an ISO 8601 UTC timestamp.
+fn format_millis(ms: i64) -> String {
+    Utc.timestamp_millis_opt(ms)
+        .single()
+        .unwrap_or_default()
+        .to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Map a GeoJSON status to QuakeML evaluation mode and status.
+fn evaluation(status: &EventStatus) -> (&'static str, &'static str) {
+    match status {
+        EventStatus::Reviewed => ("manual", "reviewed"),
+        EventStatus::Deleted => ("automatic", "rejected"),
+        EventStatus::Automatic | EventStatus::Unknown(_) => ("automatic", "preliminary"),
+    }
+}
+
+/// Map QuakeML evaluation mode and status back to a GeoJSON status.
+fn status(mode: Option<&str>, evaluation: Option<&str>) -> EventStatus {
+    match (mode, evaluation) {
+        (_, Some("rejected")) => EventStatus::Deleted,
+        (_, Some("reviewed" | "final")) | (Some("manual"), _) => EventStatus::Reviewed,
+        _ => EventStatus::Automatic,
+    }
+}
+
+/// Parse a QuakeML document into features.
+///
+/// Each event uses its preferred origin and magnitude (falling back to the
+/// first of each). Events without a valid origin time are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the document is not well-formed XML.
+pub fn parse_quakeml(xml: &str) -> Result<FeatureCollection, SeismotailError> {
+    let mut reader = NsReader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    // Element names from the root, extension elements prefixed with "st:"
+    let mut path: Vec<String> = Vec::new();
+    let mut event: Option<EventRecord> = None;
+    let mut features = Vec::new();
+
+    loop {
+        let (ns, xml_event) = reader.read_resolved_event().map_err(invalid)?;
+        match xml_event {
+            Event::Start(start) => {
+                let name = element_name(&ns, &start);
+                if let Some(event) = &mut event {
+                    event.open(&name, &reader, &start);
+                } else if name == "event" {
+                    event = Some(EventRecord::new(&reader, &start));
+                }
+                path.push(name);
+            }
+            Event::Text(text) => {
+                if let Some(event) = &mut event {
+                    let text = text.unescape().map_err(invalid)?;
+                    let names: Vec<&str> = path.iter().map(String::as_str).collect();
+                    event.text(&names, text.into_owned());
+                }
+            }
+            Event::End(_) => {
+                if path.pop().as_deref() == Some("event")
+                    && let Some(done) = event.take()
+                {
+                    features.extend(done.into_feature());
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    debug!("parsed {} QuakeML events", features.len());
+    Ok(FeatureCollection::from_features(features))
+}
+
+/// Wrap an XML error.
+fn invalid(e: impl std::fmt::Display) -> SeismotailError {
+    SeismotailError::InvalidResponse(format!("invalid QuakeML: {e}"))
+}
+
+/// Local name of an element, prefixed with `st:` in the extension namespace.
+fn element_name(ns: &ResolveResult, start: &BytesStart) -> String {
+    let local = String::from_utf8_lossy(start.local_name().into_inner()).into_owned();
+    match ns {
+        ResolveResult::Bound(n) if n.into_inner() == SEISMOTAIL_NS.as_bytes() => {
+            format!("st:{local}")
+        }
+        _ => local,
+    }
+}
+
+/// Attribute value by local name, ignoring its namespace.
+fn attribute(reader: &NsReader<&[u8]>, start: &BytesStart, name: &str) -> Option<String> {
+    start
+        .attributes()
+        .flatten()
+        .find(|attr| reader.resolve_attribute(attr.key).1.into_inner() == name.as_bytes())
+        .and_then(|attr| attr.unescape_value().ok().map(Cow::into_owned))
+}
+
+/// An event being assembled from its child elements.
+#[derive(Debug, Default)]
+struct EventRecord {
+    public_id: String,
+    /// `catalog:eventsource` (network)
+    source: Option<String>,
+    /// `catalog:eventid` (code within the network)
+    code: Option<String>,
+    earthquake_name: Option<String>,
+    region_name: Option<String>,
+    description: (Option<String>, Option<String>),
+    preferred_origin: Option<String>,
+    preferred_magnitude: Option<String>,
+    origins: Vec<OriginRecord>,
+    magnitudes: Vec<MagnitudeRecord>,
+    updated: Option<String>,
+    /// Fields read directly (event type and extension elements)
+    properties: Properties,
+}
+
+/// An origin being assembled.
+#[derive(Debug, Default)]
+struct OriginRecord {
+    public_id: String,
+    time: Option<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    depth_m: Option<f64>,
+    nst: Option<i32>,
+    rms: Option<f64>,
+    gap: Option<f64>,
+    dmin: Option<f64>,
+    mode: Option<String>,
+    status: Option<String>,
+    agency: Option<String>,
+}
+
+/// A magnitude being assembled.
+#[derive(Debug, Default)]
+struct MagnitudeRecord {
+    public_id: String,
+    mag: Option<f64>,
+    mag_type: Option<Published<MagnitudeType>>,
+}
+
+impl EventRecord {
+    fn new(reader: &NsReader<&[u8]>, start: &BytesStart) -> Self {
+        Self {
+            public_id: attribute(reader, start, "publicID").unwrap_or_default(),
+            source: attribute(reader, start, "eventsource"),
+            code: attribute(reader, start, "eventid"),
+            ..Self::default()
+        }
+    }
+
+    /// Handle a child element opening.
+    fn open(&mut self, name: &str, reader: &NsReader<&[u8]>, start: &BytesStart) {
+        let public_id = || attribute(reader, start, "publicID").unwrap_or_default();
+        match name {
+            "origin" => self.origins.push(OriginRecord {
+                public_id: public_id(),
+                ..OriginRecord::default()
+            }),
+            "magnitude" => self.magnitudes.push(MagnitudeRecord {
+                public_id: public_id(),
+                ..MagnitudeRecord::default()
+            }),
+            "description" => self.description = (None, None),
+            _ => {}
+        }
+    }
+
+    /// Handle text content at `path`.
+    fn text(&mut self, path: &[&str], text: String) {
+        let p = &mut self.properties;
+        match path {
+            [.., "event", "type"] => p.event_type = Some(text.into()),
+            [.., "event", "preferredOriginID"] => self.preferred_origin = Some(text),
+            [.., "event", "preferredMagnitudeID"] => self.preferred_magnitude = Some(text),
+            [.., "event", "creationInfo", "creationTime"] => self.updated = Some(text),
+            [.., "event", "description", "type"] => {
+                self.description.0 = Some(text);
+                self.take_description();
+            }
+            [.., "event", "description", "text"] => {
+                self.description.1 = Some(text);
+                self.take_description();
+            }
+            _ if path.contains(&"origin") => {
+                if let Some(origin) = self.origins.last_mut() {
+                    origin.text(path, text);
+                }
+            }
+            [.., "magnitude", "mag", "value"] => {
+                if let Some(m) = self.magnitudes.last_mut() {
+                    m.mag = text.parse().ok();
+                }
+            }
+            [.., "magnitude", "type"] => {
+                if let Some(m) = self.magnitudes.last_mut() {
+                    m.mag_type = Some(text.into());
+                }
+            }
+            [.., "event", "st:title"] => p.title = Some(text),
+            [.., "event", "st:url"] => p.url = Some(text),
+            [.., "event", "st:detail"] => p.detail = Some(text),
+            [.., "event", "st:alert"] => p.alert = Some(text.into()),
+            [.., "event", "st:tsunami"] => p.tsunami = text.parse().unwrap_or_default(),
+            [.., "event", "st:sig"] => p.sig = text.parse().unwrap_or_default(),
+            [.., "event", "st:felt"] => p.felt = text.parse().ok(),
+            [.., "event", "st:cdi"] => p.cdi = text.parse().ok(),
+            [.., "event", "st:mmi"] => p.mmi = text.parse().ok(),
+            [.., "event", "st:ids"] => p.ids = Some(text),
+            [.., "event", "st:sources"] => p.sources = Some(text),
+            [.., "event", "st:types"] => p.types = Some(text),
+            _ => {}
+        }
+    }
+
+    /// File a completed description under its type.
+    fn take_description(&mut self) {
+        let (Some(kind), Some(text)) = &self.description else {
+            return;
+        };
+        match kind.as_str() {
+            "earthquake name" => self.earthquake_name = Some(text.clone()),
+            "region name" | "Flinn-Engdahl region" => self.region_name = Some(text.clone()),
+            _ => {}
+        }
+    }
+
+    /// Build the feature from the preferred origin and magnitude.
+    fn into_feature(mut self) -> Option<Feature> {
+        let origin_at = self
+            .preferred_origin
+            .as_ref()
+            .and_then(|id| self.origins.iter().position(|o| &o.public_id == id))
+            .unwrap_or(0);
+        if origin_at >= self.origins.len() {
+            debug!("skipping QuakeML event {} without origin", self.public_id);
+            return None;
+        }
+        let origin = self.origins.swap_remove(origin_at);
+        let magnitude = self
+            .preferred_magnitude
+            .as_ref()
+            .and_then(|id| self.magnitudes.iter().position(|m| &m.public_id == id))
+            .or_else(|| (!self.magnitudes.is_empty()).then_some(0))
+            .map(|at| self.magnitudes.swap_remove(at));
+
+        let Some(time) = origin.time.as_deref().and_then(|t| parse_time(t).ok()) else {
+            debug!(
+                "skipping QuakeML event {} with invalid time",
+                self.public_id
+            );
+            return None;
+        };
+        let time = time.timestamp_millis();
+        let updated = self
+            .updated
+            .as_deref()
+            .and_then(|t| parse_time(t).ok())
+            .map_or(time, |t| t.timestamp_millis());
+
+        let id = event_id(&self.public_id).or_else(|| {
+            Some(format!(
+                "{}{}",
+                self.source.as_deref()?,
+                self.code.as_deref()?
+            ))
+        })?;
+        let net = self
+            .source
+            .or_else(|| origin.agency.as_ref().map(|a| a.to_lowercase()))
+            .unwrap_or_default();
+
+        let (mag, mag_type) = magnitude.map_or((None, None), |m| (m.mag, m.mag_type));
+        Some(Feature {
+            type_: "Feature".into(),
+            geometry: Geometry {
+                type_: "Point".into(),
+                coordinates: vec![
+                    origin.longitude.unwrap_or(0.0),
+                    origin.latitude.unwrap_or(0.0),
+                    origin.depth_m.unwrap_or(0.0) / 1000.0,
+                ],
+            },
+            properties: Properties {
+                mag,
+                mag_type,
+                place: self.earthquake_name.or(self.region_name),
+                time,
+                updated,
+                status: status(origin.mode.as_deref(), origin.status.as_deref()).into(),
+                net,
+                code: self.code.unwrap_or_else(|| id.clone()),
+                nst: origin.nst,
+                dmin: origin.dmin,
+                rms: origin.rms,
+                gap: origin.gap,
+                ..self.properties
+            },
+            regions: Vec::new(),
+            home: None,
+            nearest_place: None,
+            zones: Vec::new(),
+            id,
+        })
+    }
+}
+
+impl OriginRecord {
+    /// Handle text content at `path` inside this origin.
+    fn text(&mut self, path: &[&str], text: String) {
+        match path {
+            [.., "origin", "time", "value"] => self.time = Some(text),
+            [.., "origin", "latitude", "value"] => self.latitude = text.parse().ok(),
+            [.., "origin", "longitude", "value"] => self.longitude = text.parse().ok(),
+            [.., "origin", "depth", "value"] => self.depth_m = text.parse().ok(),
+            [.., "origin", "quality", "usedStationCount"] => self.nst = text.parse().ok(),
+            [.., "origin", "quality", "standardError"] => self.rms = text.parse().ok(),
+            [.., "origin", "quality", "azimuthalGap"] => self.gap = text.parse().ok(),
+            [.., "origin", "quality", "minimumDistance"] => self.dmin = text.parse().ok(),
+            [.., "origin", "evaluationMode"] => self.mode = Some(text),
+            [.., "origin", "evaluationStatus"] => self.status = Some(text),
+            [.., "origin", "creationInfo", "agencyID"] => self.agency = Some(text),
+            _ => {}
+        }
+    }
+}
+
+/// Extract the event ID from a resource identifier.
+///
+/// Handles FDSN query URLs (`...?eventid=us7000n1a2&format=quakeml`) and
+/// path-style identifiers (`smi:www.emsc-csem.org/event/20241203_0000142`).
+fn event_id(public_id: &str) -> Option<String> {
+    if let Some((_, query)) = public_id.split_once('?') {
+        return query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("eventid="))
+            .map(String::from);
+    }
+    public_id
+        .rsplit('/')
+        .next()
+        .filter(|id| !id.is_empty() && !id.contains(':'))
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{SAMPLE_FEED, SAMPLE_QUAKEML};
+
+    fn sample_events() -> Vec<Feature> {
+        let feed: FeatureCollection = serde_json::from_str(SAMPLE_FEED).unwrap();
+        feed.features
+    }
+
+    /// Compare every field `Properties` carries.
+    fn assert_same(a: &Feature, b: &Feature) {
+        assert_eq!(a.id, b.id);
+        for (x, y) in a.geometry.coordinates.iter().zip(&b.geometry.coordinates) {
+            assert!((x - y).abs() < 1e-9, "{} coordinates differ", a.id);
+        }
+        assert_eq!(format!("{:?}", a.properties), format!("{:?}", b.properties));
+    }
+
+    #[test]
+    fn test_round_trip_preserves_properties() {
+        let events = sample_events();
+        let mut xml = Vec::new();
+        write_quakeml(&mut xml, &events).unwrap();
+
+        let parsed = parse_quakeml(&String::from_utf8(xml).unwrap()).unwrap();
+        assert_eq!(parsed.features.len(), events.len());
+        for (original, parsed) in events.iter().zip(&parsed.features) {
+            assert_same(original, parsed);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_escapes_text() {
+        let mut event = sample_events().remove(0);
+        event.properties.place = Some("Gulf of <Alaska> & \"Aleutians\"".into());
+        event.properties.mag = None;
+        event.properties.mag_type = None;
+        event.properties.status = EventStatus::Deleted.into();
+        let mut xml = Vec::new();
+        write_quakeml(&mut xml, std::slice::from_ref(&event)).unwrap();
+
+        let parsed = parse_quakeml(&String::from_utf8(xml).unwrap()).unwrap();
+        assert_same(&event, &parsed.features[0]);
+    }
+
+    #[test]
+    fn test_parse_fdsn_response() {
+        let feed = parse_quakeml(SAMPLE_QUAKEML).unwrap();
+        assert_eq!(feed.features.len(), 3);
+
+        let usgs = &feed.features[0];
+        assert_eq!(usgs.id, "us7000n1a2");
+        assert_eq!(usgs.properties.net, "us");
+        assert_eq!(usgs.properties.code, "7000n1a2");
+        assert_eq!(usgs.properties.mag, Some(7.0));
+        assert_eq!(
+            usgs.properties.mag_type.as_deref(),
+            Some(&MagnitudeType::Mww)
+        );
+        assert_eq!(
+            usgs.properties.place.as_deref(),
+            Some("45 km SW of Ferndale, California")
+        );
+        assert_eq!(usgs.properties.status, EventStatus::Reviewed);
+        assert_eq!(usgs.properties.nst, Some(112));
+        assert_eq!(usgs.properties.time, 1_733_215_287_123);
+        assert_eq!(usgs.properties.updated, 1_733_328_131_582);
+        assert!((usgs.depth_km() - 10.0).abs() < f64::EPSILON);
+        usgs.validate().unwrap();
+
+        // Preferred origin wins over a later one
+        let nc = &feed.features[1];
+        assert_eq!(nc.id, "nc75095651");
+        assert_eq!(nc.properties.status, EventStatus::Automatic);
+        assert!((nc.latitude() - 38.7925).abs() < f64::EPSILON);
+
+        let emsc = &feed.features[2];
+        assert_eq!(emsc.id, "20241203_0000142");
+        assert_eq!(emsc.properties.net, "the");
+        assert_eq!(emsc.properties.place.as_deref(), Some("CRETE, GREECE"));
+        assert_eq!(emsc.properties.updated, emsc.properties.time);
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_xml() {
+        let err = parse_quakeml("<q:quakeml><eventParameters></q:quakeml>").unwrap_err();
+        assert!(matches!(err, SeismotailError::InvalidResponse(_)));
+    }
+}
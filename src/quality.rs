@@ -0,0 +1,160 @@
+//! Location-quality grade for events.
+//!
+//! Follows the HYPO71 convention. A station-distribution grade (`QD`) is
+//! taken from the number of stations, the azimuthal gap and the distance to
+//! the nearest station relative to depth. A solution grade (`QS`) is taken
+//! from the travel-time RMS. The overall grade is their average, rounded
+//! towards the worse one. The summary feeds carry no hypocentre error
+//! estimates, so `QS` uses the RMS limits alone.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::models::Feature;
+
+/// Kilometres per degree of arc, for the feed's `dmin`.
+const KM_PER_DEGREE: f64 = 111.19;
+
+/// Location quality, best (`A`) to worst (`D`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum QualityGrade {
+    A,
+    B,
+    C,
+    D,
+}
+
+impl QualityGrade {
+    const ALL: [Self; 4] = [Self::A, Self::B, Self::C, Self::D];
+
+    /// Grade a located event, or `None` if it has neither a gap nor an RMS.
+    #[must_use]
+    pub fn of(event: &Feature) -> Option<Self> {
+        let p = &event.properties;
+        let distribution = p.gap.map(|gap| {
+            let dmin_km = p.dmin.map(|d| d * KM_PER_DEGREE);
+            distribution_grade(p.nst, gap, dmin_km, event.depth_km())
+        });
+        let solution = p.rms.map(solution_grade);
+        match (solution, distribution) {
+            (Some(qs), Some(qd)) => Some(Self::ALL[(qs as usize + qd as usize).div_ceil(2)]),
+            (qs, qd) => qs.or(qd),
+        }
+    }
+
+    /// Get the grade letter.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::A => "A",
+            Self::B => "B",
+            Self::C => "C",
+            Self::D => "D",
+        }
+    }
+}
+
+impl fmt::Display for QualityGrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for QualityGrade {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "A" => Ok(Self::A),
+            "B" => Ok(Self::B),
+            "C" => Ok(Self::C),
+            "D" => Ok(Self::D),
+            _ => Err(format!("unknown quality grade: {s} (expected: A, B, C, D)")),
+        }
+    }
+}
+
+/// HYPO71 `QD`. A missing station count or distance does not lower the grade.
+fn distribution_grade(
+    nst: Option<i32>,
+    gap: f64,
+    dmin_km: Option<f64>,
+    depth_km: f64,
+) -> QualityGrade {
+    if nst.is_some_and(|n| n < 6) {
+        return QualityGrade::D;
+    }
+    let near = |limit: f64| dmin_km.is_none_or(|d| d <= limit);
+    if gap <= 90.0 && near(depth_km.max(5.0)) {
+        QualityGrade::A
+    } else if gap <= 135.0 && near((2.0 * depth_km).max(10.0)) {
+        QualityGrade::B
+    } else if gap <= 180.0 && near(50.0) {
+        QualityGrade::C
+    } else {
+        QualityGrade::D
+    }
+}
+
+/// HYPO71 `QS` from the RMS travel-time residual (seconds).
+fn solution_grade(rms: f64) -> QualityGrade {
+    if rms < 0.15 {
+        QualityGrade::A
+    } else if rms < 0.30 {
+        QualityGrade::B
+    } else if rms < 0.50 {
+        QualityGrade::C
+    } else {
+        QualityGrade::D
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FeatureCollection;
+
+    #[test]
+    fn test_quality_grade() {
+        assert_eq!(
+            distribution_grade(Some(40), 60.0, Some(3.0), 8.0),
+            QualityGrade::A
+        );
+        assert_eq!(
+            distribution_grade(Some(40), 60.0, Some(12.0), 8.0),
+            QualityGrade::B
+        );
+        assert_eq!(
+            distribution_grade(Some(40), 170.0, None, 8.0),
+            QualityGrade::C
+        );
+        assert_eq!(
+            distribution_grade(Some(5), 30.0, Some(1.0), 8.0),
+            QualityGrade::D
+        );
+        assert_eq!(distribution_grade(None, 200.0, None, 8.0), QualityGrade::D);
+        assert_eq!(solution_grade(0.1), QualityGrade::A);
+        assert_eq!(solution_grade(0.5), QualityGrade::D);
+
+        let mut event = serde_json::from_str::<FeatureCollection>(crate::test_support::SAMPLE_FEED)
+            .unwrap()
+            .features
+            .remove(0);
+        let p = &mut event.properties;
+        (p.nst, p.gap, p.dmin, p.rms) = (Some(30), Some(45.0), Some(0.02), Some(0.1));
+        assert_eq!(QualityGrade::of(&event), Some(QualityGrade::A));
+        // A and B average to B; A and D to C
+        event.properties.rms = Some(0.2);
+        assert_eq!(QualityGrade::of(&event), Some(QualityGrade::B));
+        event.properties.rms = Some(0.9);
+        assert_eq!(QualityGrade::of(&event), Some(QualityGrade::C));
+        event.properties.gap = None;
+        assert_eq!(QualityGrade::of(&event), Some(QualityGrade::D));
+        event.properties.rms = None;
+        assert_eq!(QualityGrade::of(&event), None);
+
+        assert_eq!("b".parse::<QualityGrade>().unwrap(), QualityGrade::B);
+        assert!("E".parse::<QualityGrade>().is_err());
+    }
+}
@@ -0,0 +1,391 @@
+//! FDSN event query parameters.
+//!
+//! Maps historical search criteria onto the parameters of the USGS
+//! `fdsnws/event/1/query` web service.
+//! See <https://earthquake.usgs.gov/fdsnws/event/1/> for the full contract.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, SecondsFormat, TimeDelta, Utc};
+
+use crate::filters::{BBox, RadiusFilter};
+
+/// Maximum number of events the USGS service returns per request.
+pub const MAX_QUERY_LIMIT: usize = 20_000;
+
+/// Result ordering supported by the FDSN service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OrderBy {
+    /// Most recent first (default)
+    #[default]
+    Time,
+    /// Oldest first
+    TimeAsc,
+    /// Largest magnitude first
+    Magnitude,
+    /// Smallest magnitude first
+    MagnitudeAsc,
+}
+
+impl OrderBy {
+    /// Get the FDSN `orderby` parameter value.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Time => "time",
+            Self::TimeAsc => "time-asc",
+            Self::Magnitude => "magnitude",
+            Self::MagnitudeAsc => "magnitude-asc",
+        }
+    }
+}
+
+impl std::str::FromStr for OrderBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "time" => Ok(Self::Time),
+            "time-asc" => Ok(Self::TimeAsc),
+            "magnitude" => Ok(Self::Magnitude),
+            "magnitude-asc" => Ok(Self::MagnitudeAsc),
+            _ => Err(format!(
+                "unknown ordering: {s} (expected: time, time-asc, magnitude, magnitude-asc)"
+            )),
+        }
+    }
+}
+
+/// Search criteria for an FDSN event query.
+#[derive(Debug, Clone, Default)]
+pub struct EventQuery {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    pub min_magnitude: Option<f64>,
+    pub max_magnitude: Option<f64>,
+    pub min_depth: Option<f64>,
+    pub max_depth: Option<f64>,
+    pub bbox: Option<BBox>,
+    pub radius: Option<RadiusFilter>,
+    pub order_by: OrderBy,
+    pub limit: Option<usize>,
+}
+
+impl EventQuery {
+    /// Check the criteria for contradictions before sending them.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the first inconsistent criterion.
+    pub fn validate(&self) -> Result<(), String> {
+        if let (Some(start), Some(end)) = (self.start, self.end)
+            && start >= end
+        {
+            return Err(format!("start {start} must be before end {end}"));
+        }
+        if let (Some(min), Some(max)) = (self.min_magnitude, self.max_magnitude)
+            && min > max
+        {
+            return Err(format!(
+                "min magnitude {min} must be <= max magnitude {max}"
+            ));
+        }
+        if let (Some(min), Some(max)) = (self.min_depth, self.max_depth)
+            && min > max
+        {
+            return Err(format!("min depth {min} must be <= max depth {max}"));
+        }
+        if self.bbox.is_some() && self.radius.is_some() {
+            return Err("bbox and radius cannot be combined in one query".into());
+        }
+        if let Some(limit) = self.limit
+            && (limit == 0 || limit > MAX_QUERY_LIMIT)
+        {
+            return Err(format!(
+                "limit must be between 1 and {MAX_QUERY_LIMIT}, got {limit}"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Build the FDSN query-string parameters (excluding `format`).
+    #[must_use]
+    pub fn to_params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+
+        if let Some(start) = self.start {
+            params.push(("starttime", format_time(start)));
+        }
+        if let Some(end) = self.end {
+            params.push(("endtime", format_time(end)));
+        }
+        if let Some(v) = self.min_magnitude {
+            params.push(("minmagnitude", v.to_string()));
+        }
+        if let Some(v) = self.max_magnitude {
+            params.push(("maxmagnitude", v.to_string()));
+        }
+        if let Some(v) = self.min_depth {
+            params.push(("mindepth", v.to_string()));
+        }
+        if let Some(v) = self.max_depth {
+            params.push(("maxdepth", v.to_string()));
+        }
+        if let Some(bbox) = &self.bbox {
+            params.push(("minlatitude", bbox.min_lat.to_string()));
+            params.push(("maxlatitude", bbox.max_lat.to_string()));
+            // FDSN expresses a box across the antimeridian by unwrapping one
+            // edge past ±180; whichever edge keeps both within [-360, 360]
+            let (min_lon, max_lon) = if !bbox.crosses_antimeridian() {
+                (bbox.min_lon, bbox.max_lon)
+            } else if bbox.max_lon <= 0.0 {
+                (bbox.min_lon, bbox.max_lon + 360.0)
+            } else {
+                (bbox.min_lon - 360.0, bbox.max_lon)
+            };
+            params.push(("minlongitude", min_lon.to_string()));
+            params.push(("maxlongitude", max_lon.to_string()));
+        }
+        if let Some(radius) = &self.radius {
+            params.push(("latitude", radius.center_lat.to_string()));
+            params.push(("longitude", radius.center_lon.to_string()));
+            params.push(("maxradiuskm", radius.radius_km.to_string()));
+        }
+        params.push(("orderby", self.order_by.as_str().to_string()));
+        if let Some(limit) = self.limit {
+            params.push(("limit", limit.to_string()));
+        }
+
+        params
+    }
+}
+
+/// A time slice of a larger query, small enough for a single request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Window {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Matching events reported by the `count` endpoint
+    pub count: u64,
+}
+
+/// Split `[start, end]` into windows that each hold at most `cap` events.
+///
+/// Windows are bisected recursively using `count` until they fit. The
+/// halves do not overlap (the left half ends 1 ms before the right half
+/// starts), since FDSN time bounds are inclusive. A window that cannot be
+/// split further is kept even if it exceeds `cap`.
+///
+/// The result is in chronological order, ready to be reversed for
+/// newest-first output.
+///
+/// # Errors
+///
+/// Propagates the first error returned by `count`.
+pub fn plan_windows<E>(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    total: u64,
+    cap: u64,
+    count: &mut impl FnMut(DateTime<Utc>, DateTime<Utc>) -> Result<u64, E>,
+) -> Result<Vec<Window>, E> {
+    let mut windows = Vec::new();
+    // Explicit stack instead of recursion (bounded by the millisecond span)
+    let mut pending = vec![Window {
+        start,
+        end,
+        count: total,
+    }];
+
+    while let Some(window) = pending.pop() {
+        let span = window.end - window.start;
+        if window.count <= cap || span <= TimeDelta::milliseconds(1) {
+            windows.push(window);
+            continue;
+        }
+
+        let mid = window.start + TimeDelta::milliseconds(span.num_milliseconds() / 2);
+        let left_end = mid - TimeDelta::milliseconds(1);
+        let left = Window {
+            start: window.start,
+            end: left_end,
+            count: count(window.start, left_end)?,
+        };
+        let right = Window {
+            start: mid,
+            end: window.end,
+            count: count(mid, window.end)?,
+        };
+
+        // Push right first so the left half is processed (and emitted) first
+        pending.push(right);
+        pending.push(left);
+    }
+
+    Ok(windows)
+}
+
+/// Format a timestamp the way the FDSN service expects (ISO8601, UTC).
+fn format_time(t: DateTime<Utc>) -> String {
+    t.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Parse a user-supplied time: `YYYY-MM-DD`, `YYYY-MM-DDTHH:MM:SS` (UTC) or RFC 3339.
+///
+/// # Errors
+///
+/// Returns an error message if the string matches none of the formats.
+pub fn parse_time(s: &str) -> Result<DateTime<Utc>, String> {
+    let s = s.trim();
+
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Ok(t.with_timezone(&Utc));
+    }
+    if let Ok(t) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f") {
+        return Ok(t.and_utc());
+    }
+    if let Ok(d) = NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        && let Some(t) = d.and_hms_opt(0, 0, 0)
+    {
+        return Ok(t.and_utc());
+    }
+
+    Err(format!(
+        "invalid time '{s}' (expected YYYY-MM-DD, YYYY-MM-DDTHH:MM:SS or RFC 3339)"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time_formats() {
+        let date = parse_time("2024-01-15").unwrap();
+        assert_eq!(date.to_rfc3339(), "2024-01-15T00:00:00+00:00");
+
+        let naive = parse_time("2024-01-15T12:30:00").unwrap();
+        assert_eq!(naive.to_rfc3339(), "2024-01-15T12:30:00+00:00");
+
+        let offset = parse_time("2024-01-15T12:30:00+02:00").unwrap();
+        assert_eq!(offset.to_rfc3339(), "2024-01-15T10:30:00+00:00");
+
+        assert!(parse_time("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_order_by_round_trip() {
+        for order in [
+            OrderBy::Time,
+            OrderBy::TimeAsc,
+            OrderBy::Magnitude,
+            OrderBy::MagnitudeAsc,
+        ] {
+            assert_eq!(order.as_str().parse::<OrderBy>().unwrap(), order);
+        }
+    }
+
+    /// Look up one FDSN parameter by name.
+    fn param<'a>(params: &'a [(&str, String)], key: &str) -> Option<&'a str> {
+        params
+            .iter()
+            .find(|(n, _)| *n == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    #[test]
+    fn test_params_mapping() {
+        let query = EventQuery {
+            start: Some(parse_time("2024-01-01").unwrap()),
+            min_magnitude: Some(2.5),
+            bbox: Some("32.5,-124.5,42.0,-114.0".parse().unwrap()),
+            limit: Some(10),
+            ..Default::default()
+        };
+        let params = query.to_params();
+        assert_eq!(
+            param(&params, "starttime"),
+            Some("2024-01-01T00:00:00.000Z")
+        );
+        assert_eq!(param(&params, "minmagnitude"), Some("2.5"));
+        assert_eq!(param(&params, "minlongitude"), Some("-124.5"));
+        assert_eq!(param(&params, "orderby"), Some("time"));
+        assert_eq!(param(&params, "limit"), Some("10"));
+        assert_eq!(param(&params, "endtime"), None);
+
+        let query = EventQuery {
+            bbox: Some("-25,170,-10,-170".parse().unwrap()),
+            ..Default::default()
+        };
+        let params = query.to_params();
+        assert_eq!(param(&params, "minlongitude"), Some("170"));
+        assert_eq!(param(&params, "maxlongitude"), Some("190"));
+
+        // Both edges east of Greenwich: unwrap the western edge instead
+        let query = EventQuery {
+            bbox: Some("0,175,10,170".parse().unwrap()),
+            ..Default::default()
+        };
+        let params = query.to_params();
+        assert_eq!(param(&params, "minlongitude"), Some("-185"));
+        assert_eq!(param(&params, "maxlongitude"), Some("170"));
+    }
+
+    #[test]
+    fn test_plan_windows_splits_until_under_cap() {
+        // Synthetic catalog: one event per day over 2024
+        let start = parse_time("2024-01-01").unwrap();
+        let end = parse_time("2024-12-31T23:59:59").unwrap();
+        let mut calls = 0;
+        let mut count = |s: DateTime<Utc>, e: DateTime<Utc>| -> Result<u64, ()> {
+            calls += 1;
+            Ok(u64::try_from((e - s).num_days() + 1).unwrap())
+        };
+
+        let windows = plan_windows(start, end, 366, 100, &mut count).unwrap();
+
+        assert!(windows.len() >= 4);
+        assert!(windows.iter().all(|w| w.count <= 100));
+        assert_eq!(windows.first().unwrap().start, start);
+        assert_eq!(windows.last().unwrap().end, end);
+        for pair in windows.windows(2) {
+            // Contiguous, chronological and non-overlapping
+            assert_eq!(pair[1].start - pair[0].end, TimeDelta::milliseconds(1));
+        }
+        assert!(calls > 0);
+    }
+
+    #[test]
+    fn test_plan_windows_single_when_under_cap() {
+        let start = parse_time("2024-01-01").unwrap();
+        let end = parse_time("2024-02-01").unwrap();
+        let mut count = |_: DateTime<Utc>, _: DateTime<Utc>| -> Result<u64, ()> {
+            panic!("count should not be called")
+        };
+
+        let windows = plan_windows(start, end, 50, 100, &mut count).unwrap();
+        assert_eq!(
+            windows,
+            vec![Window {
+                start,
+                end,
+                count: 50
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_inverted_ranges() {
+        let query = EventQuery {
+            min_magnitude: Some(5.0),
+            max_magnitude: Some(4.0),
+            ..Default::default()
+        };
+        assert!(query.validate().is_err());
+
+        let query = EventQuery {
+            start: Some(parse_time("2024-02-01").unwrap()),
+            end: Some(parse_time("2024-01-01").unwrap()),
+            ..Default::default()
+        };
+        assert!(query.validate().is_err());
+    }
+}
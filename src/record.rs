@@ -0,0 +1,527 @@
+//! Recording and replay of live feed polls.
+//!
+//! A [`RecordingSource`] wraps any [`EventSource`] and persists every poll
+//! to a directory: a `polls.ndjson` manifest with the fetch time and outcome
+//! of each poll, plus the response bodies of each modified feed exactly as
+//! received. A [`ReplaySource`] reads such a directory back, parses the
+//! bodies again and hands the polls to the same live loop on the recorded
+//! timeline, optionally accelerated.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+use crate::client::FeedFetch;
+use crate::errors::SeismotailError;
+use crate::models::{BodyFormat, FeatureCollection, RawBody};
+use crate::source::{EventSource, SourceFuture};
+use crate::{emsc, geonet, quakeml};
+
+/// Manifest file name inside a recording directory.
+const MANIFEST: &str = "polls.ndjson";
+
+/// One line of the manifest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PollRecord {
+    seq: u64,
+    fetched_at: DateTime<Utc>,
+    #[serde(flatten)]
+    outcome: Outcome,
+}
+
+/// What a recorded poll returned.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+enum Outcome {
+    /// Feed changed; response bodies stored in `files`
+    Modified {
+        files: Vec<RecordedBody>,
+        count: usize,
+    },
+    /// Feed unchanged since the previous poll
+    NotModified,
+    /// Fetch failed
+    Error { error: String },
+}
+
+/// A response body file of a recorded poll.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedBody {
+    file: String,
+    #[serde(flatten)]
+    format: BodyFormat,
+}
+
+/// Parse a replay speed such as `10x`, `10` or `0.5x`.
+///
+/// # Errors
+///
+/// Returns an error message unless the speed is a positive number.
+pub fn parse_speed(s: &str) -> Result<f64, String> {
+    let value = s.trim().trim_end_matches(['x', 'X']);
+    match value.parse::<f64>() {
+        Ok(speed) if speed > 0.0 && speed.is_finite() => Ok(speed),
+        _ => Err(format!("invalid speed: {s} (expected e.g. 1x, 10x, 0.5x)")),
+    }
+}
+
+/// Persists every poll of the wrapped source.
+///
+/// Only polls (`fetch_if_modified`) are recorded; one-shot fetches pass
+/// through untouched.
+pub struct RecordingSource {
+    inner: Arc<dyn EventSource>,
+    dir: PathBuf,
+    seq: AtomicU64,
+    /// Serializes manifest appends
+    manifest: Mutex<()>,
+}
+
+impl RecordingSource {
+    /// Start recording `inner` into `dir`, creating it if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created or already holds
+    /// a recording.
+    pub fn create(inner: Arc<dyn EventSource>, dir: &Path) -> Result<Self, SeismotailError> {
+        std::fs::create_dir_all(dir).map_err(|e| record_error(dir, &e))?;
+        if dir.join(MANIFEST).exists() {
+            return Err(SeismotailError::Input(format!(
+                "{} already contains a recording",
+                dir.display()
+            )));
+        }
+
+        Ok(Self {
+            inner,
+            dir: dir.to_path_buf(),
+            seq: AtomicU64::new(0),
+            manifest: Mutex::new(()),
+        })
+    }
+
+    /// Persist one poll result.
+    fn record(&self, result: &Result<FeedFetch, SeismotailError>) -> Result<(), SeismotailError> {
+        use std::io::Write;
+
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed) + 1;
+        let outcome = match result {
+            Ok(FeedFetch::Modified(feed)) => Outcome::Modified {
+                files: self.write_bodies(seq, feed)?,
+                count: feed.features.len(),
+            },
+            Ok(FeedFetch::NotModified) => Outcome::NotModified,
+            Err(e) => Outcome::Error {
+                error: e.to_string(),
+            },
+        };
+        let line = serde_json::to_string(&PollRecord {
+            seq,
+            fetched_at: Utc::now(),
+            outcome,
+        })?;
+
+        let path = self.dir.join(MANIFEST);
+        let _guard = self.manifest.lock();
+        let mut manifest = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| record_error(&path, &e))?;
+        writeln!(manifest, "{line}").map_err(|e| record_error(&path, &e))
+    }
+
+    /// Store the response bodies of a modified feed.
+    ///
+    /// Sources that keep no body (none of the built-in ones) get the parsed
+    /// collection written as GeoJSON instead.
+    fn write_bodies(
+        &self,
+        seq: u64,
+        feed: &FeatureCollection,
+    ) -> Result<Vec<RecordedBody>, SeismotailError> {
+        let serialized;
+        let bodies = if feed.bodies.is_empty() {
+            serialized = [RawBody {
+                format: BodyFormat::GeoJson,
+                text: serde_json::to_string(feed)?,
+            }];
+            &serialized[..]
+        } else {
+            &feed.bodies[..]
+        };
+
+        let mut files = Vec::with_capacity(bodies.len());
+        for (n, body) in bodies.iter().enumerate() {
+            let extension = extension(body.format);
+            let file = if bodies.len() == 1 {
+                format!("{seq:06}.{extension}")
+            } else {
+                format!("{seq:06}-{}.{extension}", n + 1)
+            };
+            let path = self.dir.join(&file);
+            std::fs::write(&path, &body.text).map_err(|e| record_error(&path, &e))?;
+            files.push(RecordedBody {
+                file,
+                format: body.format,
+            });
+        }
+        Ok(files)
+    }
+}
+
+impl EventSource for RecordingSource {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn fetch(&self) -> SourceFuture<'_, FeatureCollection> {
+        self.inner.fetch()
+    }
+
+    fn fetch_if_modified(&self) -> SourceFuture<'_, FeedFetch> {
+        Box::pin(async move {
+            let result = self.inner.fetch_if_modified().await;
+            // A full disk should not take down live monitoring
+            if let Err(e) = self.record(&result) {
+                warn!("failed to record poll: {}", e);
+            }
+            result
+        })
+    }
+
+    fn exhausted(&self) -> bool {
+        self.inner.exhausted()
+    }
+}
+
+/// Plays back a recording made by [`RecordingSource`].
+///
+/// Each poll waits until its recorded offset from the first poll, divided
+/// by the speed, has elapsed since playback started.
+pub struct ReplaySource {
+    dir: PathBuf,
+    polls: Vec<PollRecord>,
+    speed: f64,
+    next: AtomicUsize,
+    started: Mutex<Option<Instant>>,
+}
+
+impl ReplaySource {
+    /// Load the manifest of a recording directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the manifest is missing or malformed.
+    pub fn open(dir: &Path, speed: f64) -> Result<Self, SeismotailError> {
+        let path = dir.join(MANIFEST);
+        let text = std::fs::read_to_string(&path).map_err(|e| record_error(&path, &e))?;
+        let polls = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<PollRecord>, _>>()?;
+        debug!(
+            "loaded {} recorded polls from {}",
+            polls.len(),
+            dir.display()
+        );
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            polls,
+            speed,
+            next: AtomicUsize::new(0),
+            started: Mutex::new(None),
+        })
+    }
+
+    /// Number of recorded polls.
+    #[must_use]
+    pub fn poll_count(&self) -> usize {
+        self.polls.len()
+    }
+
+    /// Time from playback start until `poll` is due.
+    fn offset(&self, poll: &PollRecord) -> Duration {
+        let Some(first) = self.polls.first() else {
+            return Duration::ZERO;
+        };
+        let elapsed = (poll.fetched_at - first.fetched_at)
+            .to_std()
+            .unwrap_or_default();
+        elapsed.div_f64(self.speed)
+    }
+
+    /// Load and parse the recorded bodies of a poll.
+    async fn snapshot(&self, poll: &PollRecord) -> Result<FeatureCollection, SeismotailError> {
+        let Outcome::Modified { files, .. } = &poll.outcome else {
+            return Ok(FeatureCollection::empty());
+        };
+
+        let mut collections = Vec::with_capacity(files.len());
+        for body in files {
+            let path = self.dir.join(&body.file);
+            let text = tokio::fs::read_to_string(&path)
+                .await
+                .map_err(|e| record_error(&path, &e))?;
+            let feed = match body.format {
+                BodyFormat::GeoJson => FeatureCollection::from_json(&text),
+                BodyFormat::QuakeMl => quakeml::parse_quakeml(&text),
+                BodyFormat::Emsc => emsc::parse_body(&text),
+                BodyFormat::GeoNet { feed } => geonet::parse_body(&text, feed, poll.fetched_at),
+            }
+            .map_err(|e| SeismotailError::Input(format!("{}: {e}", path.display())))?;
+            collections.push(feed.with_body(body.format, text));
+        }
+
+        if collections.len() == 1 {
+            return Ok(collections.remove(0));
+        }
+        Ok(FeatureCollection::merge(collections))
+    }
+
+    /// Wait for and return the next recorded poll.
+    async fn next_poll(&self) -> Result<FeedFetch, SeismotailError> {
+        let at = self.next.fetch_add(1, Ordering::Relaxed);
+        let Some(poll) = self.polls.get(at) else {
+            return Ok(FeedFetch::NotModified);
+        };
+
+        let started = {
+            let Ok(mut started) = self.started.lock() else {
+                return Ok(FeedFetch::NotModified);
+            };
+            *started.get_or_insert_with(Instant::now)
+        };
+        tokio::time::sleep_until(started + self.offset(poll)).await;
+
+        match &poll.outcome {
+            Outcome::Modified { .. } => Ok(FeedFetch::Modified(self.snapshot(poll).await?)),
+            Outcome::NotModified => Ok(FeedFetch::NotModified),
+            Outcome::Error { error } => Err(SeismotailError::Recorded(error.clone())),
+        }
+    }
+}
+
+impl EventSource for ReplaySource {
+    fn name(&self) -> &'static str {
+        "replay"
+    }
+
+    fn fetch(&self) -> SourceFuture<'_, FeatureCollection> {
+        Box::pin(async move {
+            // The last snapshot is the most complete view of the feed
+            let last = self
+                .polls
+                .iter()
+                .rev()
+                .find(|poll| matches!(poll.outcome, Outcome::Modified { .. }));
+            match last {
+                Some(poll) => self.snapshot(poll).await,
+                None => Ok(FeatureCollection::empty()),
+            }
+        })
+    }
+
+    fn fetch_if_modified(&self) -> SourceFuture<'_, FeedFetch> {
+        Box::pin(self.next_poll())
+    }
+
+    fn exhausted(&self) -> bool {
+        self.next.load(Ordering::Relaxed) >= self.polls.len()
+    }
+}
+
+/// File extension for a body of `format`.
+fn extension(format: BodyFormat) -> &'static str {
+    match format {
+        BodyFormat::GeoJson => "geojson",
+        BodyFormat::QuakeMl => "xml",
+        BodyFormat::Emsc | BodyFormat::GeoNet { .. } => "json",
+    }
+}
+
+/// Wrap an I/O error with the path it concerns.
+fn record_error(path: &Path, e: &std::io::Error) -> SeismotailError {
+    SeismotailError::Input(format!("{}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Feature;
+    use crate::test_support::{SAMPLE_EMSC, SAMPLE_FEED, SAMPLE_QUAKEML};
+
+    /// Scripted source returning queued poll results.
+    struct Scripted(Mutex<Vec<Result<FeedFetch, SeismotailError>>>);
+
+    impl EventSource for Scripted {
+        fn name(&self) -> &'static str {
+            "scripted"
+        }
+
+        fn fetch(&self) -> SourceFuture<'_, FeatureCollection> {
+            Box::pin(async { Ok(FeatureCollection::empty()) })
+        }
+
+        fn fetch_if_modified(&self) -> SourceFuture<'_, FeedFetch> {
+            Box::pin(async move { self.0.lock().unwrap().remove(0) })
+        }
+    }
+
+    fn sample_feed() -> FeatureCollection {
+        serde_json::from_str(SAMPLE_FEED).unwrap()
+    }
+
+    fn ids(features: &[Feature]) -> Vec<&str> {
+        features.iter().map(|f| f.id.as_str()).collect()
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("seismotail-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_parse_speed() {
+        assert_eq!(parse_speed("10x").ok(), Some(10.0));
+        assert_eq!(parse_speed("2").ok(), Some(2.0));
+        assert_eq!(parse_speed("0.5X").ok(), Some(0.5));
+        assert!(parse_speed("0x").is_err());
+        assert!(parse_speed("fast").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay_polls() {
+        let dir = scratch_dir("record");
+        let mut revised = sample_feed();
+        revised.features[0].properties.mag = Some(6.4);
+        revised.features[0].properties.updated += 60_000;
+
+        let scripted = Scripted(Mutex::new(vec![
+            Ok(FeedFetch::Modified(sample_feed())),
+            Ok(FeedFetch::NotModified),
+            Err(SeismotailError::Timeout("deadline".into())),
+            Ok(FeedFetch::Modified(revised)),
+        ]));
+        let recorder = RecordingSource::create(Arc::new(scripted), &dir).unwrap();
+        for _ in 0..4 {
+            let _ = recorder.fetch_if_modified().await;
+        }
+        assert!(RecordingSource::create(Arc::new(Scripted(Mutex::default())), &dir).is_err());
+
+        let replay = ReplaySource::open(&dir, 1000.0).unwrap();
+        assert_eq!(replay.poll_count(), 4);
+
+        let FeedFetch::Modified(first) = replay.fetch_if_modified().await.unwrap() else {
+            panic!("expected the first snapshot");
+        };
+        assert_eq!(ids(&first.features), ids(&sample_feed().features));
+        assert!(matches!(
+            replay.fetch_if_modified().await.unwrap(),
+            FeedFetch::NotModified
+        ));
+
+        let err = replay.fetch_if_modified().await.unwrap_err();
+        assert!(err.is_retryable());
+        assert!(err.to_string().contains("deadline"));
+
+        assert!(!replay.exhausted());
+        let FeedFetch::Modified(last) = replay.fetch_if_modified().await.unwrap() else {
+            panic!("expected the revised snapshot");
+        };
+        assert_eq!(last.features[0].properties.mag, Some(6.4));
+        assert!(replay.exhausted());
+    }
+
+    #[tokio::test]
+    async fn test_record_keeps_response_bodies() {
+        let dir = scratch_dir("bodies");
+        let mut doc: serde_json::Value = serde_json::from_str(SAMPLE_FEED).unwrap();
+        doc["bbox"] = serde_json::json!([-180.0, -90.0, 180.0, 90.0]);
+        doc["features"]
+            .as_array_mut()
+            .unwrap()
+            .push(serde_json::json!({"type": "Feature", "id": "bad"}));
+        let body = doc.to_string();
+        let feed = FeatureCollection::from_json(&body)
+            .unwrap()
+            .with_body(BodyFormat::GeoJson, body.clone());
+        assert_eq!(feed.report.rejected.len(), 1);
+
+        let scripted = Scripted(Mutex::new(vec![Ok(FeedFetch::Modified(feed))]));
+        let recorder = RecordingSource::create(Arc::new(scripted), &dir).unwrap();
+        recorder.fetch_if_modified().await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.join("000001.geojson")).unwrap(),
+            body
+        );
+
+        let replay = ReplaySource::open(&dir, 1000.0).unwrap();
+        let FeedFetch::Modified(replayed) = replay.fetch_if_modified().await.unwrap() else {
+            panic!("expected the recorded body");
+        };
+        assert_eq!(replayed.features.len(), 8);
+        assert_eq!(replayed.report.rejected[0].id.as_deref(), Some("bad"));
+    }
+
+    #[tokio::test]
+    async fn test_replay_parses_each_body_format() {
+        let dir = scratch_dir("formats");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("000001-1.json"), SAMPLE_EMSC).unwrap();
+        std::fs::write(dir.join("000001-2.xml"), SAMPLE_QUAKEML).unwrap();
+        std::fs::write(
+            dir.join(MANIFEST),
+            r#"{"seq":1,"fetched_at":"2024-12-03T08:00:00Z","result":"modified","files":[{"file":"000001-1.json","format":"emsc"},{"file":"000001-2.xml","format":"quakeml"}],"count":5}"#,
+        )
+        .unwrap();
+
+        let replay = ReplaySource::open(&dir, 1000.0).unwrap();
+        let feed = replay.fetch().await.unwrap();
+        // Both samples carry 20241203_0000142; bodies merge like snapshots
+        let mut expected = ids(&emsc::parse_body(SAMPLE_EMSC).unwrap().features)
+            .into_iter()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        for feature in quakeml::parse_quakeml(SAMPLE_QUAKEML).unwrap().features {
+            if !expected.contains(&feature.id) {
+                expected.push(feature.id);
+            }
+        }
+        assert_eq!(ids(&feed.features), expected);
+        assert_eq!(feed.bodies.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_replay_follows_recorded_timeline() {
+        let dir = scratch_dir("timeline");
+        std::fs::create_dir_all(&dir).unwrap();
+        let manifest = [
+            r#"{"seq":1,"fetched_at":"2024-12-03T08:00:00Z","result":"not_modified"}"#,
+            r#"{"seq":2,"fetched_at":"2024-12-03T08:01:00Z","result":"not_modified"}"#,
+            r#"{"seq":3,"fetched_at":"2024-12-03T08:11:00Z","result":"not_modified"}"#,
+        ];
+        std::fs::write(dir.join(MANIFEST), manifest.join("\n")).unwrap();
+
+        // 6000x: one recorded minute becomes 10ms
+        let replay = ReplaySource::open(&dir, 6000.0).unwrap();
+        let start = Instant::now();
+        replay.fetch_if_modified().await.unwrap();
+        assert!(start.elapsed() < Duration::from_millis(10));
+        replay.fetch_if_modified().await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(10));
+        replay.fetch_if_modified().await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(110));
+    }
+}
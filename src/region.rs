@@ -0,0 +1,477 @@
+//! Polygon regions for `--region`.
+//!
+//! A [`Region`] is one or more polygons, with holes, read from a GeoJSON
+//! (`Polygon`, `MultiPolygon`, `Feature` or `FeatureCollection`) or WKT
+//! (`POLYGON`, `MULTIPOLYGON`) file. Coordinates are longitude/latitude in
+//! degrees, as in both formats.
+
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::errors::SeismotailError;
+
+/// Named area of interest made of polygons.
+#[derive(Debug, Clone)]
+pub struct Region {
+    /// Attached to matching events in the output, if set
+    pub name: Option<String>,
+    polygons: Vec<Polygon>,
+}
+
+/// A polygon: an exterior ring minus any holes.
+#[derive(Debug, Clone)]
+struct Polygon {
+    exterior: Ring,
+    holes: Vec<Ring>,
+}
+
+/// A closed ring of `[lon, lat]` vertices (the closing vertex is implied).
+#[derive(Debug, Clone)]
+struct Ring {
+    points: Vec<[f64; 2]>,
+    /// `[min_lon, min_lat, max_lon, max_lat]`, to skip distant points cheaply
+    bounds: [f64; 4],
+}
+
+/// Where a point lies relative to a ring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+/// Tolerance (degrees) for a point to count as lying on an edge.
+const EDGE_EPSILON: f64 = 1e-9;
+
+impl Region {
+    /// Load a region from `[NAME=]FILE`.
+    ///
+    /// Without a name, the `name` property of a single GeoJSON feature is used.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or holds no valid polygon.
+    pub fn load(spec: &str) -> Result<Self, SeismotailError> {
+        let (name, path) = match spec.split_once('=') {
+            Some((name, path)) if !name.is_empty() && !name.contains(['/', '\\']) => {
+                (Some(name.to_string()), path)
+            }
+            _ => (None, spec),
+        };
+        let path = Path::new(path);
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| SeismotailError::Input(format!("{}: {e}", path.display())))?;
+
+        let mut region = Self::parse(&text)
+            .map_err(|e| SeismotailError::Input(format!("{}: {e}", path.display())))?;
+        if name.is_some() {
+            region.name = name;
+        }
+        Ok(region)
+    }
+
+    /// Parse GeoJSON, or WKT if the text is not a JSON object.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the first problem found.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        if text.starts_with('{') {
+            let value: Value =
+                serde_json::from_str(text).map_err(|e| format!("invalid GeoJSON: {e}"))?;
+            let name = value
+                .pointer("/properties/name")
+                .and_then(Value::as_str)
+                .map(String::from);
+            let mut polygons = Vec::new();
+            geojson_polygons(&value, &mut polygons)?;
+            Self::new(name, polygons)
+        } else {
+            Self::new(None, wkt_polygons(text)?)
+        }
+    }
+
+    fn new(name: Option<String>, polygons: Vec<Polygon>) -> Result<Self, String> {
+        if polygons.is_empty() {
+            return Err("no Polygon or MultiPolygon geometry found".into());
+        }
+        Ok(Self { name, polygons })
+    }
+
+    /// Whether a point lies in the region; points on an edge count as inside.
+    #[must_use]
+    pub fn contains(&self, lat: f64, lon: f64) -> bool {
+        self.polygons.iter().any(|p| p.contains(lon, lat))
+    }
+}
+
+impl Polygon {
+    fn contains(&self, x: f64, y: f64) -> bool {
+        // A hole's edge is still part of the polygon
+        self.exterior.side(x, y) != Side::Outside
+            && self
+                .holes
+                .iter()
+                .all(|hole| hole.side(x, y) != Side::Inside)
+    }
+}
+
+impl Ring {
+    fn new(points: Vec<[f64; 2]>) -> Result<Self, String> {
+        let mut points = points;
+        if points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        if points.len() < 3 {
+            return Err(format!(
+                "ring needs at least 3 distinct vertices, got {}",
+                points.len()
+            ));
+        }
+        for [lon, lat] in &points {
+            if !(-180.0..=180.0).contains(lon) || !(-90.0..=90.0).contains(lat) {
+                return Err(format!("coordinate ({lon}, {lat}) out of range"));
+            }
+        }
+
+        let mut bounds = [f64::MAX, f64::MAX, f64::MIN, f64::MIN];
+        for [x, y] in &points {
+            bounds = [
+                bounds[0].min(*x),
+                bounds[1].min(*y),
+                bounds[2].max(*x),
+                bounds[3].max(*y),
+            ];
+        }
+        Ok(Self { points, bounds })
+    }
+
+    /// Locate a point by crossing number, checking edges first.
+    fn side(&self, x: f64, y: f64) -> Side {
+        let [min_x, min_y, max_x, max_y] = self.bounds;
+        if x < min_x - EDGE_EPSILON
+            || x > max_x + EDGE_EPSILON
+            || y < min_y - EDGE_EPSILON
+            || y > max_y + EDGE_EPSILON
+        {
+            return Side::Outside;
+        }
+
+        let mut inside = false;
+        let n = self.points.len();
+        for i in 0..n {
+            let [ax, ay] = self.points[i];
+            let [bx, by] = self.points[(i + 1) % n];
+
+            if on_segment(x, y, ax, ay, bx, by) {
+                return Side::Boundary;
+            }
+            // Half-open rule: an edge counts when it spans y with one end
+            // strictly above, so rays through vertices are counted once
+            if (ay > y) != (by > y) {
+                let cross_x = ax + (y - ay) * (bx - ax) / (by - ay);
+                if x < cross_x {
+                    inside = !inside;
+                }
+            }
+        }
+
+        if inside { Side::Inside } else { Side::Outside }
+    }
+}
+
+/// Whether `(x, y)` lies on the segment from `a` to `b`.
+fn on_segment(x: f64, y: f64, ax: f64, ay: f64, bx: f64, by: f64) -> bool {
+    let cross = (bx - ax) * (y - ay) - (by - ay) * (x - ax);
+    let length = (bx - ax).hypot(by - ay);
+    cross.abs() <= EDGE_EPSILON * length.max(1.0)
+        && x >= ax.min(bx) - EDGE_EPSILON
+        && x <= ax.max(bx) + EDGE_EPSILON
+        && y >= ay.min(by) - EDGE_EPSILON
+        && y <= ay.max(by) + EDGE_EPSILON
+}
+
+/// Collect the polygons of a GeoJSON object.
+fn geojson_polygons(value: &Value, out: &mut Vec<Polygon>) -> Result<(), String> {
+    let kind = value
+        .get("type")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let coordinates = || {
+        value
+            .get("coordinates")
+            .ok_or(format!("{kind} without coordinates"))
+    };
+
+    match kind {
+        "Polygon" => out.push(geojson_polygon(coordinates()?)?),
+        "MultiPolygon" => {
+            for polygon in as_array(coordinates()?, "MultiPolygon")? {
+                out.push(geojson_polygon(polygon)?);
+            }
+        }
+        "Feature" => {
+            if let Some(geometry) = value.get("geometry").filter(|g| !g.is_null()) {
+                geojson_polygons(geometry, out)?;
+            }
+        }
+        "FeatureCollection" => {
+            for feature in value
+                .get("features")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                geojson_polygons(feature, out)?;
+            }
+        }
+        "GeometryCollection" => {
+            for geometry in value
+                .get("geometries")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                geojson_polygons(geometry, out)?;
+            }
+        }
+        // Points and lines have no area; skip them
+        "Point" | "MultiPoint" | "LineString" | "MultiLineString" => {}
+        other => return Err(format!("unsupported GeoJSON type '{other}'")),
+    }
+    Ok(())
+}
+
+/// Build a polygon from GeoJSON `[[ [lon, lat], ... ], ...]` rings.
+fn geojson_polygon(value: &Value) -> Result<Polygon, String> {
+    let mut rings = Vec::new();
+    for ring in as_array(value, "Polygon")? {
+        let mut points = Vec::new();
+        for position in as_array(ring, "ring")? {
+            match position.as_array().map(Vec::as_slice) {
+                Some([lon, lat, ..]) => match (lon.as_f64(), lat.as_f64()) {
+                    (Some(lon), Some(lat)) => points.push([lon, lat]),
+                    _ => return Err(format!("invalid position {position}")),
+                },
+                _ => return Err(format!("invalid position {position}")),
+            }
+        }
+        rings.push(Ring::new(points)?);
+    }
+    polygon_from_rings(rings)
+}
+
+fn as_array<'a>(value: &'a Value, what: &str) -> Result<&'a Vec<Value>, String> {
+    value
+        .as_array()
+        .ok_or_else(|| format!("{what} coordinates must be an array"))
+}
+
+fn polygon_from_rings(rings: Vec<Ring>) -> Result<Polygon, String> {
+    let mut rings = rings.into_iter();
+    let exterior = rings.next().ok_or("polygon without rings")?;
+    Ok(Polygon {
+        exterior,
+        holes: rings.collect(),
+    })
+}
+
+/// Parenthesized WKT coordinate structure.
+enum Nested {
+    List(Vec<Nested>),
+    Point([f64; 2]),
+}
+
+/// Parse a WKT `POLYGON` or `MULTIPOLYGON`.
+fn wkt_polygons(text: &str) -> Result<Vec<Polygon>, String> {
+    let open = text
+        .find('(')
+        .ok_or("expected WKT POLYGON or MULTIPOLYGON")?;
+    let keyword = text[..open].trim().to_uppercase();
+    let (nested, rest) = wkt_nested(&text[open..])?;
+    if !rest.trim().is_empty() {
+        return Err(format!(
+            "unexpected text after WKT geometry: '{}'",
+            rest.trim()
+        ));
+    }
+
+    match keyword.as_str() {
+        "POLYGON" => Ok(vec![wkt_polygon(&nested)?]),
+        "MULTIPOLYGON" => match nested {
+            Nested::List(polygons) => polygons.iter().map(wkt_polygon).collect(),
+            Nested::Point(_) => Err("MULTIPOLYGON needs a list of polygons".into()),
+        },
+        other => Err(format!(
+            "unsupported WKT geometry '{other}' (expected POLYGON or MULTIPOLYGON)"
+        )),
+    }
+}
+
+fn wkt_polygon(nested: &Nested) -> Result<Polygon, String> {
+    let Nested::List(rings) = nested else {
+        return Err("POLYGON needs a list of rings".into());
+    };
+    let rings = rings
+        .iter()
+        .map(|ring| match ring {
+            Nested::List(points) => points
+                .iter()
+                .map(|p| match p {
+                    Nested::Point(p) => Ok(*p),
+                    Nested::List(_) => Err("ring vertices must be 'lon lat' pairs".to_string()),
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .and_then(Ring::new),
+            Nested::Point(_) => Err("polygon rings must be parenthesized".into()),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    polygon_from_rings(rings)
+}
+
+/// Parse `( item, item, ... )` where an item is a nested list or `x y`.
+///
+/// Returns the structure and the text after the closing parenthesis.
+fn wkt_nested(text: &str) -> Result<(Nested, &str), String> {
+    let mut rest = text.trim_start().strip_prefix('(').ok_or("expected '('")?;
+    let mut items = Vec::new();
+
+    loop {
+        rest = rest.trim_start();
+        if rest.starts_with('(') {
+            let (item, after) = wkt_nested(rest)?;
+            items.push(item);
+            rest = after;
+        } else {
+            let end = rest
+                .find([',', ')'])
+                .ok_or("unbalanced parentheses in WKT")?;
+            let numbers: Vec<&str> = rest[..end].split_whitespace().collect();
+            match numbers.as_slice() {
+                [x, y, ..] => {
+                    let parse = |s: &str| {
+                        s.parse::<f64>()
+                            .map_err(|_| format!("invalid number '{s}'"))
+                    };
+                    items.push(Nested::Point([parse(x)?, parse(y)?]));
+                }
+                _ => {
+                    return Err(format!(
+                        "expected 'lon lat', found '{}'",
+                        rest[..end].trim()
+                    ));
+                }
+            }
+            rest = &rest[end..];
+        }
+
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix(',') {
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix(')') {
+            return Ok((Nested::List(items), after));
+        } else {
+            return Err("unbalanced parentheses in WKT".into());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 10x10 square with a 2x2 hole in the middle.
+    const SQUARE_WITH_HOLE: &str =
+        "POLYGON ((0 0, 10 0, 10 10, 0 10, 0 0), (4 4, 6 4, 6 6, 4 6, 4 4))";
+
+    #[test]
+    fn test_point_in_polygon_with_hole() {
+        let region = Region::parse(SQUARE_WITH_HOLE).unwrap();
+        assert!(region.contains(1.0, 1.0));
+        assert!(!region.contains(5.0, 5.0)); // in the hole
+        assert!(region.contains(4.0, 5.0)); // on the hole's edge
+        assert!(region.contains(0.0, 5.0)); // on the outer edge
+        assert!(region.contains(10.0, 10.0)); // on a vertex
+        assert!(!region.contains(5.0, 11.0));
+        // Concave polygon; the ray from (lon 1, lat 2) passes through the
+        // notch vertex at (2, 2)
+        let notched = Region::parse("POLYGON ((0 0, 4 0, 4 4, 2 2, 0 4))").unwrap();
+        assert!(notched.contains(2.0, 1.0));
+        assert!(notched.contains(2.0, 3.0));
+        assert!(!notched.contains(3.0, 2.0));
+    }
+
+    #[test]
+    fn test_geojson_and_wkt_agree() {
+        let geojson = r#"{
+            "type": "Feature",
+            "properties": {"name": "square"},
+            "geometry": {
+                "type": "MultiPolygon",
+                "coordinates": [
+                    [[[0, 0], [10, 0], [10, 10], [0, 10], [0, 0]],
+                     [[4, 4], [6, 4], [6, 6], [4, 6], [4, 4]]],
+                    [[[20, 20], [21, 20], [21, 21], [20, 20]]]
+                ]
+            }
+        }"#;
+        let from_json = Region::parse(geojson).unwrap();
+        let from_wkt = Region::parse(
+            "MULTIPOLYGON (((0 0, 10 0, 10 10, 0 10, 0 0), (4 4, 6 4, 6 6, 4 6, 4 4)), \
+             ((20 20, 21 20, 21 21, 20 20)))",
+        )
+        .unwrap();
+        assert_eq!(from_json.name.as_deref(), Some("square"));
+        assert_eq!(from_wkt.name, None);
+
+        for (lat, lon) in [
+            (1.0, 1.0),
+            (5.0, 5.0),
+            (20.2, 20.5),
+            (20.8, 20.5),
+            (-1.0, 3.0),
+        ] {
+            assert_eq!(
+                from_json.contains(lat, lon),
+                from_wkt.contains(lat, lon),
+                "({lat}, {lon})"
+            );
+        }
+        assert!(from_json.contains(20.2, 20.5));
+    }
+
+    #[test]
+    fn test_invalid_regions_are_rejected() {
+        assert!(
+            Region::parse("POLYGON ((0 0, 1 1))")
+                .unwrap_err()
+                .contains("at least 3")
+        );
+        assert!(Region::parse("POINT (1 2)").unwrap_err().contains("POINT"));
+        assert!(
+            Region::parse("POLYGON ((0 0, 1 0, 1 1)")
+                .unwrap_err()
+                .contains("unbalanced")
+        );
+        assert!(
+            Region::parse("POLYGON ((0 0, 200 0, 1 1))")
+                .unwrap_err()
+                .contains("out of range")
+        );
+        assert!(Region::parse(r#"{"type": "Point", "coordinates": [1, 2]}"#).is_err());
+
+        let dir = std::env::temp_dir().join(format!("seismotail-{}-region", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("zone.wkt");
+        std::fs::write(&file, SQUARE_WITH_HOLE).unwrap();
+        let region = Region::load(&format!("zone={}", file.display())).unwrap();
+        assert_eq!(region.name.as_deref(), Some("zone"));
+        assert!(matches!(
+            Region::load("missing.wkt"),
+            Err(SeismotailError::Input(_))
+        ));
+    }
+}
@@ -0,0 +1,175 @@
+//! Retry policy for USGS requests.
+//!
+//! Exponential backoff with jitter, honoring the server's `Retry-After`
+//! when it throttles us. Only errors that [`SeismotailError::is_retryable`]
+//! reports as transient are retried.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::errors::SeismotailError;
+
+/// How failed requests are retried.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Total attempts per request, including the first (1 = no retries)
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled on each subsequent retry
+    pub base_delay: Duration,
+    /// Upper bound on any single delay, including `Retry-After`
+    pub max_delay: Duration,
+    /// Randomize each backoff delay to avoid synchronized retries
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Decide whether to retry after `attempt` (1-based) failed with `err`.
+    ///
+    /// Returns the delay to wait before the next attempt, or `None` if the
+    /// error is fatal or the attempts are used up.
+    #[must_use]
+    pub fn next_delay(&self, attempt: u32, err: &SeismotailError) -> Option<Duration> {
+        if attempt >= self.max_attempts || !err.is_retryable() {
+            return None;
+        }
+        if let Some(retry_after) = err.retry_after() {
+            return Some(retry_after.min(self.max_delay));
+        }
+        Some(self.backoff(attempt))
+    }
+
+    /// Backoff delay after `attempt` (1-based) failures.
+    ///
+    /// With jitter the delay is drawn uniformly from the upper half of the
+    /// exponential step, so it never collapses to zero.
+    #[must_use]
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+            .min(self.max_delay);
+        if !self.jitter {
+            return exp;
+        }
+        exp / 2 + exp.mul_f64(random_unit() / 2.0)
+    }
+}
+
+/// Parse a `Retry-After` header: delta seconds or an HTTP date.
+#[must_use]
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    // A date in the past means "retry now"
+    Some((at.with_timezone(&Utc) - now).to_std().unwrap_or_default())
+}
+
+/// A random number in `[0, 1)`.
+///
+/// `RandomState` is randomly keyed per instance, which is plenty for jitter.
+#[allow(clippy::cast_precision_loss)]
+fn random_unit() -> f64 {
+    let bits = RandomState::new().build_hasher().finish() >> 11;
+    bits as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn throttled(retry_after: Option<Duration>) -> SeismotailError {
+        SeismotailError::Throttled {
+            status: 429,
+            retry_after,
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+            jitter: false,
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(500));
+        assert_eq!(policy.backoff(60), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_jitter_stays_in_upper_half() {
+        let policy = RetryPolicy::default();
+        for _ in 0..100 {
+            let delay = policy.backoff(2);
+            assert!(delay >= Duration::from_millis(500));
+            assert!(delay <= Duration::from_secs(1));
+        }
+    }
+
+    #[test]
+    fn test_next_delay_respects_attempts_and_fatal_errors() {
+        let policy = RetryPolicy::default();
+        assert!(policy.next_delay(1, &throttled(None)).is_some());
+        assert!(policy.next_delay(3, &throttled(None)).is_none());
+
+        let fatal = SeismotailError::Api {
+            status: 404,
+            message: String::new(),
+        };
+        assert!(policy.next_delay(1, &fatal).is_none());
+
+        let single = RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        };
+        assert!(single.next_delay(1, &throttled(None)).is_none());
+    }
+
+    #[test]
+    fn test_next_delay_prefers_retry_after() {
+        let policy = RetryPolicy::default();
+        let delay = policy.next_delay(1, &throttled(Some(Duration::from_secs(7))));
+        assert_eq!(delay, Some(Duration::from_secs(7)));
+
+        // Clamped to the policy maximum
+        let delay = policy.next_delay(1, &throttled(Some(Duration::from_hours(1))));
+        assert_eq!(delay, Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = DateTime::parse_from_rfc3339("2024-12-04T08:13:20Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(parse_retry_after("120", now), Some(Duration::from_mins(2)));
+        assert_eq!(
+            parse_retry_after("Wed, 04 Dec 2024 08:14:00 GMT", now),
+            Some(Duration::from_secs(40))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 04 Dec 2024 08:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+}
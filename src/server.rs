@@ -24,18 +24,24 @@ use tokio::sync::broadcast;
 use tokio_stream::wrappers::BroadcastStream;
 use tokio_stream::StreamExt;
 
-use crate::client::{FeedType, UsgsClient};
+use crate::annotate::Annotator;
+use crate::client::FeedFetch;
 use crate::filters::EventFilter;
 use crate::models::Feature;
+use crate::quality::QualityGrade;
+use crate::source::{EventSource, SourceConfig};
+use crate::types::{AlertLevel, EventStatus, Published};
 
 /// Server configuration.
 #[derive(Debug, Clone)]
 pub struct ServerConfig {
     pub port: u16,
     pub host: String,
-    pub feed_type: FeedType,
     pub poll_interval: u64,
     pub filter: EventFilter,
+    pub annotator: Annotator,
+    /// Event source and feed window to poll
+    pub source: SourceConfig,
 }
 
 impl Default for ServerConfig {
@@ -43,9 +49,10 @@ impl Default for ServerConfig {
         Self {
             port: 8080,
             host: "127.0.0.1".to_string(),
-            feed_type: FeedType::AllHour,
             poll_interval: 60,
             filter: EventFilter::default(),
+            annotator: Annotator::default(),
+            source: SourceConfig::default(),
         }
     }
 }
@@ -57,6 +64,8 @@ pub struct AppState {
     tx: broadcast::Sender<String>,
     /// Flag to control feed polling
     feed_active: Arc<AtomicBool>,
+    /// Shared event source (one connection pool for polling and page loads)
+    source: Arc<dyn EventSource>,
     /// Server configuration
     config: ServerConfig,
 }
@@ -79,10 +88,12 @@ pub async fn run_server(config: ServerConfig) -> anyhow::Result<()> {
     // Create broadcast channel for SSE
     let (tx, _rx) = broadcast::channel::<String>(100);
     let feed_active = Arc::new(AtomicBool::new(true));
+    let source = config.source.connect()?;
 
     let state = AppState {
         tx: tx.clone(),
         feed_active: feed_active.clone(),
+        source,
         config: config.clone(),
     };
 
@@ -103,16 +114,8 @@ pub async fn run_server(config: ServerConfig) -> anyhow::Result<()> {
     Ok(())
 }
 
-/// Background task that polls USGS and broadcasts events.
+/// Background task that polls the event source and broadcasts events.
 async fn poll_earthquakes(state: AppState) {
-    let client = match UsgsClient::new() {
-        Ok(c) => c,
-        Err(e) => {
-            tracing::error!("Failed to create USGS client: {}", e);
-            return;
-        }
-    };
-
     let mut seen_ids = std::collections::HashSet::new();
 
     loop {
@@ -122,9 +125,12 @@ async fn poll_earthquakes(state: AppState) {
             continue;
         }
 
-        match client.fetch_feed(state.config.feed_type) {
-            Ok(feed) => {
-                for event in feed.features {
+        match state.source.fetch_if_modified().await {
+            Ok(FeedFetch::NotModified) => {
+                tracing::debug!("Feed not modified, skipping dedup pass");
+            }
+            Ok(FeedFetch::Modified(feed)) => {
+                for mut event in feed.features {
                     // Deduplication
                     if seen_ids.contains(&event.id) {
                         continue;
@@ -136,6 +142,8 @@ async fn poll_earthquakes(state: AppState) {
                     }
 
                     seen_ids.insert(event.id.clone());
+                    state.config.filter.tag_matches(&mut event);
+                    state.config.annotator.annotate(&mut event);
 
                     // Format as HTML for HTMX swap
                     let html = format_event_html(&event);
@@ -154,10 +162,19 @@ async fn poll_earthquakes(state: AppState) {
 }
 
 /// Format an earthquake event as HTML.
+#[allow(clippy::too_many_lines)]
 fn format_event_html(event: &Feature) -> String {
     let mag = event.properties.mag.unwrap_or(0.0);
-    let mag_type = event.properties.mag_type.as_deref().unwrap_or("?");
-    let place = event.properties.place.as_deref().unwrap_or("Unknown location");
+    let mag_type = event
+        .properties
+        .mag_type
+        .as_ref()
+        .map_or("?", Published::as_str);
+    let place = event
+        .properties
+        .place
+        .as_deref()
+        .unwrap_or("Unknown location");
     let depth = event.depth_km();
     let severity_class = match mag {
         m if m >= 7.0 => "severity-critical",
@@ -180,8 +197,9 @@ fn format_event_html(event: &Feature) -> String {
     let lon = event.longitude();
     
     // Relative time (e.g., "2 hours ago")
-    let relative_time = event.time()
-        .map(|t| {
+    let relative_time = event.time().map_or_else(
+        || "Unknown".to_string(),
+        |t| {
             let now = chrono::Utc::now();
             let diff = now.signed_duration_since(t);
             if diff.num_hours() < 1 {
@@ -191,43 +209,41 @@ fn format_event_html(event: &Feature) -> String {
             } else {
                 format!("{} days ago", diff.num_days())
             }
-        })
-        .unwrap_or_else(|| "Unknown".to_string());
+        },
+    );
 
     // Build rich metadata pills
     let mut meta_pills = Vec::new();
     
     // Status (reviewed vs automatic)
-    let status_class = if event.properties.status == "reviewed" { "reviewed" } else { "automatic" };
-    let status_icon = if event.properties.status == "reviewed" { "✓" } else { "◐" };
+    let reviewed = event.properties.status == EventStatus::Reviewed;
+    let status_class = if reviewed { "reviewed" } else { "automatic" };
+    let status_icon = if reviewed { "✓" } else { "◐" };
     meta_pills.push(format!(
         r#"<span class="meta-pill {}">{} {}</span>"#,
         status_class, status_icon, event.properties.status
     ));
     
     // Felt reports
-    if let Some(felt) = event.properties.felt {
-        if felt > 0 {
-            meta_pills.push(format!(
-                r#"<span class="meta-pill felt">👥 {} felt</span>"#,
-                felt
-            ));
-        }
+    if let Some(felt) = event.properties.felt
+        && felt > 0
+    {
+        meta_pills.push(format!(
+            r#"<span class="meta-pill felt">👥 {felt} felt</span>"#
+        ));
     }
     
     // Community Intensity (CDI)
     if let Some(cdi) = event.properties.cdi {
         meta_pills.push(format!(
-            r#"<span class="meta-pill intensity">📊 CDI {:.1}</span>"#,
-            cdi
+            r#"<span class="meta-pill intensity">📊 CDI {cdi:.1}</span>"#
         ));
     }
     
     // Modified Mercalli Intensity (MMI)
     if let Some(mmi) = event.properties.mmi {
         meta_pills.push(format!(
-            r#"<span class="meta-pill intensity">📈 MMI {:.1}</span>"#,
-            mmi
+            r#"<span class="meta-pill intensity">📈 MMI {mmi:.1}</span>"#
         ));
     }
     
@@ -235,32 +251,59 @@ fn format_event_html(event: &Feature) -> String {
     let sig = event.properties.sig;
     if sig >= 500 {
         meta_pills.push(format!(
-            r#"<span class="meta-pill sig-high">⚡ sig {}</span>"#,
-            sig
+            r#"<span class="meta-pill sig-high">⚡ sig {sig}</span>"#
         ));
     } else if sig >= 100 {
         meta_pills.push(format!(
-            r#"<span class="meta-pill">⚡ sig {}</span>"#,
-            sig
+            r#"<span class="meta-pill">⚡ sig {sig}</span>"#
         ));
     }
     
+    // Location quality
+    if let Some(quality) = QualityGrade::of(event) {
+        meta_pills.push(format!(
+            r#"<span class="meta-pill">◎ quality {quality}</span>"#
+        ));
+    }
+
     // Number of stations
     if let Some(nst) = event.properties.nst {
         meta_pills.push(format!(
-            r#"<span class="meta-pill">📡 {} stations</span>"#,
-            nst
+            r#"<span class="meta-pill">📡 {nst} stations</span>"#
         ));
     }
     
     // Azimuthal gap
     if let Some(gap) = event.properties.gap {
         meta_pills.push(format!(
-            r#"<span class="meta-pill">◔ gap {:.0}°</span>"#,
-            gap
+            r#"<span class="meta-pill">◔ gap {gap:.0}°</span>"#
         ));
     }
     
+    // Nearest place and distance from home
+    if let Some(place) = &event.nearest_place {
+        meta_pills.push(format!(
+            r#"<span class="meta-pill">🏙 {:.0} km {} of {}</span>"#,
+            place.proximity.distance_km, place.proximity.direction, place.name
+        ));
+    }
+    if let Some(home) = &event.home {
+        meta_pills.push(format!(
+            r#"<span class="meta-pill">🏠 {:.0} km {}</span>"#,
+            home.distance_km, home.direction
+        ));
+    }
+
+    // Matching regions and triggered watch zones
+    for region in &event.regions {
+        meta_pills.push(format!(r#"<span class="meta-pill">📍 {region}</span>"#));
+    }
+    for zone in &event.zones {
+        meta_pills.push(format!(
+            r#"<span class="meta-pill sig-high">⚑ {zone}</span>"#
+        ));
+    }
+
     // Network
     meta_pills.push(format!(
         r#"<span class="meta-pill">🌐 {}</span>"#,
@@ -336,11 +379,15 @@ fn format_event_html(event: &Feature) -> String {
             r#"<span class="badge badge-tsunami">🌊 Tsunami</span>"#
         } else { "" },
         alert_badge = match event.properties.alert.as_deref() {
-            Some("red") => r#"<span class="badge badge-alert badge-alert-red">⚠ Red Alert</span>"#,
-            Some("orange") => r#"<span class="badge badge-alert badge-alert-orange">⚠ Orange</span>"#,
-            Some("yellow") => r#"<span class="badge badge-alert badge-alert-yellow">⚠ Yellow</span>"#,
-            Some("green") => r#"<span class="badge badge-alert badge-alert-green">✓ Green</span>"#,
-            _ => "",
+            Some(AlertLevel::Red) =>
+                r#"<span class="badge badge-alert badge-alert-red">⚠ Red Alert</span>"#,
+            Some(AlertLevel::Orange) =>
+                r#"<span class="badge badge-alert badge-alert-orange">⚠ Orange</span>"#,
+            Some(AlertLevel::Yellow) =>
+                r#"<span class="badge badge-alert badge-alert-yellow">⚠ Yellow</span>"#,
+            Some(AlertLevel::Green) =>
+                r#"<span class="badge badge-alert badge-alert-green">✓ Green</span>"#,
+            Some(AlertLevel::Unknown(_)) | None => "",
         },
         place = place,
         depth = depth,
@@ -413,12 +460,7 @@ async fn health_handler() -> &'static str {
 
 /// Recent events handler - fetches current events for initial page load.
 async fn recent_events_handler(State(state): State<AppState>) -> Html<String> {
-    let client = match UsgsClient::new() {
-        Ok(c) => c,
-        Err(_) => return Html("<div class='error'>Failed to fetch events</div>".to_string()),
-    };
-
-    match client.fetch_feed(state.config.feed_type) {
+    match state.source.fetch().await {
         Ok(feed) => {
             let mut html = String::new();
             let mut count = 0;
@@ -429,7 +471,10 @@ async fn recent_events_handler(State(state): State<AppState>) -> Html<String> {
                     continue;
                 }
                 
-                html.push_str(&format_event_html(event));
+                let mut event = event.clone();
+                state.config.filter.tag_matches(&mut event);
+                state.config.annotator.annotate(&mut event);
+                html.push_str(&format_event_html(&event));
                 count += 1;
             }
             
@@ -1119,3 +1164,44 @@ const INDEX_HTML: &str = r##"<!DOCTYPE html>
 </body>
 </html>
 "##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{Response, SAMPLE_FEED, StandInServer};
+
+    fn test_state(base_url: &str) -> AppState {
+        let (tx, _rx) = broadcast::channel::<String>(16);
+        AppState {
+            tx,
+            feed_active: Arc::new(AtomicBool::new(true)),
+            source: SourceConfig {
+                usgs_url: base_url.to_string(),
+                ..SourceConfig::default()
+            }
+            .connect()
+            .expect("source"),
+            config: ServerConfig::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recent_events_uses_shared_source() {
+        let server = StandInServer::start(|_| Response::json(SAMPLE_FEED));
+        let state = test_state(server.url());
+
+        let Html(first) = recent_events_handler(State(state.clone())).await;
+        let Html(second) = recent_events_handler(State(state)).await;
+
+        assert!(first.contains("event-us7000n1a2"));
+        assert_eq!(first, second);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_recent_events_reports_fetch_failure() {
+        let server = StandInServer::start(|_| Response::status(500));
+        let Html(html) = recent_events_handler(State(test_state(server.url()))).await;
+        assert!(html.contains("Failed to fetch events"));
+    }
+}
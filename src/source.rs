@@ -0,0 +1,255 @@
+//! Pluggable earthquake data sources.
+//!
+//! An [`EventSource`] yields recent events normalized to the USGS
+//! [`Feature`] model, so filtering, dedup and output work the same for
+//! every provider. Sources are async and object-safe; the CLI drives them
+//! through a small tokio runtime, the web server directly.
+
+use std::collections::hash_map::DefaultHasher;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use crate::client::{AsyncUsgsClient, FeedFetch, FeedType, USGS_BASE_URL};
+use crate::emsc::{EMSC_BASE_URL, EMSC_WS_URL, EmscClient};
+use crate::errors::SeismotailError;
+use crate::geonet::{GEONET_BASE_URL, GeoNetClient};
+use crate::input::FileSource;
+use crate::models::{Feature, FeatureCollection};
+use crate::record::RecordingSource;
+use crate::retry::RetryPolicy;
+
+/// Boxed future returned by [`EventSource`] methods.
+pub type SourceFuture<'a, T> =
+    Pin<Box<dyn Future<Output = Result<T, SeismotailError>> + Send + 'a>>;
+
+/// A provider of recent earthquake events.
+pub trait EventSource: Send + Sync {
+    /// Short provider name for logs and banners.
+    fn name(&self) -> &'static str;
+
+    /// Fetch the current window of recent events.
+    fn fetch(&self) -> SourceFuture<'_, FeatureCollection>;
+
+    /// Fetch recent events only if they changed since the last call.
+    fn fetch_if_modified(&self) -> SourceFuture<'_, FeedFetch>;
+
+    /// Whether a finite source (stdin, a replay) has nothing left to poll.
+    fn exhausted(&self) -> bool {
+        false
+    }
+}
+
+/// Supported data providers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SourceKind {
+    /// USGS summary feeds (default)
+    #[default]
+    Usgs,
+    /// EMSC `SeismicPortal` FDSN service (Euro-Mediterranean focus)
+    Emsc,
+    /// `GeoNet` quake API (New Zealand)
+    GeoNet,
+}
+
+impl SourceKind {
+    /// Get the CLI name of this source.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Usgs => "usgs",
+            Self::Emsc => "emsc",
+            Self::GeoNet => "geonet",
+        }
+    }
+}
+
+impl std::str::FromStr for SourceKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "usgs" => Ok(Self::Usgs),
+            "emsc" => Ok(Self::Emsc),
+            "geonet" => Ok(Self::GeoNet),
+            _ => Err(format!(
+                "unknown source: {s} (expected: usgs, emsc, geonet)"
+            )),
+        }
+    }
+}
+
+/// Everything needed to connect to a source.
+#[derive(Debug, Clone)]
+pub struct SourceConfig {
+    pub kind: SourceKind,
+    /// Recent-events window (and magnitude threshold for non-USGS sources)
+    pub feed: FeedType,
+    pub usgs_url: String,
+    pub emsc_url: String,
+    /// EMSC WebSocket stream, used for push ingestion
+    pub emsc_ws_url: String,
+    pub geonet_url: String,
+    pub retry: RetryPolicy,
+    /// Read from this file, snapshot directory or `-` (stdin) instead
+    pub input: Option<PathBuf>,
+    /// Record every poll into this directory
+    pub record: Option<PathBuf>,
+}
+
+impl Default for SourceConfig {
+    fn default() -> Self {
+        Self {
+            kind: SourceKind::Usgs,
+            feed: FeedType::AllHour,
+            usgs_url: USGS_BASE_URL.to_string(),
+            emsc_url: EMSC_BASE_URL.to_string(),
+            emsc_ws_url: EMSC_WS_URL.to_string(),
+            geonet_url: GEONET_BASE_URL.to_string(),
+            retry: RetryPolicy::default(),
+            input: None,
+            record: None,
+        }
+    }
+}
+
+impl SourceConfig {
+    /// Create the configured source.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be initialized, the
+    /// input path does not exist or the recording cannot be started.
+    pub fn connect(&self) -> Result<Arc<dyn EventSource>, SeismotailError> {
+        let source = self.connect_inner()?;
+        Ok(match &self.record {
+            Some(dir) => Arc::new(RecordingSource::create(source, dir)?),
+            None => source,
+        })
+    }
+
+    /// Create the source without recording.
+    fn connect_inner(&self) -> Result<Arc<dyn EventSource>, SeismotailError> {
+        if let Some(path) = &self.input {
+            return Ok(Arc::new(FileSource::open(path)?));
+        }
+        Ok(match self.kind {
+            SourceKind::Usgs => Arc::new(UsgsSource {
+                client: AsyncUsgsClient::with_base_url(&self.usgs_url)?.with_retry(self.retry),
+                feed: self.feed,
+            }),
+            SourceKind::Emsc => Arc::new(
+                EmscClient::with_base_url(&self.emsc_url, self.feed)?.with_retry(self.retry),
+            ),
+            SourceKind::GeoNet => Arc::new(
+                GeoNetClient::with_base_url(&self.geonet_url, self.feed)?.with_retry(self.retry),
+            ),
+        })
+    }
+}
+
+/// A USGS summary feed as an event source.
+pub struct UsgsSource {
+    client: AsyncUsgsClient,
+    feed: FeedType,
+}
+
+impl EventSource for UsgsSource {
+    fn name(&self) -> &'static str {
+        SourceKind::Usgs.as_str()
+    }
+
+    fn fetch(&self) -> SourceFuture<'_, FeatureCollection> {
+        Box::pin(self.client.fetch_feed(self.feed))
+    }
+
+    fn fetch_if_modified(&self) -> SourceFuture<'_, FeedFetch> {
+        Box::pin(self.client.fetch_feed_if_modified(self.feed))
+    }
+}
+
+/// Detects unchanged results for providers without cache validators.
+///
+/// Remembers a fingerprint of the event IDs and update times of the last
+/// result.
+#[derive(Debug, Default)]
+pub struct ChangeTracker {
+    last: Mutex<Option<u64>>,
+}
+
+impl ChangeTracker {
+    /// Record `events` and report whether they differ from the last call.
+    pub fn changed(&self, events: &[Feature]) -> bool {
+        let mut hasher = DefaultHasher::new();
+        for event in events {
+            event.id.hash(&mut hasher);
+            event.properties.updated.hash(&mut hasher);
+        }
+        let fingerprint = hasher.finish();
+
+        let Ok(mut last) = self.last.lock() else {
+            return true;
+        };
+        last.replace(fingerprint) != Some(fingerprint)
+    }
+
+    /// Wrap a fresh result as a conditional fetch.
+    pub fn fetch_result(&self, feed: FeatureCollection) -> FeedFetch {
+        if self.changed(&feed.features) {
+            FeedFetch::Modified(feed)
+        } else {
+            FeedFetch::NotModified
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{Response, SAMPLE_FEED, StandInServer};
+
+    fn sample_events() -> Vec<Feature> {
+        let feed: FeatureCollection = serde_json::from_str(SAMPLE_FEED).unwrap();
+        feed.features
+    }
+
+    #[test]
+    fn test_source_kind_round_trip() {
+        for kind in [SourceKind::Usgs, SourceKind::Emsc, SourceKind::GeoNet] {
+            assert_eq!(kind.as_str().parse::<SourceKind>().unwrap(), kind);
+        }
+        assert!("isc".parse::<SourceKind>().is_err());
+    }
+
+    #[test]
+    fn test_change_tracker() {
+        let tracker = ChangeTracker::default();
+        let mut events = sample_events();
+
+        assert!(tracker.changed(&events));
+        assert!(!tracker.changed(&events));
+
+        events[0].properties.updated += 1;
+        assert!(tracker.changed(&events));
+    }
+
+    #[tokio::test]
+    async fn test_usgs_source_through_trait() {
+        let server = StandInServer::start(|_| Response::json(SAMPLE_FEED));
+        let config = SourceConfig {
+            feed: FeedType::Mag45Week,
+            usgs_url: server.url().to_string(),
+            ..SourceConfig::default()
+        };
+        let source = config.connect().unwrap();
+
+        assert_eq!(source.name(), "usgs");
+        assert_eq!(source.fetch().await.unwrap().features.len(), 8);
+        assert_eq!(
+            server.requests()[0].path,
+            "/earthquakes/feed/v1.0/summary/4.5_week.geojson"
+        );
+    }
+}
@@ -0,0 +1,309 @@
+//! Test helpers.
+//!
+//! Provides a minimal local HTTP stand-in for the USGS endpoints so client
+//! tests run without network access.
+
+// Not every test module uses every helper.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// A request received by the stand-in server.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    /// Path without the query string
+    pub path: String,
+    /// Decoded query parameters
+    pub query: HashMap<String, String>,
+    /// Headers with lowercased names
+    pub headers: HashMap<String, String>,
+}
+
+impl Request {
+    /// Get a decoded query parameter.
+    #[must_use]
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.query.get(name).map(String::as_str)
+    }
+
+    /// Get a header by (case-insensitive) name.
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_lowercase()).map(String::as_str)
+    }
+}
+
+/// A canned response from the stand-in server.
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// A 200 response with a JSON body.
+    pub fn json(body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status: 200,
+            headers: vec![("Content-Type".into(), "application/json".into())],
+            body: body.into(),
+        }
+    }
+
+    /// An empty response with the given status.
+    #[must_use]
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// Add a response header.
+    #[must_use]
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// Local HTTP server answering every request through a handler closure.
+pub struct StandInServer {
+    url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl StandInServer {
+    /// Start the server on an ephemeral localhost port.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the listener cannot be bound.
+    pub fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&Request) -> Response + Send + Sync + 'static,
+    {
+        #[allow(clippy::expect_used)]
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind stand-in server");
+        #[allow(clippy::expect_used)]
+        let addr = listener.local_addr().expect("no local address");
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let log = Arc::clone(&requests);
+        let handler = Arc::new(handler);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let log = Arc::clone(&log);
+                let handler = Arc::clone(&handler);
+                thread::spawn(move || serve_connection(stream, &*handler, &log));
+            }
+        });
+
+        Self {
+            url: format!("http://{addr}"),
+            requests,
+        }
+    }
+
+    /// Base URL of the server (no trailing slash).
+    #[must_use]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Requests received so far, in arrival order.
+    #[must_use]
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().map(|r| r.clone()).unwrap_or_default()
+    }
+}
+
+fn serve_connection(
+    stream: TcpStream,
+    handler: &(dyn Fn(&Request) -> Response + Send + Sync),
+    log: &Mutex<Vec<Request>>,
+) {
+    let Ok(read_half) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(read_half);
+    let mut writer = stream;
+
+    let Some(request) = read_request(&mut reader) else {
+        return;
+    };
+    if let Ok(mut log) = log.lock() {
+        log.push(request.clone());
+    }
+
+    let response = handler(&request);
+    let _ = write_response(&mut writer, &response);
+}
+
+fn read_request<R: BufRead>(reader: &mut R) -> Option<Request> {
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?.to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).ok()?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    // Drain any request body so the client sees a clean close
+    if let Some(len) = headers
+        .get("content-length")
+        .and_then(|l| l.parse::<usize>().ok())
+    {
+        let mut body = vec![0; len];
+        reader.read_exact(&mut body).ok()?;
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((target.as_str(), ""));
+    let query = query
+        .split('&')
+        .filter(|kv| !kv.is_empty())
+        .map(|kv| {
+            let (k, v) = kv.split_once('=').unwrap_or((kv, ""));
+            (percent_decode(k), percent_decode(v))
+        })
+        .collect();
+
+    Some(Request {
+        method,
+        path: path.to_string(),
+        query,
+        headers,
+    })
+}
+
+fn write_response<W: Write>(writer: &mut W, response: &Response) -> std::io::Result<()> {
+    write!(writer, "HTTP/1.1 {} Stand-In\r\n", response.status)?;
+    for (name, value) in &response.headers {
+        write!(writer, "{name}: {value}\r\n")?;
+    }
+    write!(
+        writer,
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        response.body.len()
+    )?;
+    writer.write_all(&response.body)?;
+    writer.flush()
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 3 <= bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+                if let Ok(b) = u8::from_str_radix(hex, 16) {
+                    out.push(b);
+                    i += 3;
+                    continue;
+                }
+                out.push(b'%');
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// The recorded sample feed shipped in `tools/`.
+pub const SAMPLE_FEED: &str = include_str!("../tools/sample_2.5_day.json");
+
+/// The recorded detail GeoJSON of the first sample event.
+pub const SAMPLE_DETAIL: &str = include_str!("../tools/sample_detail.json");
+
+/// A recorded EMSC `SeismicPortal` FDSN response.
+pub const SAMPLE_EMSC: &str = include_str!("../tools/sample_emsc.json");
+
+/// A recorded `GeoNet` quake API response.
+pub const SAMPLE_GEONET: &str = include_str!("../tools/sample_geonet.json");
+
+/// Recorded EMSC push messages, one JSON message per line.
+pub const SAMPLE_EMSC_WS: &str = include_str!("../tools/sample_emsc_ws.ndjson");
+
+/// Start a local WebSocket server that replays scripted sessions.
+///
+/// Each accepted connection is sent the next session's messages and then
+/// closed. Once the sessions run out the listener is dropped, so further
+/// connection attempts fail. Returns the `ws://` URL.
+///
+/// # Panics
+///
+/// Panics if the listener cannot be bound.
+pub async fn start_ws_stand_in(sessions: Vec<Vec<String>>) -> String {
+    use futures_util::SinkExt;
+    use tokio_tungstenite::tungstenite::Message;
+
+    #[allow(clippy::expect_used)]
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("failed to bind WebSocket stand-in");
+    #[allow(clippy::expect_used)]
+    let addr = listener.local_addr().expect("no local address");
+
+    tokio::spawn(async move {
+        for messages in sessions {
+            let Ok((stream, _)) = listener.accept().await else {
+                return;
+            };
+            let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await else {
+                continue;
+            };
+            for message in messages {
+                if ws.send(Message::text(message)).await.is_err() {
+                    break;
+                }
+            }
+            let _ = ws.close(None).await;
+        }
+    });
+
+    format!("ws://{addr}")
+}
+
+/// FDSN QuakeML response with USGS and EMSC style events.
+pub const SAMPLE_QUAKEML: &str = include_str!("../tools/sample_quakeml.xml");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("a+b%2Cc"), "a b,c");
+        assert_eq!(
+            percent_decode("2024-01-01T00%3A00%3A00"),
+            "2024-01-01T00:00:00"
+        );
+        // A trailing escape decodes; a truncated one is kept as is
+        assert_eq!(percent_decode("a%2C"), "a,");
+        assert_eq!(percent_decode("a%2"), "a%2");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+}
@@ -0,0 +1,487 @@
+//! Typed event attributes.
+//!
+//! USGS publishes status, alert level, event type and magnitude type as
+//! free-form strings. These enums give the known values names (and an
+//! order where one makes sense) while keeping anything unrecognized in an
+//! `Unknown` variant, so parsing never fails. Names are matched
+//! case-insensitively; [`Published`] keeps the spelling a provider used so
+//! output carries `ML` or `mblg` as published.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+
+use serde::{Deserialize, Serialize};
+
+/// Review status of an event.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum EventStatus {
+    /// Computed automatically, not yet looked at by a seismologist
+    #[default]
+    Automatic,
+    /// Reviewed by a seismologist
+    Reviewed,
+    /// Retracted by the network
+    Deleted,
+    Unknown(String),
+}
+
+impl EventStatus {
+    /// Get the wire name of this status.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Automatic => "automatic",
+            Self::Reviewed => "reviewed",
+            Self::Deleted => "deleted",
+            Self::Unknown(s) => s,
+        }
+    }
+}
+
+impl From<&str> for EventStatus {
+    fn from(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "automatic" => Self::Automatic,
+            "reviewed" => Self::Reviewed,
+            "deleted" => Self::Deleted,
+            _ => Self::Unknown(s.to_string()),
+        }
+    }
+}
+
+/// PAGER alert level, ordered by severity (`Green < Yellow < Orange < Red`).
+///
+/// Unrecognized levels sort below `Green`, so they never satisfy a minimum.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum AlertLevel {
+    Unknown(String),
+    Green,
+    Yellow,
+    Orange,
+    Red,
+}
+
+impl AlertLevel {
+    /// Get the wire name of this level.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Green => "green",
+            Self::Yellow => "yellow",
+            Self::Orange => "orange",
+            Self::Red => "red",
+            Self::Unknown(s) => s,
+        }
+    }
+
+    /// Whether this is one of the four PAGER levels.
+    #[must_use]
+    pub fn is_known(&self) -> bool {
+        !matches!(self, Self::Unknown(_))
+    }
+}
+
+impl From<&str> for AlertLevel {
+    fn from(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "green" => Self::Green,
+            "yellow" => Self::Yellow,
+            "orange" => Self::Orange,
+            "red" => Self::Red,
+            _ => Self::Unknown(s.to_string()),
+        }
+    }
+}
+
+/// Kind of seismic event, using the USGS vocabulary.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum EventType {
+    Earthquake,
+    QuarryBlast,
+    Explosion,
+    ChemicalExplosion,
+    MiningExplosion,
+    NuclearExplosion,
+    RockBurst,
+    IceQuake,
+    Landslide,
+    VolcanicEruption,
+    OtherEvent,
+    Unknown(String),
+}
+
+impl EventType {
+    /// Get the wire name of this event type.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Earthquake => "earthquake",
+            Self::QuarryBlast => "quarry blast",
+            Self::Explosion => "explosion",
+            Self::ChemicalExplosion => "chemical explosion",
+            Self::MiningExplosion => "mining explosion",
+            Self::NuclearExplosion => "nuclear explosion",
+            Self::RockBurst => "rock burst",
+            Self::IceQuake => "ice quake",
+            Self::Landslide => "landslide",
+            Self::VolcanicEruption => "volcanic eruption",
+            Self::OtherEvent => "other event",
+            Self::Unknown(s) => s,
+        }
+    }
+}
+
+impl From<&str> for EventType {
+    fn from(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "earthquake" => Self::Earthquake,
+            "quarry blast" | "quarry" => Self::QuarryBlast,
+            "explosion" => Self::Explosion,
+            "chemical explosion" => Self::ChemicalExplosion,
+            "mining explosion" => Self::MiningExplosion,
+            "nuclear explosion" => Self::NuclearExplosion,
+            "rock burst" => Self::RockBurst,
+            "ice quake" => Self::IceQuake,
+            "landslide" => Self::Landslide,
+            "volcanic eruption" => Self::VolcanicEruption,
+            "other event" => Self::OtherEvent,
+            _ => Self::Unknown(s.to_string()),
+        }
+    }
+}
+
+/// Magnitude scale. Names are matched case-insensitively (`ML` = `ml`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum MagnitudeType {
+    /// W-phase moment magnitude
+    Mww,
+    /// Centroid moment magnitude
+    Mwc,
+    /// Body-wave moment magnitude
+    Mwb,
+    /// Regional moment magnitude
+    Mwr,
+    /// Moment magnitude
+    Mw,
+    /// Surface-wave magnitude
+    Ms,
+    /// Body-wave magnitude
+    Mb,
+    /// Lg-wave body magnitude
+    MbLg,
+    /// Local (Richter) magnitude
+    Ml,
+    /// Duration magnitude
+    Md,
+    /// Hand-picked magnitude
+    Mh,
+    Unknown(String),
+}
+
+impl MagnitudeType {
+    /// Get the wire name of this magnitude type.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Mww => "mww",
+            Self::Mwc => "mwc",
+            Self::Mwb => "mwb",
+            Self::Mwr => "mwr",
+            Self::Mw => "mw",
+            Self::Ms => "ms",
+            Self::Mb => "mb",
+            Self::MbLg => "mb_lg",
+            Self::Ml => "ml",
+            Self::Md => "md",
+            Self::Mh => "mh",
+            Self::Unknown(s) => s,
+        }
+    }
+}
+
+impl From<&str> for MagnitudeType {
+    fn from(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "mww" => Self::Mww,
+            "mwc" => Self::Mwc,
+            "mwb" => Self::Mwb,
+            "mwr" => Self::Mwr,
+            "mw" => Self::Mw,
+            "ms" => Self::Ms,
+            "mb" => Self::Mb,
+            "mb_lg" | "mblg" => Self::MbLg,
+            "ml" => Self::Ml,
+            "md" => Self::Md,
+            "mh" => Self::Mh,
+            _ => Self::Unknown(s.to_string()),
+        }
+    }
+}
+
+// Conversions shared by all attribute enums: serde goes through `String`
+// and `Display` prints the wire name.
+
+impl From<String> for EventStatus {
+    fn from(s: String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl From<String> for AlertLevel {
+    fn from(s: String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl From<String> for EventType {
+    fn from(s: String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl From<String> for MagnitudeType {
+    fn from(s: String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl From<EventStatus> for String {
+    fn from(v: EventStatus) -> Self {
+        v.as_str().to_string()
+    }
+}
+
+impl From<AlertLevel> for String {
+    fn from(v: AlertLevel) -> Self {
+        v.as_str().to_string()
+    }
+}
+
+impl From<EventType> for String {
+    fn from(v: EventType) -> Self {
+        v.as_str().to_string()
+    }
+}
+
+impl From<MagnitudeType> for String {
+    fn from(v: MagnitudeType) -> Self {
+        v.as_str().to_string()
+    }
+}
+
+impl From<EventStatus> for Published<EventStatus> {
+    fn from(v: EventStatus) -> Self {
+        Self::new(v)
+    }
+}
+
+impl From<AlertLevel> for Published<AlertLevel> {
+    fn from(v: AlertLevel) -> Self {
+        Self::new(v)
+    }
+}
+
+impl From<EventType> for Published<EventType> {
+    fn from(v: EventType) -> Self {
+        Self::new(v)
+    }
+}
+
+impl From<MagnitudeType> for Published<MagnitudeType> {
+    fn from(v: MagnitudeType) -> Self {
+        Self::new(v)
+    }
+}
+
+impl fmt::Display for EventStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for AlertLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for EventType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for MagnitudeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A typed attribute together with the spelling it was published with.
+///
+/// Comparisons, ordering and hashing go through the typed value, so `ML`
+/// equals `ml`; serialization and `Display` use the published spelling.
+#[derive(Debug, Clone)]
+pub struct Published<T> {
+    value: T,
+    raw: String,
+}
+
+impl<T: fmt::Display> Published<T> {
+    /// Wrap a value under its canonical spelling.
+    #[must_use]
+    pub fn new(value: T) -> Self {
+        let raw = value.to_string();
+        Self { value, raw }
+    }
+}
+
+impl<T> Published<T> {
+    /// Get the spelling this value was published with.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+}
+
+impl<T> Deref for Published<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: for<'a> From<&'a str>> From<&str> for Published<T> {
+    fn from(s: &str) -> Self {
+        Self {
+            value: T::from(s),
+            raw: s.to_string(),
+        }
+    }
+}
+
+impl<T: for<'a> From<&'a str>> From<String> for Published<T> {
+    fn from(raw: String) -> Self {
+        Self {
+            value: T::from(raw.as_str()),
+            raw,
+        }
+    }
+}
+
+impl<T: Default + fmt::Display> Default for Published<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: PartialEq> PartialEq for Published<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: PartialEq> PartialEq<T> for Published<T> {
+    fn eq(&self, other: &T) -> bool {
+        self.value == *other
+    }
+}
+
+impl<T: Eq> Eq for Published<T> {}
+
+impl<T: PartialOrd> PartialOrd for Published<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<T: Ord> Ord for Published<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl<T: Hash> Hash for Published<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
+impl<T> fmt::Display for Published<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl<T> Serialize for Published<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.raw)
+    }
+}
+
+impl<'de, T: for<'a> From<&'a str>> Deserialize<'de> for Published<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alert_levels_are_ordered() {
+        assert!(AlertLevel::Green < AlertLevel::Yellow);
+        assert!(AlertLevel::Yellow < AlertLevel::Orange);
+        assert!(AlertLevel::Orange < AlertLevel::Red);
+        assert!(AlertLevel::from("RED") >= AlertLevel::Orange);
+        // Unknown levels never pass a threshold
+        assert!(AlertLevel::from("purple") < AlertLevel::Green);
+        assert!(!AlertLevel::from("purple").is_known());
+    }
+
+    #[test]
+    fn test_unknown_values_round_trip() {
+        for raw in ["mb_lg", "ML", "mwp", "Mi"] {
+            let parsed: MagnitudeType = serde_json::from_str(&format!("\"{raw}\"")).unwrap();
+            let expected = if matches!(parsed, MagnitudeType::Unknown(_)) {
+                raw.to_string()
+            } else {
+                raw.to_lowercase()
+            };
+            assert_eq!(
+                serde_json::to_string(&parsed).unwrap(),
+                format!("\"{expected}\"")
+            );
+        }
+        assert_eq!(MagnitudeType::from("ML"), MagnitudeType::Ml);
+        assert_eq!(EventType::from("quarry blast"), EventType::QuarryBlast);
+        assert_eq!(EventType::from("sinkhole").as_str(), "sinkhole");
+        assert_eq!(EventStatus::from("reviewed").to_string(), "reviewed");
+    }
+
+    #[test]
+    fn test_published_keeps_spelling() {
+        for raw in ["ML", "mblg", "Mww", "mwp"] {
+            let parsed: Published<MagnitudeType> =
+                serde_json::from_str(&format!("\"{raw}\"")).unwrap();
+            assert_eq!(
+                serde_json::to_string(&parsed).unwrap(),
+                format!("\"{raw}\"")
+            );
+        }
+        let ml = Published::<MagnitudeType>::from("ML");
+        assert_eq!(*ml, MagnitudeType::Ml);
+        assert_eq!(ml, Published::from("ml"));
+        assert_eq!(ml.to_string(), "ML");
+        assert!(Published::<AlertLevel>::from("RED") > AlertLevel::Orange.into());
+        assert_eq!(Published::from(EventStatus::Reviewed).as_str(), "reviewed");
+    }
+}
@@ -0,0 +1,187 @@
+//! Named watch zones for `--zones`.
+//!
+//! A zones file is a JSON array of zones, each with a name, one area
+//! (`radius`, `bbox` or a `region` polygon file) and its own thresholds:
+//!
+//! ```json
+//! [
+//!   { "name": "hq", "radius": "37.77,-122.42,150", "min_magnitude": 3.0 },
+//!   { "name": "dam", "region": "dam.geojson", "min_magnitude": 2.0, "max_depth": 20 }
+//! ]
+//! ```
+//!
+//! Region paths are relative to the zones file.
+
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::errors::SeismotailError;
+use crate::filters::{BBox, RadiusFilter};
+use crate::geodesic::DistanceModel;
+use crate::models::Feature;
+use crate::region::Region;
+
+/// Area covered by a zone.
+#[derive(Debug, Clone)]
+pub enum Area {
+    Circle(RadiusFilter),
+    BBox(BBox),
+    Polygon(Region),
+}
+
+impl Area {
+    /// Whether a point lies in the area; circles are measured with `model`.
+    #[must_use]
+    pub fn contains(&self, lat: f64, lon: f64, model: DistanceModel) -> bool {
+        match self {
+            Self::Circle(radius) => radius.contains(lat, lon, model),
+            Self::BBox(bbox) => bbox.contains(lat, lon),
+            Self::Polygon(region) => region.contains(lat, lon),
+        }
+    }
+}
+
+/// A named area with its own thresholds.
+#[derive(Debug, Clone)]
+pub struct Zone {
+    pub name: String,
+    pub area: Area,
+    pub min_magnitude: Option<f64>,
+    pub max_depth: Option<f64>,
+}
+
+impl Zone {
+    /// Whether an event lies in the zone and meets its thresholds.
+    #[must_use]
+    pub fn matches(&self, event: &Feature, model: DistanceModel) -> bool {
+        self.area
+            .contains(event.latitude(), event.longitude(), model)
+            && self
+                .min_magnitude
+                .is_none_or(|min| event.properties.mag.is_some_and(|m| m >= min))
+            && self.max_depth.is_none_or(|max| event.depth_km() <= max)
+    }
+}
+
+/// Zones read from one `--zones` file.
+#[derive(Debug, Clone)]
+pub struct ZoneFile(pub Vec<Zone>);
+
+/// Zone as written in a zones file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ZoneSpec {
+    name: String,
+    radius: Option<String>,
+    bbox: Option<String>,
+    region: Option<String>,
+    min_magnitude: Option<f64>,
+    max_depth: Option<f64>,
+}
+
+impl ZoneFile {
+    /// Load zones from a JSON file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or a zone is invalid.
+    pub fn load(path: &Path) -> Result<Self, SeismotailError> {
+        let input_error = |e: String| SeismotailError::Input(format!("{}: {e}", path.display()));
+        let text = std::fs::read_to_string(path).map_err(|e| input_error(e.to_string()))?;
+        let specs: Vec<ZoneSpec> =
+            serde_json::from_str(&text).map_err(|e| input_error(e.to_string()))?;
+        let base = path.parent().unwrap_or_else(|| Path::new(""));
+
+        let mut zones = Vec::with_capacity(specs.len());
+        for spec in specs {
+            let name = spec.name.clone();
+            let zone = spec
+                .into_zone(base)
+                .map_err(|e| input_error(format!("zone '{name}': {e}")))?;
+            zones.push(zone);
+        }
+        if zones.is_empty() {
+            return Err(input_error("no zones defined".into()));
+        }
+        Ok(Self(zones))
+    }
+}
+
+impl ZoneSpec {
+    fn into_zone(self, base: &Path) -> Result<Zone, String> {
+        let area = match (self.radius, self.bbox, self.region) {
+            (Some(radius), None, None) => Area::Circle(radius.parse()?),
+            (None, Some(bbox), None) => Area::BBox(bbox.parse()?),
+            (None, None, Some(region)) => {
+                let path = base.join(region);
+                let region = Region::load(&path.to_string_lossy()).map_err(|e| e.to_string())?;
+                Area::Polygon(region)
+            }
+            _ => return Err("needs exactly one of radius, bbox or region".into()),
+        };
+        Ok(Zone {
+            name: self.name,
+            area,
+            min_magnitude: self.min_magnitude,
+            max_depth: self.max_depth,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FeatureCollection;
+
+    #[test]
+    fn test_zone_thresholds() {
+        let dir = std::env::temp_dir().join(format!("seismotail-{}-zones", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("nz.wkt"),
+            "POLYGON ((165 -48, 180 -48, 180 -33, 165 -33, 165 -48))",
+        )
+        .unwrap();
+        let path = dir.join("zones.json");
+        std::fs::write(
+            &path,
+            r#"[
+                { "name": "bay-area", "radius": "37.77,-122.42,150", "min_magnitude": 2.5 },
+                { "name": "socal", "bbox": "32,-121,35,-114", "max_depth": 5 },
+                { "name": "alaska-deep", "bbox": "55,-170,65,-140", "max_depth": 50 },
+                { "name": "nz", "region": "nz.wkt" }
+            ]"#,
+        )
+        .unwrap();
+        let ZoneFile(zones) = ZoneFile::load(&path).unwrap();
+        assert_eq!(zones.len(), 4);
+
+        let feed: FeatureCollection =
+            serde_json::from_str(crate::test_support::SAMPLE_FEED).unwrap();
+        let matching = |id: &str| -> Vec<&str> {
+            let event = feed.features.iter().find(|e| e.id == id).unwrap();
+            zones
+                .iter()
+                .filter(|z| z.matches(event, DistanceModel::Wgs84))
+                .map(|z| z.name.as_str())
+                .collect()
+        };
+        // M2.67 at 1.9 km near The Geysers
+        assert_eq!(matching("nc75094411"), ["bay-area"]);
+        // M3.02 at 8.4 km near Ocotillo Wells is too deep for socal
+        assert!(matching("ci40812345").is_empty());
+        // M3.4 at 68.9 km in Cook Inlet is too deep
+        assert!(matching("ak0247xyz1").is_empty());
+
+        std::fs::write(
+            &path,
+            r#"[{ "name": "both", "radius": "0,0,10", "bbox": "0,0,1,1" }]"#,
+        )
+        .unwrap();
+        let err = ZoneFile::load(&path).unwrap_err().to_string();
+        assert!(err.contains("zone 'both'"), "{err}");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}